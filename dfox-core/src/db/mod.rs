@@ -1,9 +1,14 @@
-use crate::{errors::DbError, models::schema::TableSchema};
+use crate::{errors::DbError, models::schema::{ColumnSchema, ColumnStats, RoutineInfo, SessionInfo, TableSchema, TableStats}};
+use std::path::Path;
+
 use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use serde_json::Value;
+use sqlx::{query::Query, Database, Encode, Type};
 
 pub mod mysql;
 pub mod postgres;
+pub mod retry;
 pub mod sqlite;
 
 pub use mysql::MySqlClient;
@@ -11,13 +16,251 @@ pub use postgres::PostgresClient;
 
 #[async_trait]
 pub trait DbClient: Send + Sync {
-    async fn execute(&self, query: &str) -> Result<(), DbError>;
+    /// Runs a statement that returns no rows, returning how many rows it changed.
+    async fn execute(&self, query: &str) -> Result<u64, DbError>;
     async fn query(&self, query: &str) -> Result<Vec<Value>, DbError>;
+    /// Runs `query` with `params` bound to its placeholders (`$1`, `$2`, ... on Postgres,
+    /// `?` on MySQL and SQLite) instead of interpolating them into the SQL text.
+    async fn query_with_params(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError>;
+    /// Runs a statement that returns no rows with `params` bound like
+    /// [`DbClient::query_with_params`], returning how many rows it changed.
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError>;
+    /// Runs `query` like [`DbClient::query_with_params`], but inside a read-only transaction
+    /// that is rolled back afterwards, so it cannot change anything even through a function
+    /// it calls. Only PostgreSQL supports this.
+    async fn query_read_only(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError>;
     async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError>;
+    /// Every result set the statement returns, such as the several a MySQL `CALL` can
+    /// produce, laid out like [`DbClient::query_with_column_order`]. Result sets without
+    /// rows are left out.
+    async fn query_multi(&self, query: &str) -> Result<Vec<ResultSet>, DbError>;
     async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
     async fn list_databases(&self) -> Result<Vec<String>, DbError>;
     async fn list_tables(&self) -> Result<Vec<String>, DbError>;
+    /// Describes a table, which may be schema-qualified (`schema.table`). Unqualified names
+    /// are looked up in the default schema: `public` on Postgres, the current database on
+    /// MySQL and `main` on SQLite.
     async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
+    async fn describe_table_in_schema(&self, schema: &str, table_name: &str) -> Result<TableSchema, DbError>;
+    /// Whether a table or view of this name exists, without reading it. Names are looked
+    /// up the same way as in [`DbClient::describe_table`].
+    async fn table_exists(&self, table_name: &str) -> Result<bool, DbError>;
+    /// Names of the table's columns in table order, without the types, defaults and
+    /// indexes [`DbClient::describe_table`] also reads. Empty when the table doesn't exist.
+    async fn list_columns(&self, table_name: &str) -> Result<Vec<String>, DbError>;
+    /// Columns of the table's primary key in key order; empty when it has none.
+    async fn primary_key_columns(&self, table_name: &str) -> Result<Vec<String>, DbError>;
+    /// Roughly how many rows the table holds, from the server's statistics where it keeps
+    /// them. `None` when no estimate is available.
+    async fn estimated_row_count(&self, table_name: &str) -> Result<Option<u64>, DbError>;
+    /// Stored functions and procedures in the current schema, sorted by name.
+    async fn list_routines(&self) -> Result<Vec<RoutineInfo>, DbError>;
+    /// Other client sessions on the server with what they are running, longest running
+    /// first. Not supported on SQLite.
+    async fn list_active_queries(&self) -> Result<Vec<SessionInfo>, DbError>;
+    /// Terminates the session with the given [`SessionInfo::pid`], rolling back its open
+    /// transaction.
+    async fn kill_session(&self, pid: i64) -> Result<(), DbError>;
+    /// Returns a human readable server name and version, e.g. "PostgreSQL 16.2".
+    async fn server_version(&self) -> Result<String, DbError>;
+    /// Runs `query` under EXPLAIN ANALYZE and returns its plan with actual timings: plan
+    /// JSON on PostgreSQL, text rows on MySQL and SQLite. The statement really executes,
+    /// side effects included.
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError>;
+    /// Counts the table's rows and, per column, its NULLs and distinct values, plus
+    /// min/max of numeric columns. Scans the whole table in one aggregate query.
+    async fn table_stats(&self, table_name: &str) -> Result<TableStats, DbError>;
+    /// Sets how binary column values are encoded in subsequent query results.
+    fn set_binary_format(&mut self, format: BinaryFormat);
+    /// Sets whether queries run through this client keep their prepared statement on the
+    /// connection, so running the same query text again skips preparing it. On by
+    /// default, as in sqlx; turning it off keeps one-off queries from holding server
+    /// memory until they are evicted.
+    fn set_statement_cache(&mut self, enabled: bool);
+    /// Sets whether [`DbClient::list_databases`] includes the server's own databases
+    /// (`postgres` and templates, MySQL's `mysql`, `sys` and schema databases). Hidden
+    /// by default.
+    fn set_show_system_databases(&mut self, show: bool);
+    /// Opens a dedicated connection subscribed to `channel` with LISTEN. Only PostgreSQL
+    /// supports notifications.
+    async fn listen(&self, channel: &str) -> Result<Box<dyn Listener>, DbError>;
+    /// Streams the rows of `query` to `on_row` one at a time, as JSON objects in column
+    /// order, without holding the result in memory. Returns the number of rows streamed.
+    /// Clients that can't stream fetch the whole result first.
+    async fn stream_rows(&self, query: &str, on_row: &mut RowCallback<'_>) -> Result<u64, DbError> {
+        let rows = self.query(query).await?;
+        let count = rows.len() as u64;
+        rows.into_iter().try_for_each(on_row)?;
+        Ok(count)
+    }
+    /// Streams the result of `query` as CSV with a header line, produced by the server's
+    /// `COPY ... TO STDOUT`, to `on_chunk` as it arrives. Much faster than
+    /// [`DbClient::stream_rows`] for large results. Only PostgreSQL supports this.
+    async fn copy_out_csv(&self, _query: &str, _on_chunk: &mut ChunkCallback<'_>) -> Result<(), DbError> {
+        Err(DbError::General("COPY is only supported on PostgreSQL".into()))
+    }
+    /// Loads the CSV file at `path` into `table` with `COPY ... FROM STDIN`, filling
+    /// `columns` in file order, or every column in table order when empty. With `header`
+    /// the file's first line is skipped. A malformed row fails the whole load, naming its
+    /// line. Returns the number of rows loaded. Only PostgreSQL supports this.
+    async fn copy_in_csv(&self, table: &str, columns: &[String], path: &Path, header: bool) -> Result<u64, DbError>;
+    /// Another handle on the same connection pool, for long-running work that should not
+    /// keep the connection list locked.
+    fn clone_client(&self) -> Box<dyn DbClient + Send + Sync>;
+    /// Closes the pool's connections, waiting for them to shut down. Handles from
+    /// [`DbClient::clone_client`] share the pool and are closed too.
+    async fn close(&self);
+}
+
+pub type RowCallback<'a> = dyn FnMut(Value) -> Result<(), DbError> + Send + 'a;
+
+pub type ChunkCallback<'a> = dyn FnMut(&[u8]) -> Result<(), DbError> + Send + 'a;
+
+/// One result set's column names and rows, as [`DbClient::query_with_column_order`] returns.
+pub type ResultSet = (Vec<String>, Vec<Vec<String>>);
+
+/// Text encoding used for binary column values (BYTEA, BLOB, ...).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinaryFormat {
+    #[default]
+    Base64,
+    /// Postgres style `\x` prefixed hex, e.g. `\xdeadbeef`.
+    Hex,
+}
+
+impl BinaryFormat {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            BinaryFormat::Base64 => BASE64.encode(bytes),
+            BinaryFormat::Hex => {
+                let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                format!("\\x{}", hex)
+            }
+        }
+    }
+
+    /// Bytes of a text column as the text they spell, or encoded like binary when they
+    /// aren't valid UTF-8 (e.g. latin1 data stored in a utf8 column).
+    pub fn encode_text(&self, bytes: &[u8]) -> String {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => text.to_string(),
+            Err(_) => self.encode(bytes),
+        }
+    }
+}
+
+/// Splits `schema.table` into its parts; names without a schema get `default_schema`.
+pub fn split_qualified_name<'a>(name: &'a str, default_schema: &'a str) -> (&'a str, &'a str) {
+    match name.split_once('.') {
+        Some((schema, table)) if !schema.is_empty() && !table.is_empty() => (schema, table),
+        _ => (default_schema, name),
+    }
+}
+
+/// Binds a JSON value to the next placeholder as the closest SQL type. Arrays and
+/// objects are bound as their JSON text.
+pub(crate) fn bind_json_value<'q, DB>(
+    query: Query<'q, DB, <DB as Database>::Arguments<'q>>,
+    value: &Value,
+) -> Query<'q, DB, <DB as Database>::Arguments<'q>>
+where
+    DB: Database,
+    bool: Encode<'q, DB> + Type<DB>,
+    i64: Encode<'q, DB> + Type<DB>,
+    f64: Encode<'q, DB> + Type<DB>,
+    String: Encode<'q, DB> + Type<DB>,
+    Option<String>: Encode<'q, DB> + Type<DB>,
+{
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+// Postgres types without an equality operator, which COUNT(DISTINCT ...) rejects
+const NON_COMPARABLE_TYPES: [&str; 2] = ["json", "xml"];
+const NUMERIC_TYPES: [&str; 18] = [
+    "smallint", "integer", "int", "bigint", "tinyint", "mediumint", "int2", "int4", "int8",
+    "serial", "bigserial", "numeric", "decimal", "real", "double", "float", "float4", "float8",
+];
+
+/// The declared type without its size or modifiers, e.g. `int` for `int(11) unsigned`.
+fn base_type(data_type: &str) -> String {
+    data_type
+        .split(|c: char| c == '(' || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Quotes an identifier the standard way, as Postgres and SQLite expect.
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes `schema.table` part by part, or `name` as a whole when it has no schema.
+pub(crate) fn quote_qualified_name(name: &str, quote: fn(&str) -> String) -> String {
+    match name.split_once('.') {
+        Some((schema, table)) if !schema.is_empty() && !table.is_empty() => {
+            format!("{}.{}", quote(schema), quote(table))
+        }
+        _ => quote(name),
+    }
+}
+
+/// The aggregate query behind [`DbClient::table_stats`]: `row_count`, then `nulls_i`,
+/// `distinct_i`, `min_i` and `max_i` for the i-th column.
+pub(crate) fn table_stats_query(table_name: &str, columns: &[ColumnSchema], quote: fn(&str) -> String) -> String {
+    let table = quote_qualified_name(table_name, quote);
+    let mut aggregates = vec!["COUNT(*) AS row_count".to_string()];
+    for (i, column) in columns.iter().enumerate() {
+        let name = quote(&column.name);
+        let base = base_type(&column.data_type);
+        aggregates.push(format!("COUNT(*) - COUNT({}) AS nulls_{}", name, i));
+        if !NON_COMPARABLE_TYPES.contains(&base.as_str()) {
+            aggregates.push(format!("COUNT(DISTINCT {}) AS distinct_{}", name, i));
+        }
+        if NUMERIC_TYPES.contains(&base.as_str()) {
+            aggregates.push(format!("MIN({}) AS min_{}, MAX({}) AS max_{}", name, i, name, i));
+        }
+    }
+    format!("SELECT {} FROM {}", aggregates.join(", "), table)
+}
+
+/// Reads the single row returned by [`table_stats_query`].
+pub(crate) fn parse_table_stats(columns: &[ColumnSchema], row: &Value) -> TableStats {
+    // Counts come back as numbers, or as decimal text from some MySQL expressions
+    let count = |key: String| match row.get(&key) {
+        Some(Value::Number(n)) => n.as_u64(),
+        Some(Value::String(s)) => s.parse().ok(),
+        _ => None,
+    };
+    let text = |key: String| match row.get(&key) {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(other) => Some(other.to_string()),
+    };
+    TableStats {
+        row_count: count("row_count".to_string()).unwrap_or(0),
+        columns: columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| ColumnStats {
+                name: column.name.clone(),
+                data_type: column.data_type.clone(),
+                null_count: count(format!("nulls_{}", i)).unwrap_or(0),
+                distinct_count: count(format!("distinct_{}", i)),
+                min: text(format!("min_{}", i)),
+                max: text(format!("max_{}", i)),
+            })
+            .collect(),
+    }
 }
 
 #[async_trait]
@@ -26,3 +269,58 @@ pub trait Transaction {
     async fn commit_transaction(self: Box<Self>) -> Result<(), DbError>;
     async fn rollback_transaction(self: Box<Self>) -> Result<(), DbError>;
 }
+
+#[async_trait]
+pub trait Listener: Send {
+    /// Waits for the next notification, returning its channel and payload.
+    async fn recv(&mut self) -> Result<(String, String), DbError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_qualified_name() {
+        assert_eq!(split_qualified_name("users", "public"), ("public", "users"));
+        assert_eq!(split_qualified_name("audit.users", "public"), ("audit", "users"));
+        assert_eq!(split_qualified_name(".users", "public"), ("public", ".users"));
+    }
+
+    #[test]
+    fn test_binary_format_encode() {
+        let bytes = [0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(BinaryFormat::Base64.encode(&bytes), "3q2+7w==");
+        assert_eq!(BinaryFormat::Hex.encode(&bytes), "\\xdeadbeef");
+        assert_eq!(BinaryFormat::Hex.encode(&[]), "\\x");
+        assert_eq!(BinaryFormat::Hex.encode_text("Grüße".as_bytes()), "Grüße");
+        assert_eq!(BinaryFormat::Hex.encode_text(&[b'G', 0xfc, b'e']), "\\x47fc65");
+    }
+
+    #[test]
+    fn test_table_stats_query_and_parse() {
+        let column = |name: &str, data_type: &str| ColumnSchema {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            is_nullable: true,
+            default: None,
+        };
+        let columns = vec![column("id", "int(11) unsigned"), column("payload", "json")];
+        assert_eq!(
+            table_stats_query("audit.events", &columns, quote_identifier),
+            "SELECT COUNT(*) AS row_count, COUNT(*) - COUNT(\"id\") AS nulls_0, \
+             COUNT(DISTINCT \"id\") AS distinct_0, MIN(\"id\") AS min_0, MAX(\"id\") AS max_0, \
+             COUNT(*) - COUNT(\"payload\") AS nulls_1 FROM \"audit\".\"events\""
+        );
+
+        let row = serde_json::json!({
+            "row_count": 3, "nulls_0": 0, "distinct_0": "3", "min_0": 1, "max_0": 9, "nulls_1": 2
+        });
+        let stats = parse_table_stats(&columns, &row);
+        assert_eq!(stats.row_count, 3);
+        assert_eq!(stats.columns[0].distinct_count, Some(3));
+        assert_eq!((stats.columns[0].min.as_deref(), stats.columns[0].max.as_deref()), (Some("1"), Some("9")));
+        assert_eq!((stats.columns[1].null_count, stats.columns[1].distinct_count), (2, None));
+        assert_eq!(stats.columns[1].min, None);
+    }
+}
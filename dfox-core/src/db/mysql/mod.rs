@@ -1,110 +1,206 @@
 mod types;
-pub use types::ColumnType;
+pub use types::{is_boolean_type, ColumnType};
+
+use std::path::Path;
 
 use async_trait::async_trait;
+use futures_util::TryStreamExt;
 use serde_json::Value;
-use sqlx::{mysql::MySqlPoolOptions, MySqlPool, Row, Column, TypeInfo};
+use sqlx::{mysql::{MySqlPoolOptions, MySqlRow}, Either, MySqlPool, Row, Column, TypeInfo};
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::schema::{ColumnSchema, RoutineInfo, SessionInfo, TableSchema, TableStats},
+};
+
+use super::{
+    bind_json_value, parse_table_stats,
+    retry::{retry, RetryPolicy},
+    table_stats_query, BinaryFormat, DbClient, Listener, ResultSet, RowCallback, Transaction,
 };
 
-use super::{DbClient, Transaction};
+/// Databases MySQL creates for its own bookkeeping.
+const SYSTEM_DATABASES: [&str; 4] = ["information_schema", "mysql", "performance_schema", "sys"];
 
+fn is_system_database(name: &str) -> bool {
+    SYSTEM_DATABASES.iter().any(|system| system.eq_ignore_ascii_case(name))
+}
+
+#[derive(Clone)]
 pub struct MySqlClient {
     pub pool: MySqlPool,
+    binary_format: BinaryFormat,
+    cache_statements: bool,
+    show_system_databases: bool,
 }
 
 impl MySqlClient {
     pub async fn connect(database_url: &str) -> Result<Self, DbError> {
-        let pool = MySqlPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
+        Self::connect_with_retry(database_url, &RetryPolicy::default()).await
+    }
+
+    /// Connects like [`Self::connect`], retrying transient failures according to `policy`.
+    pub async fn connect_with_retry(database_url: &str, policy: &RetryPolicy) -> Result<Self, DbError> {
+        let pool = retry(policy, || {
+            MySqlPoolOptions::new()
+                .max_connections(5)
+                .connect(database_url)
+        })
+        .await
+        .map_err(DbError::from_connect)?;
+
+        Ok(Self {
+            pool,
+            binary_format: BinaryFormat::default(),
+            cache_statements: true,
+            show_system_databases: false,
+        })
+    }
+
+    /// Converts a row to a JSON object keyed by column name, in result column order.
+    fn row_to_json(&self, row: &MySqlRow) -> Value {
+        let json_map = row
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let column_name = column.name().to_string();
+                let column_type = ColumnType::from_type_name(column.type_info().name());
+                let value = column_type.to_json_value(row, i, self.binary_format);
+                (column_name, value)
+            })
+            .collect();
+
+        Value::Object(json_map)
+    }
+
+    /// Column names and text values of one result set, `None` when it has no rows.
+    fn result_set(&self, rows: &[MySqlRow]) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+        let column_names = rows
+            .first()?
+            .columns()
+            .iter()
+            .map(|col| col.name().to_string())
+            .collect();
+
+        let data_rows = rows
+            .iter()
+            .map(|row| {
+                row.columns()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, column)| {
+                        let column_type = ColumnType::from_type_name(column.type_info().name());
+                        let value = column_type.to_json_value(row, i, self.binary_format);
+                        match value {
+                            Value::Null => "NULL".to_string(),
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some((column_names, data_rows))
+    }
+
+    /// Runs `DESCRIBE` on an already quoted table reference.
+    async fn describe(&self, target: &str, table_name: &str) -> Result<TableSchema, DbError> {
+        let query = format!("DESCRIBE {}", target);
+        let rows = sqlx::query(&query)
+            .fetch_all(&self.pool)
             .await
-            .map_err(|e| DbError::Connection(e.to_string()))?;
+            .map_err(DbError::from_sqlx)?;
+
+        let columns = rows
+            .iter()
+            .map(|row| ColumnSchema {
+                name: text_column(row, "Field").unwrap_or_default(),
+                data_type: text_column(row, "Type").unwrap_or_default(),
+                is_nullable: text_column(row, "Null").as_deref() == Some("YES"),
+                default: text_column(row, "Default"),
+            })
+            .collect();
 
-        Ok(Self { pool })
+        Ok(TableSchema {
+            table_name: table_name.to_string(),
+            columns,
+            indexes: Vec::new(),
+        })
     }
 }
 
 #[async_trait]
 impl DbClient for MySqlClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
+            .persistent(self.cache_statements)
             .execute(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
-        Ok(())
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
     }
 
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
         let rows = sqlx::query(query)
+            .persistent(self.cache_statements)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
 
-        let results = rows
+    async fn query_with_params(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError> {
+        let rows = params
             .iter()
-            .map(|row| {
-                let json_map = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, column)| {
-                        let column_name = column.name().to_string();
-                        let column_type = ColumnType::from_type_name(column.type_info().name());
-                        let value = column_type.to_json_value(row, i);
-                        (column_name, value)
-                    })
-                    .collect();
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
 
-                Value::Object(json_map)
-            })
-            .collect();
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
 
-        Ok(results)
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError> {
+        let result = params
+            .iter()
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
     }
 
-    async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError> {
-        // TODO: Implement proper column order preservation for MySQL
-        let rows = self.query(query).await?;
-        
-        if rows.is_empty() {
-            return Ok((Vec::new(), Vec::new()));
-        }
+    async fn query_read_only(&self, _query: &str, _params: &[Value]) -> Result<Vec<Value>, DbError> {
+        Err(DbError::General("Read-only transactions are only supported on PostgreSQL".into()))
+    }
 
-        // Extract column names from first row (alphabetical order for now)
-        let column_names: Vec<String> = if let Value::Object(map) = &rows[0] {
-            map.keys().cloned().collect()
-        } else {
-            Vec::new()
-        };
+    async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError> {
+        let rows = sqlx::query(query)
+            .persistent(self.cache_statements)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
 
-        // Convert rows to string vectors
-        let data_rows: Vec<Vec<String>> = rows
-            .into_iter()
-            .map(|row| {
-                if let Value::Object(map) = row {
-                    column_names
-                        .iter()
-                        .map(|col| {
-                            map.get(col)
-                                .map(|v| match v {
-                                    Value::Null => "NULL".to_string(),
-                                    Value::String(s) => s.clone(),
-                                    other => other.to_string(),
-                                })
-                                .unwrap_or_else(|| "NULL".to_string())
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            })
-            .collect();
+        Ok(self.result_set(&rows).unwrap_or_default())
+    }
 
-        Ok((column_names, data_rows))
+    async fn query_multi(&self, query: &str) -> Result<Vec<ResultSet>, DbError> {
+        // The steps show where one result set ends and the next begins
+        let mut stream = sqlx::raw_sql(query).fetch_many(&self.pool);
+        let mut result_sets = Vec::new();
+        let mut rows = Vec::new();
+        while let Some(step) = stream.try_next().await.map_err(DbError::from_sqlx)? {
+            match step {
+                // Every result set ends with a status, and so does the CALL itself
+                Either::Left(_) => result_sets.extend(self.result_set(&std::mem::take(&mut rows))),
+                Either::Right(row) => rows.push(row),
+            }
+        }
+        result_sets.extend(self.result_set(&rows));
+        Ok(result_sets)
     }
 
     async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
@@ -121,11 +217,12 @@ impl DbClient for MySqlClient {
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let databases: Vec<String> = rows
             .iter()
             .map(|row| row.try_get::<String, _>(0).unwrap_or_default())
+            .filter(|name| self.show_system_databases || !is_system_database(name))
             .collect();
 
         Ok(databases)
@@ -136,7 +233,7 @@ impl DbClient for MySqlClient {
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let tables = rows
             .iter()
@@ -146,31 +243,227 @@ impl DbClient for MySqlClient {
         Ok(tables)
     }
 
-    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!("DESCRIBE {}", table_name);
-        let rows = sqlx::query(&query)
+    async fn table_exists(&self, table_name: &str) -> Result<bool, DbError> {
+        let (schema, table_name) = match table_name.split_once('.') {
+            Some((schema, table_name)) => (Some(schema), table_name),
+            None => (None, table_name),
+        };
+        let query = r#"
+            SELECT COUNT(*)
+            FROM information_schema.TABLES
+            WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?
+        "#;
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(schema)
+            .bind(table_name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(count > 0)
+    }
+
+    async fn list_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let (schema, table_name) = match table_name.split_once('.') {
+            Some((schema, table_name)) => (Some(schema), table_name),
+            None => (None, table_name),
+        };
+        let query = r#"
+            SELECT COLUMN_NAME
+            FROM information_schema.COLUMNS
+            WHERE TABLE_SCHEMA = COALESCE(?, DATABASE()) AND TABLE_NAME = ?
+            ORDER BY ORDINAL_POSITION
+        "#;
+        let rows = sqlx::query(query)
+            .bind(schema)
+            .bind(table_name)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
-        let columns = rows
+        Ok(rows
             .iter()
-            .map(|row| ColumnSchema {
-                name: row.try_get("Field").unwrap(),
-                data_type: row.try_get("Type").unwrap(),
-                is_nullable: row.try_get::<String, _>("Null").unwrap() == "YES",
-                default: row.try_get("Default").ok(),
+            .filter_map(|row| text_column(row, "COLUMN_NAME"))
+            .collect())
+    }
+
+    async fn primary_key_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let query = r#"
+            SELECT COLUMN_NAME
+            FROM information_schema.KEY_COLUMN_USAGE
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND CONSTRAINT_NAME = 'PRIMARY'
+            ORDER BY ORDINAL_POSITION
+        "#;
+        let rows = sqlx::query(query)
+            .bind(table_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows
+            .iter()
+            .filter_map(|row| text_column(row, "COLUMN_NAME"))
+            .collect())
+    }
+
+    async fn estimated_row_count(&self, table_name: &str) -> Result<Option<u64>, DbError> {
+        // InnoDB's TABLE_ROWS is a sampled estimate, not an exact count
+        let query = r#"
+            SELECT TABLE_ROWS
+            FROM information_schema.TABLES
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+        "#;
+        let estimate: Option<Option<u64>> = sqlx::query_scalar(query)
+            .bind(table_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(estimate.flatten())
+    }
+
+    async fn list_routines(&self) -> Result<Vec<RoutineInfo>, DbError> {
+        let query = r#"
+            SELECT ROUTINE_NAME, ROUTINE_TYPE, DTD_IDENTIFIER
+            FROM information_schema.routines
+            WHERE ROUTINE_SCHEMA = DATABASE()
+            ORDER BY ROUTINE_NAME
+        "#;
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        let routines = rows
+            .iter()
+            .map(|row| RoutineInfo {
+                name: text_column(row, "ROUTINE_NAME").unwrap_or_default(),
+                routine_type: text_column(row, "ROUTINE_TYPE").unwrap_or_default(),
+                return_type: text_column(row, "DTD_IDENTIFIER").filter(|t| !t.is_empty()),
             })
             .collect();
 
-        Ok(TableSchema {
-            table_name: table_name.to_string(),
-            columns,
-            indexes: Vec::new(),
-        })
+        Ok(routines)
+    }
+
+    async fn list_active_queries(&self) -> Result<Vec<SessionInfo>, DbError> {
+        let query = r#"
+            SELECT CAST(ID AS SIGNED) AS id, USER, COMMAND, INFO, CAST(TIME AS SIGNED) AS seconds
+            FROM information_schema.PROCESSLIST
+            WHERE ID <> CONNECTION_ID()
+            ORDER BY TIME DESC
+        "#;
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SessionInfo {
+                pid: row.try_get::<i64, _>("id").unwrap_or_default(),
+                user: row.try_get::<Option<String>, _>("USER").ok().flatten(),
+                state: row.try_get::<Option<String>, _>("COMMAND").ok().flatten(),
+                query: row.try_get::<Option<String>, _>("INFO").ok().flatten(),
+                seconds: row.try_get::<Option<i64>, _>("seconds").ok().flatten(),
+            })
+            .collect())
+    }
+
+    async fn kill_session(&self, pid: i64) -> Result<(), DbError> {
+        // KILL takes no placeholders; pid is an integer so it is safe to inline
+        sqlx::query(&format!("KILL {}", pid))
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(())
+    }
+
+    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
+        match table_name.split_once('.') {
+            Some((schema, table_name)) => self.describe_table_in_schema(schema, table_name).await,
+            None => self.describe(&quote_identifier(table_name), table_name).await,
+        }
+    }
+
+    async fn describe_table_in_schema(&self, schema: &str, table_name: &str) -> Result<TableSchema, DbError> {
+        let target = format!("{}.{}", quote_identifier(schema), quote_identifier(table_name));
+        self.describe(&target, table_name).await
+    }
+
+    async fn server_version(&self) -> Result<String, DbError> {
+        let version: String = sqlx::query_scalar("SELECT VERSION()")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(format!("MySQL {}", version))
+    }
+
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError> {
+        let query = query.trim().trim_end_matches(';');
+        self.query(&format!("EXPLAIN ANALYZE {}", query)).await
+    }
+
+    async fn table_stats(&self, table_name: &str) -> Result<TableStats, DbError> {
+        let schema = self.describe_table(table_name).await?;
+        let query = table_stats_query(table_name, &schema.columns, quote_identifier);
+        let rows = self.query(&query).await?;
+        Ok(parse_table_stats(&schema.columns, rows.first().unwrap_or(&Value::Null)))
+    }
+
+    fn set_binary_format(&mut self, format: BinaryFormat) {
+        self.binary_format = format;
+    }
+
+    fn set_statement_cache(&mut self, enabled: bool) {
+        self.cache_statements = enabled;
+    }
+
+    fn set_show_system_databases(&mut self, show: bool) {
+        self.show_system_databases = show;
+    }
+
+    async fn stream_rows(&self, query: &str, on_row: &mut RowCallback<'_>) -> Result<u64, DbError> {
+        let mut rows = sqlx::query(query).persistent(self.cache_statements).fetch(&self.pool);
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await.map_err(DbError::from_sqlx)? {
+            on_row(self.row_to_json(&row))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn copy_in_csv(&self, _table: &str, _columns: &[String], _path: &Path, _header: bool) -> Result<u64, DbError> {
+        Err(DbError::General("COPY is only supported on PostgreSQL".into()))
+    }
+
+    fn clone_client(&self) -> Box<dyn DbClient + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn listen(&self, _channel: &str) -> Result<Box<dyn Listener>, DbError> {
+        Err(DbError::General("LISTEN/NOTIFY is only supported on PostgreSQL".into()))
     }
 }
 
+/// Backtick-quotes an identifier, doubling any backticks inside it.
+fn quote_identifier(name: &str) -> String {
+    format!("`{}`", name.replace('`', "``"))
+}
+
+/// Reads a textual metadata column. Depending on the server version, `DESCRIBE`
+/// reports some of its columns as binary strings, so fall back to decoding bytes.
+fn text_column(row: &MySqlRow, column: &str) -> Option<String> {
+    row.try_get::<String, _>(column).ok().or_else(|| {
+        row.try_get::<Vec<u8>, _>(column)
+            .ok()
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    })
+}
+
 pub struct MySqlTransaction<'a> {
     tx: sqlx::Transaction<'a, sqlx::MySql>,
 }
@@ -198,4 +491,26 @@ impl<'a> Transaction for MySqlTransaction<'a> {
             .await
             .map_err(|e| DbError::Transaction(e.to_string()))
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_system_database() {
+        assert!(is_system_database("information_schema"));
+        assert!(is_system_database("PERFORMANCE_SCHEMA"));
+        assert!(!is_system_database("shop"));
+        assert!(!is_system_database("mysql_app"));
+    }
+
+    #[test]
+    fn test_is_boolean_type() {
+        assert!(is_boolean_type("tinyint(1)"));
+        assert!(is_boolean_type("TINYINT(1) UNSIGNED"));
+        assert!(is_boolean_type("boolean"));
+        assert!(!is_boolean_type("tinyint(4)"));
+        assert!(!is_boolean_type("tinyint"));
+    }
+}
@@ -1,8 +1,8 @@
 use serde_json::Value;
 use sqlx::{Row, mysql::MySqlRow};
 use chrono::{NaiveDate, NaiveTime, NaiveDateTime};
-use base64::engine::general_purpose::STANDARD as BASE64;
-use base64::Engine;
+
+use crate::db::BinaryFormat;
 
 #[derive(Debug)]
 pub enum ColumnType {
@@ -53,6 +53,16 @@ pub enum ColumnType {
     Unknown,
 }
 
+/// Whether a column type as `DESCRIBE` reports it holds booleans. `BOOL` and `BOOLEAN`
+/// are aliases MySQL stores as `TINYINT(1)`, which is what it reports for them.
+pub fn is_boolean_type(column_type: &str) -> bool {
+    let column_type = column_type.to_lowercase();
+    matches!(
+        column_type.split_whitespace().next().unwrap_or_default(),
+        "tinyint(1)" | "bool" | "boolean"
+    )
+}
+
 impl ColumnType {
     pub fn from_type_name(type_name: &str) -> Self {
         match type_name.to_uppercase().as_str() {
@@ -92,8 +102,8 @@ impl ColumnType {
             // JSON type
             "JSON" => ColumnType::Json,
             
-            // Boolean type
-            "BOOLEAN" | "BOOL" => ColumnType::Boolean,
+            // Boolean type; sqlx names TINYINT(1) columns BOOLEAN
+            "BOOLEAN" | "BOOL" | "TINYINT(1)" => ColumnType::Boolean,
             
             // Enum and Set types
             "ENUM" => ColumnType::Enum,
@@ -103,7 +113,7 @@ impl ColumnType {
         }
     }
 
-    pub fn to_json_value<'a>(&self, row: &'a MySqlRow, index: usize) -> Value {
+    pub fn to_json_value(&self, row: &MySqlRow, index: usize, binary_format: BinaryFormat) -> Value {
         match self {
             ColumnType::DateTime | ColumnType::Timestamp => match row.try_get::<NaiveDateTime, _>(index) {
                 Ok(timestamp) => Value::String(timestamp.to_string()),
@@ -142,16 +152,16 @@ impl ColumnType {
                 Err(_) => Value::Null,
             },
             ColumnType::Binary | ColumnType::Varbinary | ColumnType::TinyBlob | ColumnType::Blob | ColumnType::MediumBlob | ColumnType::LongBlob => match row.try_get::<Vec<u8>, _>(index) {
-                Ok(val) => Value::String(BASE64.encode(val)),
+                Ok(val) => Value::String(binary_format.encode(&val)),
                 Err(_) => Value::Null,
             },
             ColumnType::Char | ColumnType::Varchar | ColumnType::TinyText | ColumnType::Text | ColumnType::MediumText | ColumnType::LongText => match row.try_get::<String, _>(index) {
                 Ok(text) => Value::String(text),
-                Err(_) => Value::Null,
+                Err(_) => text_bytes(row, index, binary_format),
             },
             ColumnType::Enum | ColumnType::Set => match row.try_get::<String, _>(index) {
                 Ok(val) => Value::String(val),
-                Err(_) => Value::Null,
+                Err(_) => text_bytes(row, index, binary_format),
             },
             ColumnType::Year => match row.try_get::<i32, _>(index) {
                 Ok(year) => Value::Number(year.into()),
@@ -159,8 +169,17 @@ impl ColumnType {
             },
             ColumnType::Unknown => match row.try_get::<String, _>(index) {
                 Ok(val) => Value::String(val),
-                Err(_) => Value::Null,
+                Err(_) => text_bytes(row, index, binary_format),
             },
         }
     }
-} 
\ No newline at end of file
+}
+
+/// A text column whose value didn't decode as UTF-8, read as raw bytes instead of
+/// being dropped; NULL only when the value really is NULL.
+fn text_bytes(row: &MySqlRow, index: usize, binary_format: BinaryFormat) -> Value {
+    match row.try_get::<Vec<u8>, _>(index) {
+        Ok(bytes) => Value::String(binary_format.encode_text(&bytes)),
+        Err(_) => Value::Null,
+    }
+}
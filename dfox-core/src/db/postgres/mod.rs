@@ -1,74 +1,149 @@
+pub mod notices;
 mod types;
 pub use types::ColumnType;
 
+use std::path::Path;
+
 use async_trait::async_trait;
+use futures_util::{Stream, TryStreamExt};
 use serde_json::Value;
 use indexmap::IndexMap;
-use sqlx::{postgres::PgPoolOptions, PgPool, Row, Column, TypeInfo};
+use sqlx::{postgres::{PgDatabaseError, PgListener, PgPoolCopyExt, PgPoolOptions}, PgPool, Row, Column, TypeInfo};
+use tokio::io::AsyncReadExt;
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::schema::{ColumnSchema, RoutineInfo, SessionInfo, TableSchema, TableStats},
 };
 
-use super::{DbClient, Transaction};
+use super::{
+    bind_json_value, parse_table_stats, quote_identifier, quote_qualified_name,
+    retry::{retry, RetryPolicy},
+    split_qualified_name, table_stats_query, BinaryFormat, ChunkCallback, DbClient, Listener, ResultSet, RowCallback, Transaction,
+};
 
+#[derive(Clone)]
 pub struct PostgresClient {
     pub pool: PgPool,
+    binary_format: BinaryFormat,
+    cache_statements: bool,
+    show_system_databases: bool,
 }
 
 impl PostgresClient {
     pub async fn connect(database_url: &str) -> Result<Self, DbError> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
-            .await
-            .map_err(|e| DbError::Connection(e.to_string()))?;
+        Self::connect_with_retry(database_url, &RetryPolicy::default()).await
+    }
 
-        Ok(Self { pool })
+    /// Connects like [`Self::connect`], retrying transient failures according to `policy`.
+    pub async fn connect_with_retry(database_url: &str, policy: &RetryPolicy) -> Result<Self, DbError> {
+        let pool = retry(policy, || {
+            PgPoolOptions::new()
+                .max_connections(5)
+                .connect(database_url)
+        })
+        .await
+        .map_err(DbError::from_connect)?;
+
+        Ok(Self {
+            pool,
+            binary_format: BinaryFormat::default(),
+            cache_statements: true,
+            show_system_databases: false,
+        })
+    }
+
+    /// Converts a row to a JSON object keyed by column name, in result column order.
+    fn row_to_json(&self, row: &sqlx::postgres::PgRow) -> Value {
+        let mut json_map = IndexMap::new();
+        // Insert columns in the order they appear in the SQL result
+        for (i, column) in row.columns().iter().enumerate() {
+            let column_name = column.name().to_string();
+            let column_type = ColumnType::from_type_name(column.type_info().name());
+            let value = column_type.to_json_value(row, i, self.binary_format);
+            json_map.insert(column_name, value);
+        }
+
+        Value::Object(json_map.into_iter().collect())
+    }
+
+    /// Runs `COPY (query) TO STDOUT` in CSV format with a header line and streams the
+    /// output in the chunks the server sends, without decoding rows.
+    pub async fn copy_out(
+        &self,
+        query: &str,
+    ) -> Result<impl Stream<Item = Result<impl AsRef<[u8]>, DbError>> + Send + Unpin, DbError> {
+        let statement = format!("COPY ({}) TO STDOUT WITH (FORMAT csv, HEADER)", query);
+        let chunks = self.pool.copy_out_raw(&statement).await.map_err(DbError::from_sqlx)?;
+        Ok(chunks.map_err(DbError::from_sqlx))
     }
 }
 
 #[async_trait]
 impl DbClient for PostgresClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
+            .persistent(self.cache_statements)
             .execute(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
-        Ok(())
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
     }
 
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
         let rows = sqlx::query(query)
+            .persistent(self.cache_statements)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
 
-        let results = rows
+    async fn query_with_params(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError> {
+        let rows = params
             .iter()
-            .map(|row| {
-                let mut json_map = IndexMap::new();
-                // Insert columns in the order they appear in the SQL result
-                for (i, column) in row.columns().iter().enumerate() {
-                    let column_name = column.name().to_string();
-                    let column_type = ColumnType::from_type_name(column.type_info().name());
-                    let value = column_type.to_json_value(row, i);
-                    json_map.insert(column_name, value);
-                }
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
 
-                Value::Object(json_map.into_iter().collect())
-            })
-            .collect();
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
+
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError> {
+        let result = params
+            .iter()
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
+    }
+
+    async fn query_read_only(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError> {
+        let mut tx = self.pool.begin().await.map_err(DbError::from_sqlx)?;
+        sqlx::query("SET TRANSACTION READ ONLY")
+            .execute(&mut *tx)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        let rows = params
+            .iter()
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        tx.rollback().await.map_err(DbError::from_sqlx)?;
 
-        Ok(results)
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
     }
 
     async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError> {
         let rows = sqlx::query(query)
+            .persistent(self.cache_statements)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         if rows.is_empty() {
             return Ok((Vec::new(), Vec::new()));
@@ -90,7 +165,7 @@ impl DbClient for PostgresClient {
                     .enumerate()
                     .map(|(i, column)| {
                         let column_type = ColumnType::from_type_name(column.type_info().name());
-                        let value = column_type.to_json_value(row, i);
+                        let value = column_type.to_json_value(row, i, self.binary_format);
                         match value {
                             Value::Null => "NULL".to_string(),
                             Value::String(s) => s,
@@ -104,6 +179,12 @@ impl DbClient for PostgresClient {
         Ok((column_names, data_rows))
     }
 
+    async fn query_multi(&self, query: &str) -> Result<Vec<ResultSet>, DbError> {
+        // Only MySQL procedures return more than one result set
+        let (column_names, data_rows) = self.query_with_column_order(query).await?;
+        Ok(if column_names.is_empty() { Vec::new() } else { vec![(column_names, data_rows)] })
+    }
+
     async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
         let tx = self
             .pool
@@ -114,16 +195,19 @@ impl DbClient for PostgresClient {
     }
 
     async fn list_databases(&self) -> Result<Vec<String>, DbError> {
+        // The postgres maintenance database and templates count as system databases
         let query = r#"
             SELECT datname
             FROM pg_database
-            WHERE datistemplate = false
+            WHERE datallowconn AND ($1 OR (NOT datistemplate AND datname <> 'postgres'))
+            ORDER BY datname
         "#;
 
         let rows = sqlx::query(query)
+            .bind(self.show_system_databases)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let databases: Vec<String> = rows
             .iter()
@@ -142,7 +226,7 @@ impl DbClient for PostgresClient {
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let tables = rows
             .iter()
@@ -152,28 +236,167 @@ impl DbClient for PostgresClient {
         Ok(tables)
     }
 
-    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!(
-            r#"
-            SELECT column_name, data_type, is_nullable, column_default
+    async fn table_exists(&self, table_name: &str) -> Result<bool, DbError> {
+        // to_regclass returns NULL instead of failing like a ::regclass cast
+        sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(table_name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)
+    }
+
+    async fn list_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let (schema, table_name) = split_qualified_name(table_name, "public");
+        let query = r#"
+            SELECT column_name::text
             FROM information_schema.columns
-            WHERE table_name = '{}'
+            WHERE table_schema = $1 AND table_name = $2
             ORDER BY ordinal_position
-            "#,
-            table_name
-        );
-        let rows = sqlx::query(&query)
+        "#;
+        sqlx::query_scalar(query)
+            .bind(schema)
+            .bind(table_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)
+    }
+
+    async fn primary_key_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let query = r#"
+            SELECT a.attname::text
+            FROM pg_index i
+            JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+            WHERE i.indrelid = $1::regclass AND i.indisprimary
+            ORDER BY array_position(i.indkey, a.attnum)
+        "#;
+        sqlx::query_scalar(query)
+            .bind(table_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)
+    }
+
+    async fn estimated_row_count(&self, table_name: &str) -> Result<Option<u64>, DbError> {
+        // reltuples is -1 until the table has been vacuumed or analyzed
+        let query = "SELECT reltuples::bigint FROM pg_class WHERE oid = $1::regclass";
+        let estimate: Option<i64> = sqlx::query_scalar(query)
+            .bind(table_name)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(estimate.and_then(|rows| u64::try_from(rows).ok()))
+    }
+
+    async fn list_routines(&self) -> Result<Vec<RoutineInfo>, DbError> {
+        let query = r#"
+            SELECT routine_name::text AS routine_name,
+                   routine_type::text AS routine_type,
+                   data_type::text AS data_type
+            FROM information_schema.routines
+            WHERE routine_schema = current_schema()
+            ORDER BY routine_name
+        "#;
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        let routines = rows
+            .iter()
+            .map(|row| RoutineInfo {
+                name: row.try_get("routine_name").unwrap_or_default(),
+                routine_type: row
+                    .try_get::<Option<String>, _>("routine_type")
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| "FUNCTION".to_string()),
+                // Procedures have no return type
+                return_type: row
+                    .try_get::<Option<String>, _>("data_type")
+                    .ok()
+                    .flatten(),
+            })
+            .collect();
+
+        Ok(routines)
+    }
+
+    async fn list_active_queries(&self) -> Result<Vec<SessionInfo>, DbError> {
+        let query = r#"
+            SELECT pid::bigint AS pid,
+                   usename::text AS usename,
+                   state,
+                   query,
+                   EXTRACT(EPOCH FROM now() - query_start)::bigint AS seconds
+            FROM pg_stat_activity
+            WHERE backend_type = 'client backend' AND pid <> pg_backend_pid()
+            ORDER BY query_start NULLS LAST
+        "#;
+        let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows
+            .iter()
+            .map(|row| SessionInfo {
+                pid: row.try_get::<i64, _>("pid").unwrap_or_default(),
+                user: row.try_get::<Option<String>, _>("usename").ok().flatten(),
+                state: row.try_get::<Option<String>, _>("state").ok().flatten(),
+                query: row.try_get::<Option<String>, _>("query").ok().flatten(),
+                seconds: row.try_get::<Option<i64>, _>("seconds").ok().flatten(),
+            })
+            .collect())
+    }
+
+    async fn kill_session(&self, pid: i64) -> Result<(), DbError> {
+        // pid is an int4 on the server; anything larger cannot name a backend
+        let pid = i32::try_from(pid).map_err(|_| DbError::NotFound(format!("session {}", pid)))?;
+        let terminated: Option<bool> = sqlx::query_scalar("SELECT pg_terminate_backend($1)")
+            .bind(pid)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        match terminated {
+            Some(true) => Ok(()),
+            _ => Err(DbError::NotFound(format!("session {}", pid))),
+        }
+    }
+
+    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
+        let (schema, table_name) = split_qualified_name(table_name, "public");
+        self.describe_table_in_schema(schema, table_name).await
+    }
+
+    async fn describe_table_in_schema(&self, schema: &str, table_name: &str) -> Result<TableSchema, DbError> {
+        // format_type keeps what information_schema's data_type drops: `character(10)`
+        // rather than `character`, the name of an enum rather than `USER-DEFINED`
+        let query = r#"
+            SELECT c.column_name::text AS column_name,
+                   format_type(a.atttypid, a.atttypmod) AS data_type,
+                   c.is_nullable::text AS is_nullable,
+                   c.column_default::text AS column_default
+            FROM information_schema.columns c
+            JOIN pg_catalog.pg_namespace n ON n.nspname = c.table_schema
+            JOIN pg_catalog.pg_class t ON t.relnamespace = n.oid AND t.relname = c.table_name
+            JOIN pg_catalog.pg_attribute a ON a.attrelid = t.oid AND a.attname = c.column_name
+            WHERE c.table_schema = $1 AND c.table_name = $2
+            ORDER BY c.ordinal_position
+        "#;
+        let rows = sqlx::query(query)
+            .bind(schema)
+            .bind(table_name)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
 
         let columns = rows
             .iter()
             .map(|row| ColumnSchema {
-                name: row.try_get("column_name").unwrap(),
-                data_type: row.try_get("data_type").unwrap(),
-                is_nullable: row.try_get::<String, _>("is_nullable").unwrap() == "YES",
-                default: row.try_get("column_default").ok(),
+                name: row.try_get("column_name").unwrap_or_default(),
+                data_type: row.try_get("data_type").unwrap_or_default(),
+                is_nullable: row.try_get::<String, _>("is_nullable").unwrap_or_default() == "YES",
+                default: row.try_get::<Option<String>, _>("column_default").ok().flatten(),
             })
             .collect();
 
@@ -183,6 +406,119 @@ impl DbClient for PostgresClient {
             indexes: Vec::new(),
         })
     }
+
+    async fn server_version(&self) -> Result<String, DbError> {
+        let version: String = sqlx::query_scalar("SELECT version()")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        // version() reads like "PostgreSQL 16.2 on x86_64-pc-linux-gnu, compiled by ..."
+        Ok(version.split_whitespace().take(2).collect::<Vec<_>>().join(" "))
+    }
+
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError> {
+        let query = query.trim().trim_end_matches(';');
+        self.query(&format!("EXPLAIN (ANALYZE, FORMAT JSON) {}", query)).await
+    }
+
+    async fn table_stats(&self, table_name: &str) -> Result<TableStats, DbError> {
+        let schema = self.describe_table(table_name).await?;
+        let query = table_stats_query(table_name, &schema.columns, quote_identifier);
+        let rows = self.query(&query).await?;
+        Ok(parse_table_stats(&schema.columns, rows.first().unwrap_or(&Value::Null)))
+    }
+
+    fn set_binary_format(&mut self, format: BinaryFormat) {
+        self.binary_format = format;
+    }
+
+    fn set_statement_cache(&mut self, enabled: bool) {
+        self.cache_statements = enabled;
+    }
+
+    fn set_show_system_databases(&mut self, show: bool) {
+        self.show_system_databases = show;
+    }
+
+    async fn stream_rows(&self, query: &str, on_row: &mut RowCallback<'_>) -> Result<u64, DbError> {
+        let mut rows = sqlx::query(query).persistent(self.cache_statements).fetch(&self.pool);
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await.map_err(DbError::from_sqlx)? {
+            on_row(self.row_to_json(&row))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn copy_out_csv(&self, query: &str, on_chunk: &mut ChunkCallback<'_>) -> Result<(), DbError> {
+        let mut chunks = self.copy_out(query).await?;
+        while let Some(chunk) = chunks.try_next().await? {
+            on_chunk(chunk.as_ref())?;
+        }
+        Ok(())
+    }
+
+    async fn copy_in_csv(&self, table: &str, columns: &[String], path: &Path, header: bool) -> Result<u64, DbError> {
+        let import_error = |e: std::io::Error| DbError::Import(format!("{}: {}", path.display(), e));
+        let mut file = tokio::fs::File::open(path).await.map_err(import_error)?;
+        let column_list = if columns.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<String> = columns.iter().map(|name| quote_identifier(name)).collect();
+            format!(" ({})", names.join(", "))
+        };
+        let statement = format!(
+            "COPY {}{} FROM STDIN WITH (FORMAT csv, HEADER {})",
+            quote_qualified_name(table, quote_identifier),
+            column_list,
+            header
+        );
+
+        let mut copy = self.pool.copy_in_raw(&statement).await.map_err(copy_in_error)?;
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = match file.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = copy.abort(e.to_string()).await;
+                    return Err(import_error(e));
+                }
+            };
+            copy.send(&buffer[..read]).await.map_err(copy_in_error)?;
+        }
+        copy.finish().await.map_err(copy_in_error)
+    }
+
+    fn clone_client(&self) -> Box<dyn DbClient + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn listen(&self, channel: &str) -> Result<Box<dyn Listener>, DbError> {
+        // PgListener holds its own connection rather than borrowing one from the pool
+        let mut listener = PgListener::connect_with(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        listener.listen(channel).await.map_err(DbError::from_sqlx)?;
+        Ok(Box::new(PostgresListener(listener)))
+    }
+}
+
+/// A failed `COPY ... FROM STDIN` as an import error, with the line and column the
+/// server stopped at when it names them.
+fn copy_in_error(err: sqlx::Error) -> DbError {
+    let Some(pg) = err.as_database_error().and_then(|e| e.try_downcast_ref::<PgDatabaseError>()) else {
+        return DbError::from_sqlx(err);
+    };
+    match pg.r#where() {
+        Some(context) => DbError::Import(format!("{} ({})", pg.message(), context)),
+        None => DbError::Import(pg.message().to_string()),
+    }
 }
 
 pub struct PostgresTransaction<'a> {
@@ -212,4 +548,14 @@ impl<'a> Transaction for PostgresTransaction<'a> {
             .await
             .map_err(|e| DbError::Transaction(e.to_string()))
     }
-} 
\ No newline at end of file
+}
+
+pub struct PostgresListener(PgListener);
+
+#[async_trait]
+impl Listener for PostgresListener {
+    async fn recv(&mut self) -> Result<(String, String), DbError> {
+        let notification = self.0.recv().await.map_err(DbError::from_sqlx)?;
+        Ok((notification.channel().to_string(), notification.payload().to_string()))
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Log target sqlx reports server NOTICE/WARNING messages under.
+pub const NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+static NOTICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Logger that collects Postgres notices for [`take_notices`] and hands every other record
+/// to `inner`.
+pub struct NoticeLogger<L> {
+    inner: L,
+}
+
+impl<L: Log> NoticeLogger<L> {
+    pub fn new(inner: L) -> Self {
+        NoticeLogger { inner }
+    }
+}
+
+impl<L: Log> Log for NoticeLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == NOTICE_TARGET || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.target() == NOTICE_TARGET {
+            if let Ok(mut notices) = NOTICES.lock() {
+                notices.push(format!("{}: {}", severity(record.level()), record.args()));
+            }
+        }
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs a [`NoticeLogger`] around `inner` as the global logger. Notices are captured at
+/// every severity; `inner` still filters everything else itself.
+pub fn init<L: Log + 'static>(inner: L) -> Result<(), SetLoggerError> {
+    log::set_boxed_logger(Box::new(NoticeLogger::new(inner)))?;
+    log::set_max_level(LevelFilter::Trace);
+    Ok(())
+}
+
+/// Returns the notices captured since the last call, oldest first.
+pub fn take_notices() -> Vec<String> {
+    NOTICES.lock().map(|mut notices| std::mem::take(&mut *notices)).unwrap_or_default()
+}
+
+/// Maps the log level sqlx picked back to the server's severity name.
+fn severity(level: Level) -> &'static str {
+    match level {
+        Level::Error => "ERROR",
+        Level::Warn => "WARNING",
+        Level::Info => "NOTICE",
+        Level::Debug => "DEBUG",
+        Level::Trace => "INFO",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Silent;
+
+    impl Log for Silent {
+        fn enabled(&self, _: &Metadata) -> bool {
+            false
+        }
+        fn log(&self, _: &Record) {}
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_notice_logger_captures_only_notices() {
+        let logger = NoticeLogger::new(Silent);
+        for (target, level, message) in [
+            (NOTICE_TARGET, Level::Info, "table \"t\" does not exist, skipping"),
+            ("sqlx::query", Level::Info, "SELECT 1"),
+            (NOTICE_TARGET, Level::Warn, "nothing to commit"),
+        ] {
+            logger.log(
+                &Record::builder()
+                    .target(target)
+                    .level(level)
+                    .args(format_args!("{}", message))
+                    .build(),
+            );
+        }
+
+        assert_eq!(
+            take_notices(),
+            vec![
+                "NOTICE: table \"t\" does not exist, skipping".to_string(),
+                "WARNING: nothing to commit".to_string(),
+            ]
+        );
+        assert!(take_notices().is_empty());
+    }
+}
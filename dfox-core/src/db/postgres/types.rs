@@ -1,9 +1,9 @@
 use serde_json::Value;
-use sqlx::{Row, postgres::PgRow};
+use sqlx::{Row, TypeInfo, ValueRef, postgres::{PgRow, PgTypeInfo, PgTypeKind, PgValueFormat}};
 use uuid::Uuid;
 use chrono::{NaiveDate, NaiveTime, NaiveDateTime};
-use base64::engine::general_purpose::STANDARD as BASE64;
-use base64::Engine;
+
+use crate::db::BinaryFormat;
 
 #[derive(Debug)]
 pub enum ColumnType {
@@ -122,7 +122,7 @@ impl ColumnType {
         }
     }
 
-    pub fn to_json_value<'a>(&self, row: &'a PgRow, index: usize) -> Value {
+    pub fn to_json_value(&self, row: &PgRow, index: usize, binary_format: BinaryFormat) -> Value {
         match self {
             ColumnType::Uuid => match row.try_get::<Uuid, _>(index) {
                 Ok(uuid) => Value::String(uuid.to_string()),
@@ -169,7 +169,7 @@ impl ColumnType {
                 Err(_) => Value::Null,
             },
             ColumnType::Bytea => match row.try_get::<Vec<u8>, _>(index) {
-                Ok(val) => Value::String(BASE64.encode(val)),
+                Ok(val) => Value::String(binary_format.encode(&val)),
                 Err(_) => Value::Null,
             },
             ColumnType::Money => match row.try_get::<String, _>(index) {
@@ -194,8 +194,112 @@ impl ColumnType {
             },
             ColumnType::Unknown => match row.try_get::<String, _>(index) {
                 Ok(val) => Value::String(val),
-                Err(_) => Value::Null,
+                Err(_) => unchecked_text(row, index, binary_format),
             },
         }
     }
-} 
\ No newline at end of file
+}
+
+/// Text of a column whose type sqlx won't decode as `String`: values sent in text
+/// format as they are, and ENUM and composite values rebuilt from their binary form.
+/// NULL for other binary values, which aren't text.
+fn unchecked_text(row: &PgRow, index: usize, binary_format: BinaryFormat) -> Value {
+    let Ok(raw) = row.try_get_raw(index) else {
+        return Value::Null;
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    if raw.format() == PgValueFormat::Text {
+        return row.try_get_unchecked::<String, _>(index)
+            .map_or(Value::Null, Value::String);
+    }
+    // Binary results come from prepared statements, whose types sqlx has resolved
+    let type_info = raw.type_info().into_owned();
+    let text = match (type_info.kind(), raw.as_bytes()) {
+        (PgTypeKind::Enum(_), Ok(bytes)) => std::str::from_utf8(bytes).ok().map(String::from),
+        (PgTypeKind::Composite(fields), Ok(bytes)) => composite_text(bytes, fields, binary_format),
+        _ => None,
+    };
+    text.map_or(Value::Null, Value::String)
+}
+
+/// Postgres's text form of a composite value, `(a,b,c)`, from its binary form: the
+/// field count, then per field its type oid, byte length (-1 for NULL) and bytes.
+fn composite_text(bytes: &[u8], fields: &[(String, PgTypeInfo)], binary_format: BinaryFormat) -> Option<String> {
+    let mut rest = bytes.get(4..)?;
+    let mut parts = Vec::with_capacity(fields.len());
+    for (_, field_type) in fields {
+        let len = i32::from_be_bytes(rest.get(4..8)?.try_into().ok()?);
+        rest = &rest[8..];
+        if len < 0 {
+            parts.push(String::new());
+            continue;
+        }
+        let field = rest.get(..len as usize)?;
+        rest = &rest[len as usize..];
+        let text = field_text(field, field_type, binary_format)?;
+        // Quoted like record_out does, so empty strings and separators stay unambiguous
+        if text.is_empty() || text.chars().any(|c| matches!(c, '"' | '\\' | '(' | ')' | ',') || c.is_whitespace()) {
+            parts.push(format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\"\"")));
+        } else {
+            parts.push(text);
+        }
+    }
+    Some(format!("({})", parts.join(",")))
+}
+
+/// Text of one binary composite field; types without a known layout are encoded like
+/// binary columns.
+fn field_text(bytes: &[u8], field_type: &PgTypeInfo, binary_format: BinaryFormat) -> Option<String> {
+    match field_type.kind() {
+        PgTypeKind::Composite(fields) => return composite_text(bytes, fields, binary_format),
+        PgTypeKind::Domain(base) => return field_text(bytes, base, binary_format),
+        PgTypeKind::Enum(_) => return std::str::from_utf8(bytes).ok().map(String::from),
+        _ => {}
+    }
+    let text = match field_type.name() {
+        "BOOL" => String::from(if *bytes.first()? != 0 { "t" } else { "f" }),
+        "INT2" => i16::from_be_bytes(bytes.try_into().ok()?).to_string(),
+        "INT4" => i32::from_be_bytes(bytes.try_into().ok()?).to_string(),
+        "INT8" => i64::from_be_bytes(bytes.try_into().ok()?).to_string(),
+        "FLOAT4" => f32::from_be_bytes(bytes.try_into().ok()?).to_string(),
+        "FLOAT8" => f64::from_be_bytes(bytes.try_into().ok()?).to_string(),
+        "UUID" => Uuid::from_slice(bytes).ok()?.to_string(),
+        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" | "CITEXT" => std::str::from_utf8(bytes).ok()?.to_string(),
+        _ => binary_format.encode(bytes),
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Postgres, Type};
+
+    use super::*;
+
+    #[test]
+    fn test_composite_text_from_binary() {
+        let fields = [
+            ("id".to_string(), <i32 as Type<Postgres>>::type_info()),
+            ("label".to_string(), <String as Type<Postgres>>::type_info()),
+            ("note".to_string(), <String as Type<Postgres>>::type_info()),
+        ];
+        let mut bytes = 3u32.to_be_bytes().to_vec();
+        for (oid, value) in [(23u32, Some(&7i32.to_be_bytes()[..])), (25, Some(&b"a, b"[..])), (25, None)] {
+            bytes.extend(oid.to_be_bytes());
+            match value {
+                Some(value) => {
+                    bytes.extend((value.len() as i32).to_be_bytes());
+                    bytes.extend(value);
+                }
+                None => bytes.extend((-1i32).to_be_bytes()),
+            }
+        }
+        assert_eq!(
+            composite_text(&bytes, &fields, BinaryFormat::Hex),
+            Some("(7,\"a, b\",)".to_string())
+        );
+        assert_eq!(composite_text(&bytes[..10], &fields, BinaryFormat::Hex), None);
+    }
+}
@@ -0,0 +1,133 @@
+use std::{future::Future, time::Duration};
+
+/// How many times, and how patiently, to retry a failed connection attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for every retry after it.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between two attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay to wait before retry number `retry` (counting from zero).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.checked_pow(retry).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |delay| delay.min(self.max_backoff))
+    }
+}
+
+/// Whether a failed attempt is worth retrying: the server was unreachable, still starting up
+/// or refused the connection for now. Bad credentials or URLs fail straight away.
+pub fn is_transient(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::Tls(_) => true,
+        sqlx::Error::Database(db_error) => db_error
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code == "57P03"),
+        _ => false,
+    }
+}
+
+/// Runs `attempt` until it succeeds, fails with a non-transient error or `policy` runs out of
+/// retries, sleeping with exponential backoff in between. Returns the last error on failure.
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, mut attempt: F) -> Result<T, sqlx::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut retries = 0;
+    loop {
+        match attempt().await {
+            Err(e) if retries < policy.max_retries && is_transient(&e) => {
+                let delay = policy.backoff(retries);
+                log::warn!("Connection attempt failed ({}), retrying in {:?}", e, delay);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn unreachable() -> sqlx::Error {
+        sqlx::Error::Io(io::Error::new(io::ErrorKind::ConnectionRefused, "refused"))
+    }
+
+    fn quick_policy(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(2),
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(500));
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(5), Duration::from_secs(8));
+        assert_eq!(policy.backoff(40), Duration::from_secs(8));
+    }
+
+    #[tokio::test]
+    async fn test_retry_succeeds_once_server_is_up() {
+        let mut attempts = 0;
+        let result = retry(&quick_policy(3), || {
+            attempts += 1;
+            let up = attempts > 2;
+            async move { if up { Ok(attempts) } else { Err(unreachable()) } }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_with_last_error() {
+        let mut attempts = 0;
+        let result: Result<(), _> = retry(&quick_policy(2), || {
+            attempts += 1;
+            async { Err(unreachable()) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(sqlx::Error::Io(_))));
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_permanent_errors() {
+        let mut attempts = 0;
+        let result: Result<(), _> = retry(&quick_policy(3), || {
+            attempts += 1;
+            async { Err(sqlx::Error::Configuration("bad url".into())) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
@@ -1,77 +1,150 @@
+use std::{path::Path, time::Instant};
+
 use async_trait::async_trait;
+use futures_util::TryStreamExt;
 use serde_json::Value;
-use sqlx::{sqlite::SqlitePoolOptions, Column, Pool, Row, Sqlite};
+use sqlx::{sqlite::SqlitePoolOptions, Column, Pool, Row, Sqlite, ValueRef};
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::schema::{ColumnSchema, RoutineInfo, SessionInfo, TableSchema, TableStats},
 };
 
-use super::{DbClient, Transaction};
+use super::{
+    bind_json_value, parse_table_stats, quote_identifier,
+    retry::{retry, RetryPolicy},
+    split_qualified_name, table_stats_query, BinaryFormat, DbClient, Listener, ResultSet, RowCallback, Transaction,
+};
 
+#[derive(Clone)]
 pub struct SqliteClient {
     pub pool: Pool<Sqlite>,
+    binary_format: BinaryFormat,
+    cache_statements: bool,
 }
 
 impl SqliteClient {
     pub async fn connect(database_url: &str) -> Result<Self, DbError> {
+        Self::connect_with_retry(database_url, &RetryPolicy::default()).await
+    }
+
+    /// Connects like [`Self::connect`], retrying transient failures according to `policy`.
+    pub async fn connect_with_retry(database_url: &str, policy: &RetryPolicy) -> Result<Self, DbError> {
+        let pool = retry(policy, || {
+            SqlitePoolOptions::new()
+                .max_connections(5)
+                .connect(database_url)
+        })
+        .await
+        .map_err(DbError::from_connect)?;
+
+        Ok(Self {
+            pool,
+            binary_format: BinaryFormat::default(),
+            cache_statements: true,
+        })
+    }
+
+    /// Opens a private in-memory database, e.g. for tests or a scratch session. The pool
+    /// holds a single connection that is never recycled, since the database disappears
+    /// with the connection that created it.
+    pub async fn connect_in_memory() -> Result<Self, DbError> {
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect("sqlite::memory:")
             .await
             .map_err(|e| DbError::Connection(e.to_string()))?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            binary_format: BinaryFormat::default(),
+            cache_statements: true,
+        })
+    }
+
+    /// Converts a row to a JSON object keyed by column name, in result column order.
+    fn row_to_json(&self, row: &sqlx::sqlite::SqliteRow) -> Value {
+        let json_map = row
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let column_name = column.name();
+                // A NULL decodes as an empty string, so check for it first
+                if row.try_get_raw(i).is_ok_and(|raw| raw.is_null()) {
+                    return (column_name.to_string(), Value::Null);
+                }
+                let value: Value = match row.try_get::<String, _>(i) {
+                    Ok(val) => Value::String(val),
+                    Err(_) => match row.try_get::<i64, _>(i) {
+                        Ok(val) => Value::Number(val.into()),
+                        Err(_) => match row.try_get::<f64, _>(i) {
+                            Ok(val) => serde_json::Number::from_f64(val)
+                                .map(Value::Number)
+                                .unwrap_or(Value::Null),
+                            Err(_) => match row.try_get::<Vec<u8>, _>(i) {
+                                Ok(val) => Value::String(self.binary_format.encode(&val)),
+                                Err(_) => Value::Null,
+                            },
+                        },
+                    },
+                };
+
+                (column_name.to_string(), value)
+            })
+            .collect();
+
+        Value::Object(json_map)
     }
 }
 
 #[async_trait]
 impl DbClient for SqliteClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
+            .persistent(self.cache_statements)
             .execute(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
-        Ok(())
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
     }
 
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
         let rows = sqlx::query(query)
+            .persistent(self.cache_statements)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
 
-        let results = rows
+    async fn query_with_params(&self, query: &str, params: &[Value]) -> Result<Vec<Value>, DbError> {
+        let rows = params
             .iter()
-            .map(|row| {
-                let json_map = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, column)| {
-                        let column_name = column.name();
-                        let value: Value = match row.try_get::<String, _>(i) {
-                            Ok(val) => Value::String(val),
-                            Err(_) => match row.try_get::<i64, _>(i) {
-                                Ok(val) => Value::Number(val.into()),
-                                Err(_) => match row.try_get::<f64, _>(i) {
-                                    Ok(val) => serde_json::Number::from_f64(val)
-                                        .map(Value::Number)
-                                        .unwrap_or(Value::Null),
-                                    Err(_) => Value::Null,
-                                },
-                            },
-                        };
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
 
-                        (column_name.to_string(), value)
-                    })
-                    .collect();
+        Ok(rows.iter().map(|row| self.row_to_json(row)).collect())
+    }
 
-                Value::Object(json_map)
-            })
-            .collect();
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError> {
+        let result = params
+            .iter()
+            .fold(sqlx::query(query).persistent(self.cache_statements), bind_json_value)
+            .execute(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(result.rows_affected())
+    }
 
-        Ok(results)
+    async fn query_read_only(&self, _query: &str, _params: &[Value]) -> Result<Vec<Value>, DbError> {
+        Err(DbError::General("Read-only transactions are only supported on PostgreSQL".into()))
     }
 
     async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError> {
@@ -115,6 +188,12 @@ impl DbClient for SqliteClient {
         Ok((column_names, data_rows))
     }
 
+    async fn query_multi(&self, query: &str) -> Result<Vec<ResultSet>, DbError> {
+        // Only MySQL procedures return more than one result set
+        let (column_names, data_rows) = self.query_with_column_order(query).await?;
+        Ok(if column_names.is_empty() { Vec::new() } else { vec![(column_names, data_rows)] })
+    }
+
     async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
         let tx = self
             .pool
@@ -139,7 +218,7 @@ impl DbClient for SqliteClient {
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let tables = rows
             .iter()
@@ -149,20 +228,93 @@ impl DbClient for SqliteClient {
         Ok(tables)
     }
 
+    async fn table_exists(&self, table_name: &str) -> Result<bool, DbError> {
+        let (schema, table_name) = split_qualified_name(table_name, "main");
+        let query = format!(
+            "SELECT COUNT(*) FROM \"{}\".sqlite_master WHERE type IN ('table', 'view') AND name = ?",
+            schema.replace('"', "\"\"")
+        );
+        let count: i64 = sqlx::query_scalar(&query)
+            .bind(table_name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(count > 0)
+    }
+
+    async fn list_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let (schema, table_name) = split_qualified_name(table_name, "main");
+        sqlx::query_scalar("SELECT name FROM pragma_table_info(?, ?) ORDER BY cid")
+            .bind(table_name)
+            .bind(schema)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)
+    }
+
+    async fn primary_key_columns(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let (schema, table_name) = split_qualified_name(table_name, "main");
+        // pk is the column's 1-based position in the key, 0 for other columns
+        let query = "SELECT name FROM pragma_table_info(?, ?) WHERE pk > 0 ORDER BY pk";
+        sqlx::query_scalar(query)
+            .bind(table_name)
+            .bind(schema)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)
+    }
+
+    async fn estimated_row_count(&self, table_name: &str) -> Result<Option<u64>, DbError> {
+        // SQLite keeps no row statistics, so count exactly
+        let (schema, table_name) = split_qualified_name(table_name, "main");
+        let query = format!(
+            "SELECT COUNT(*) FROM \"{}\".\"{}\"",
+            schema.replace('"', "\"\""),
+            table_name.replace('"', "\"\"")
+        );
+        let count: i64 = sqlx::query_scalar(&query)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        Ok(u64::try_from(count).ok())
+    }
+
+    async fn list_routines(&self) -> Result<Vec<RoutineInfo>, DbError> {
+        // SQLite has no stored functions or procedures
+        Ok(Vec::new())
+    }
+
+    async fn list_active_queries(&self) -> Result<Vec<SessionInfo>, DbError> {
+        Err(DbError::General("SQLite has no server sessions to list".into()))
+    }
+
+    async fn kill_session(&self, _pid: i64) -> Result<(), DbError> {
+        Err(DbError::General("SQLite has no server sessions to kill".into()))
+    }
+
     async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!("PRAGMA table_info('{}')", table_name);
+        let (schema, table_name) = split_qualified_name(table_name, "main");
+        self.describe_table_in_schema(schema, table_name).await
+    }
+
+    async fn describe_table_in_schema(&self, schema: &str, table_name: &str) -> Result<TableSchema, DbError> {
+        let query = format!(
+            "PRAGMA \"{}\".table_info('{}')",
+            schema.replace('"', "\"\""),
+            table_name.replace('\'', "''")
+        );
         let rows = sqlx::query(&query)
             .fetch_all(&self.pool)
             .await
-            .map_err(DbError::Sqlx)?;
+            .map_err(DbError::from_sqlx)?;
 
         let columns = rows
             .iter()
             .map(|row| ColumnSchema {
-                name: row.try_get("name").unwrap(),
-                data_type: row.try_get("type").unwrap(),
-                is_nullable: row.try_get::<i64, _>("notnull").unwrap() == 0,
-                default: row.try_get("dflt_value").ok(),
+                name: row.try_get("name").unwrap_or_default(),
+                data_type: row.try_get("type").unwrap_or_default(),
+                is_nullable: row.try_get::<i64, _>("notnull").unwrap_or(0) == 0,
+                default: row.try_get::<Option<String>, _>("dflt_value").ok().flatten(),
             })
             .collect();
 
@@ -172,6 +324,91 @@ impl DbClient for SqliteClient {
             indexes: Vec::new(),
         })
     }
+
+    async fn server_version(&self) -> Result<String, DbError> {
+        let version: String = sqlx::query_scalar("SELECT sqlite_version()")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+
+        Ok(format!("SQLite {}", version))
+    }
+
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError> {
+        // SQLite has no EXPLAIN ANALYZE: time the statement and add that to its plan
+        let query = query.trim().trim_end_matches(';');
+        let started = Instant::now();
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        let elapsed = started.elapsed();
+
+        let plan = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", query))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::from_sqlx)?;
+        let mut lines: Vec<Value> = plan
+            .iter()
+            .map(|row| {
+                let detail = row.try_get::<String, _>("detail").unwrap_or_default();
+                serde_json::json!({ "QUERY PLAN": detail })
+            })
+            .collect();
+        lines.push(serde_json::json!({
+            "QUERY PLAN": format!(
+                "actual time={:.3} ms rows={}",
+                elapsed.as_secs_f64() * 1000.0,
+                rows.len()
+            )
+        }));
+        Ok(lines)
+    }
+
+    async fn table_stats(&self, table_name: &str) -> Result<TableStats, DbError> {
+        let schema = self.describe_table(table_name).await?;
+        let query = table_stats_query(table_name, &schema.columns, quote_identifier);
+        let rows = self.query(&query).await?;
+        Ok(parse_table_stats(&schema.columns, rows.first().unwrap_or(&Value::Null)))
+    }
+
+    fn set_binary_format(&mut self, format: BinaryFormat) {
+        self.binary_format = format;
+    }
+
+    fn set_statement_cache(&mut self, enabled: bool) {
+        self.cache_statements = enabled;
+    }
+
+    fn set_show_system_databases(&mut self, _show: bool) {
+        // The only database is the attached file, there are no system ones to hide
+    }
+
+    async fn stream_rows(&self, query: &str, on_row: &mut RowCallback<'_>) -> Result<u64, DbError> {
+        let mut rows = sqlx::query(query).persistent(self.cache_statements).fetch(&self.pool);
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await.map_err(DbError::from_sqlx)? {
+            on_row(self.row_to_json(&row))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    async fn copy_in_csv(&self, _table: &str, _columns: &[String], _path: &Path, _header: bool) -> Result<u64, DbError> {
+        Err(DbError::General("COPY is only supported on PostgreSQL".into()))
+    }
+
+    fn clone_client(&self) -> Box<dyn DbClient + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn listen(&self, _channel: &str) -> Result<Box<dyn Listener>, DbError> {
+        Err(DbError::General("LISTEN/NOTIFY is only supported on PostgreSQL".into()))
+    }
 }
 
 pub struct SqliteTransaction<'a> {
@@ -217,12 +454,34 @@ mod tests {
 
         #[async_trait]
         impl DbClient for DbClientMock {
-            async fn execute(&self, query: &str) -> Result<(), DbError>;
+            async fn execute(&self, query: &str) -> Result<u64, DbError>;
             async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn query_with_params(&self, query: &str, params: &[serde_json::Value]) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn execute_with_params(&self, query: &str, params: &[serde_json::Value]) -> Result<u64, DbError>;
+            async fn query_read_only(&self, query: &str, params: &[serde_json::Value]) -> Result<Vec<serde_json::Value>, DbError>;
             async fn query_with_column_order(&self, query: &str) -> Result<(Vec<String>, Vec<Vec<String>>), DbError>;
+            async fn query_multi(&self, query: &str) -> Result<Vec<ResultSet>, DbError>;
             async fn list_databases(&self) -> Result<Vec<String>, DbError>;
             async fn list_tables(&self) -> Result<Vec<String>, DbError>;
             async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
+            async fn describe_table_in_schema(&self, schema: &str, table_name: &str) -> Result<TableSchema, DbError>;
+            async fn table_exists(&self, table_name: &str) -> Result<bool, DbError>;
+            async fn list_columns(&self, table_name: &str) -> Result<Vec<String>, DbError>;
+            async fn primary_key_columns(&self, table_name: &str) -> Result<Vec<String>, DbError>;
+            async fn estimated_row_count(&self, table_name: &str) -> Result<Option<u64>, DbError>;
+            async fn list_routines(&self) -> Result<Vec<RoutineInfo>, DbError>;
+            async fn list_active_queries(&self) -> Result<Vec<SessionInfo>, DbError>;
+            async fn kill_session(&self, pid: i64) -> Result<(), DbError>;
+            async fn server_version(&self) -> Result<String, DbError>;
+            async fn explain_analyze(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn table_stats(&self, table_name: &str) -> Result<TableStats, DbError>;
+            fn set_binary_format(&mut self, format: BinaryFormat);
+            fn set_statement_cache(&mut self, enabled: bool);
+            fn set_show_system_databases(&mut self, show: bool);
+            async fn listen(&self, channel: &str) -> Result<Box<dyn Listener>, DbError>;
+            async fn copy_in_csv(&self, table: &str, columns: &[String], path: &Path, header: bool) -> Result<u64, DbError>;
+            fn clone_client(&self) -> Box<dyn DbClient + Send + Sync>;
+            async fn close(&self);
             async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
         }
     }
@@ -261,12 +520,12 @@ mod tests {
             .with(predicate::eq(
                 "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_db
             .execute("INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')")
             .await;
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
     }
 
     #[tokio::test]
@@ -325,6 +584,18 @@ mod tests {
         assert_eq!(result.columns[1].name, "name");
     }
 
+    #[tokio::test]
+    async fn test_server_version() {
+        let mut mock_db = MockDbClientMock::new();
+
+        mock_db
+            .expect_server_version()
+            .returning(|| Ok("SQLite 3.45.1".to_string()));
+
+        let version = mock_db.server_version().await.unwrap();
+        assert_eq!(version, "SQLite 3.45.1");
+    }
+
     mock! {
         pub Transaction {}
 
@@ -395,4 +666,171 @@ mod tests {
             .await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_query_with_params_binds_string_and_integer() {
+        let client = SqliteClient::connect("sqlite::memory:").await.unwrap();
+
+        let rows = client
+            .query_with_params(
+                "SELECT ? AS name, ? + 1 AS next_id",
+                &[serde_json::json!("O'Brien"), serde_json::json!(41)],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0]["name"], "O'Brien");
+        assert_eq!(rows[0]["next_id"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_query_with_params_filters_rows() {
+        let client = SqliteClient::connect("sqlite::memory:").await.unwrap();
+
+        let rows = client
+            .query_with_params(
+                "SELECT value AS id FROM (SELECT 1 AS value UNION ALL SELECT 2) WHERE value = ?",
+                &[serde_json::json!(2)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows, vec![serde_json::json!({ "id": 2 })]);
+
+        let rows = client
+            .query_with_params("SELECT 1 WHERE ? IS NULL", &[serde_json::Value::Null])
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+    }
+
+    async fn memory_client() -> SqliteClient {
+        SqliteClient::connect_in_memory().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_primary_key_columns() {
+        let client = memory_client().await;
+        client
+            .execute("CREATE TABLE line_items (order_id INTEGER, line INTEGER, sku TEXT, PRIMARY KEY (order_id, line))")
+            .await
+            .unwrap();
+        client.execute("CREATE TABLE notes (body TEXT)").await.unwrap();
+
+        assert_eq!(client.primary_key_columns("line_items").await.unwrap(), vec!["order_id", "line"]);
+        assert_eq!(client.primary_key_columns("main.line_items").await.unwrap(), vec!["order_id", "line"]);
+        assert!(client.primary_key_columns("notes").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_describe_table_qualified_by_schema() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (id INTEGER NOT NULL, body TEXT)").await.unwrap();
+
+        let schema = client.describe_table("main.notes").await.unwrap();
+        assert_eq!(schema.table_name, "notes");
+        let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "body"]);
+        assert!(!schema.columns[0].is_nullable);
+
+        assert!(client.describe_table_in_schema("temp", "notes").await.unwrap().columns.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_table_exists() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (body TEXT)").await.unwrap();
+        client.execute("CREATE VIEW note_bodies AS SELECT body FROM notes").await.unwrap();
+
+        assert!(client.table_exists("notes").await.unwrap());
+        assert!(client.table_exists("main.note_bodies").await.unwrap());
+        assert!(!client.table_exists("missing").await.unwrap());
+        assert!(!client.table_exists("temp.notes").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_estimated_row_count_counts_rows() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (body TEXT)").await.unwrap();
+        client.execute("INSERT INTO notes VALUES ('a'), ('b')").await.unwrap();
+
+        assert_eq!(client.estimated_row_count("notes").await.unwrap(), Some(2));
+        assert_eq!(client.estimated_row_count("main.notes").await.unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_stream_rows_visits_rows_in_order() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (id INTEGER, body TEXT)").await.unwrap();
+        client
+            .execute("INSERT INTO notes VALUES (1, 'first'), (2, NULL), (3, 'third')")
+            .await
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let count = client
+            .stream_rows("SELECT id, body FROM notes ORDER BY id", &mut |row| {
+                seen.push(row);
+                Ok(())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(seen[1], serde_json::json!({ "id": 2, "body": null }));
+        assert_eq!(seen[2]["body"], "third");
+    }
+
+    #[tokio::test]
+    async fn test_stream_rows_stops_at_callback_error() {
+        let client = memory_client().await;
+        let result = client
+            .stream_rows("SELECT 1 UNION ALL SELECT 2", &mut |_| Err(DbError::Export("disk full".into())))
+            .await;
+        assert!(matches!(result, Err(DbError::Export(_))));
+    }
+
+    #[tokio::test]
+    async fn test_statement_cache_rebinds_repeated_query() {
+        let mut client = memory_client().await;
+        client.execute("CREATE TABLE notes (id INTEGER, body TEXT)").await.unwrap();
+        client.execute("INSERT INTO notes VALUES (1, 'first'), (2, 'second')").await.unwrap();
+
+        let query = "SELECT body FROM notes WHERE id = ?";
+        for enabled in [true, false] {
+            client.set_statement_cache(enabled);
+            for (id, body) in [(1, "first"), (2, "second"), (1, "first")] {
+                let rows = client.query_with_params(query, &[serde_json::json!(id)]).await.unwrap();
+                assert_eq!(rows, vec![serde_json::json!({ "body": body })]);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_explain_analyze_reports_plan_and_timing() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)").await.unwrap();
+        client.execute("INSERT INTO notes (body) VALUES ('a'), ('b')").await.unwrap();
+
+        let plan = client.explain_analyze("SELECT body FROM notes WHERE id = 1;").await.unwrap();
+        assert!(plan.len() >= 2);
+        assert!(plan[0]["QUERY PLAN"].as_str().unwrap().contains("notes"));
+        let timing = plan.last().unwrap()["QUERY PLAN"].as_str().unwrap();
+        assert!(timing.starts_with("actual time="));
+        assert!(timing.ends_with("rows=1"));
+    }
+
+    #[tokio::test]
+    async fn test_table_stats_counts_nulls_and_distinct_values() {
+        let client = memory_client().await;
+        client.execute("CREATE TABLE notes (id INTEGER, body TEXT)").await.unwrap();
+        client.execute("INSERT INTO notes VALUES (3, 'a'), (7, 'a'), (5, NULL)").await.unwrap();
+
+        let stats = client.table_stats("notes").await.unwrap();
+        assert_eq!(stats.row_count, 3);
+        let (id, body) = (&stats.columns[0], &stats.columns[1]);
+        assert_eq!((id.min.as_deref(), id.max.as_deref()), (Some("3"), Some("7")));
+        assert_eq!((body.null_count, body.distinct_count), (1, Some(1)));
+        assert_eq!(body.min, None);
+    }
 }
@@ -22,4 +22,152 @@ pub enum DbError {
     /// General error with a custom message.
     #[error("Error: {0}")]
     General(String),
+    /// The statement could not be parsed. `position` is the 1-based character offset
+    /// into the query when the server reports one (Postgres does).
+    #[error("Syntax error: {message}")]
+    Syntax {
+        message: String,
+        position: Option<usize>,
+    },
+    /// The connected user lacks the privileges for the statement.
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    /// A referenced database, table, column or function does not exist.
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// The server rejected the username or password.
+    #[error("Authentication failed: {0}")]
+    Authentication(String),
+    /// The database named in the connection does not exist on the server.
+    #[error("Database not found: {0}")]
+    DatabaseNotFound(String),
+    /// A statement ran longer than the configured limit and was abandoned.
+    #[error("Timeout: {0}")]
+    Timeout(String),
+}
+
+/// Broad category of a server error, derived from its SQLSTATE code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlStateKind {
+    Syntax,
+    PermissionDenied,
+    NotFound,
+    DatabaseNotFound,
+    Authentication,
+    Connection,
+    Other,
+}
+
+/// Classifies a SQLSTATE code. MySQL reports both syntax and access errors as the
+/// generic `42000`, so its vendor error number is consulted when available.
+pub fn classify_sqlstate(code: &str, mysql_error_number: Option<u16>) -> SqlStateKind {
+    match (code, mysql_error_number) {
+        (_, Some(1064 | 1149)) => SqlStateKind::Syntax,
+        // invalid_password, MySQL's access denied for a user
+        ("28P01", _) | (_, Some(1045)) => SqlStateKind::Authentication,
+        (_, Some(1044 | 1142 | 1143 | 1227)) => SqlStateKind::PermissionDenied,
+        // invalid_catalog_name, MySQL's unknown database
+        ("3D000", _) | (_, Some(1049)) => SqlStateKind::DatabaseNotFound,
+        ("42601", _) => SqlStateKind::Syntax,
+        ("42501", _) => SqlStateKind::PermissionDenied,
+        // undefined table/column/function/object/schema, MySQL 42S*
+        ("42P01" | "42703" | "42883" | "42704" | "3F000" | "42S02" | "42S22", _) => {
+            SqlStateKind::NotFound
+        }
+        _ if code.starts_with("08") => SqlStateKind::Connection,
+        _ if code.starts_with("28") => SqlStateKind::PermissionDenied,
+        _ => SqlStateKind::Other,
+    }
+}
+
+impl DbError {
+    /// Converts a sqlx error into the most specific `DbError` variant, falling back to
+    /// `DbError::Sqlx` for anything that cannot be classified.
+    pub fn from_sqlx(err: sqlx::Error) -> Self {
+        match &err {
+            sqlx::Error::Database(db_err) => {
+                let Some(code) = db_err.code() else {
+                    return DbError::Sqlx(err);
+                };
+                let mysql_error_number = db_err
+                    .try_downcast_ref::<sqlx::mysql::MySqlDatabaseError>()
+                    .map(|e| e.number());
+                let message = db_err.message().to_string();
+                match classify_sqlstate(&code, mysql_error_number) {
+                    SqlStateKind::Syntax => {
+                        let position = db_err
+                            .try_downcast_ref::<sqlx::postgres::PgDatabaseError>()
+                            .and_then(|e| match e.position() {
+                                Some(sqlx::postgres::PgErrorPosition::Original(position)) => {
+                                    Some(position)
+                                }
+                                _ => None,
+                            });
+                        DbError::Syntax { message, position }
+                    }
+                    SqlStateKind::PermissionDenied => DbError::PermissionDenied(message),
+                    SqlStateKind::NotFound => DbError::NotFound(message),
+                    SqlStateKind::DatabaseNotFound => DbError::DatabaseNotFound(message),
+                    SqlStateKind::Authentication => DbError::Authentication(message),
+                    SqlStateKind::Connection => DbError::Connection(message),
+                    SqlStateKind::Other => DbError::Sqlx(err),
+                }
+            }
+            sqlx::Error::RowNotFound => DbError::NotFound(err.to_string()),
+            sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed => DbError::Connection(err.to_string()),
+            _ => DbError::Sqlx(err),
+        }
+    }
+
+    /// Converts an error from opening a connection. A missing database and rejected
+    /// credentials keep their own variants so callers can point at the cause; anything
+    /// else is a connection error.
+    pub fn from_connect(err: sqlx::Error) -> Self {
+        let message = err.to_string();
+        match DbError::from_sqlx(err) {
+            err @ (DbError::DatabaseNotFound(_) | DbError::Authentication(_)) => err,
+            _ => DbError::Connection(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_postgres_sqlstates() {
+        assert_eq!(classify_sqlstate("42601", None), SqlStateKind::Syntax);
+        assert_eq!(classify_sqlstate("42501", None), SqlStateKind::PermissionDenied);
+        assert_eq!(classify_sqlstate("42P01", None), SqlStateKind::NotFound);
+        assert_eq!(classify_sqlstate("08006", None), SqlStateKind::Connection);
+        assert_eq!(classify_sqlstate("28P01", None), SqlStateKind::Authentication);
+        assert_eq!(classify_sqlstate("28000", None), SqlStateKind::PermissionDenied);
+        assert_eq!(classify_sqlstate("3D000", None), SqlStateKind::DatabaseNotFound);
+        assert_eq!(classify_sqlstate("23505", None), SqlStateKind::Other);
+    }
+
+    #[test]
+    fn test_classify_mysql_error_numbers() {
+        assert_eq!(classify_sqlstate("42000", Some(1064)), SqlStateKind::Syntax);
+        assert_eq!(classify_sqlstate("42000", Some(1142)), SqlStateKind::PermissionDenied);
+        assert_eq!(classify_sqlstate("28000", Some(1045)), SqlStateKind::Authentication);
+        assert_eq!(classify_sqlstate("42S02", Some(1146)), SqlStateKind::NotFound);
+        assert_eq!(classify_sqlstate("42000", Some(1049)), SqlStateKind::DatabaseNotFound);
+        assert_eq!(classify_sqlstate("42000", Some(1050)), SqlStateKind::Other);
+    }
+
+    #[test]
+    fn test_from_sqlx_classifies_transport_errors() {
+        assert!(matches!(DbError::from_sqlx(sqlx::Error::PoolTimedOut), DbError::Connection(_)));
+        assert!(matches!(DbError::from_sqlx(sqlx::Error::RowNotFound), DbError::NotFound(_)));
+        assert!(matches!(DbError::from_connect(sqlx::Error::PoolTimedOut), DbError::Connection(_)));
+        assert!(matches!(
+            DbError::from_sqlx(sqlx::Error::Protocol("bad".into())),
+            DbError::Sqlx(_)
+        ));
+    }
 }
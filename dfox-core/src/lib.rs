@@ -1,4 +1,7 @@
-use db::{mysql::MySqlClient, postgres::PostgresClient, sqlite::SqliteClient, DbClient};
+use db::{
+    mysql::MySqlClient, postgres::PostgresClient, retry::RetryPolicy, sqlite::SqliteClient,
+    DbClient,
+};
 use errors::DbError;
 use models::connections::{ConnectionConfig, DbType};
 use std::sync::Arc;
@@ -11,31 +14,63 @@ pub mod models;
 #[derive(Default)]
 pub struct DbManager {
     pub connections: Arc<Mutex<Vec<Box<dyn DbClient + Send + Sync>>>>,
+    /// Applied to every connection this manager opens.
+    pub retry_policy: RetryPolicy,
 }
 
 impl DbManager {
     pub fn new() -> Self {
         DbManager {
             connections: Arc::new(Mutex::new(Vec::new())),
+            retry_policy: RetryPolicy::default(),
         }
     }
 
     pub async fn add_connection(&self, config: ConnectionConfig) -> Result<(), DbError> {
         match config.db_type {
             DbType::Postgres => {
-                let client = PostgresClient::connect(&config.database_url).await?;
+                let client = PostgresClient::connect_with_retry(&config.database_url, &self.retry_policy).await?;
                 self.connections.lock().await.push(Box::new(client));
             }
             DbType::MySql => {
-                let client = MySqlClient::connect(&config.database_url).await?;
+                let client = MySqlClient::connect_with_retry(&config.database_url, &self.retry_policy).await?;
                 self.connections.lock().await.push(Box::new(client));
             }
             DbType::Sqlite => {
-                let client = SqliteClient::connect(&config.database_url).await?;
+                let client = SqliteClient::connect_with_retry(&config.database_url, &self.retry_policy).await?;
                 self.connections.lock().await.push(Box::new(client));
             }
         }
 
         Ok(())
     }
+
+    /// Closes and forgets every open connection. Call it before exiting, since ending the
+    /// process skips the destructors that would otherwise close them.
+    pub async fn disconnect_all(&self) {
+        let clients: Vec<_> = self.connections.lock().await.drain(..).collect();
+        for client in clients {
+            client.close().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disconnect_all_closes_connections() {
+        let manager = DbManager::new();
+        let config = ConnectionConfig {
+            db_type: DbType::Sqlite,
+            database_url: "sqlite::memory:".to_string(),
+        };
+        manager.add_connection(config).await.unwrap();
+        let client = manager.connections.lock().await[0].clone_client();
+
+        manager.disconnect_all().await;
+        assert!(manager.connections.lock().await.is_empty());
+        assert!(client.query("SELECT 1").await.is_err());
+    }
 }
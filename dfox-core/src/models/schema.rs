@@ -15,9 +15,122 @@ pub struct ColumnSchema {
     pub default: Option<String>,
 }
 
+/// A stored function or procedure.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RoutineInfo {
+    pub name: String,
+    /// `FUNCTION` or `PROCEDURE`.
+    pub routine_type: String,
+    /// Declared return type; `None` for procedures.
+    pub return_type: Option<String>,
+}
+
+/// A client session on the server, from `pg_stat_activity` or MySQL's process list.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SessionInfo {
+    /// Backend pid on Postgres, connection id on MySQL.
+    pub pid: i64,
+    pub user: Option<String>,
+    /// `active`, `idle in transaction`, ... on Postgres; the command (`Query`, `Sleep`)
+    /// on MySQL.
+    pub state: Option<String>,
+    /// The running statement, or on Postgres the last one of an idle session.
+    pub query: Option<String>,
+    /// Seconds since the statement started.
+    pub seconds: Option<i64>,
+}
+
+/// Row count and per-column aggregates of a table, from [`crate::db::DbClient::table_stats`].
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TableStats {
+    pub row_count: u64,
+    pub columns: Vec<ColumnStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub name: String,
+    pub data_type: String,
+    pub null_count: u64,
+    /// `None` for types the server cannot compare, such as Postgres `json`.
+    pub distinct_count: Option<u64>,
+    /// Smallest and largest value; only collected for numeric columns.
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct IndexSchema {
     pub name: String,
     pub columns: Vec<String>,
     pub is_unique: bool,
 }
+
+impl TableSchema {
+    /// Reconstructs a `CREATE TABLE` statement, followed by one `CREATE INDEX` per index,
+    /// from the described columns.
+    pub fn create_table_statement(&self) -> String {
+        let columns: Vec<String> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut definition = format!("    {} {}", column.name, column.data_type);
+                if !column.is_nullable {
+                    definition.push_str(" NOT NULL");
+                }
+                if let Some(default) = &column.default {
+                    definition.push_str(&format!(" DEFAULT {}", default));
+                }
+                definition
+            })
+            .collect();
+
+        let mut statement = format!("CREATE TABLE {} (\n{}\n);", self.table_name, columns.join(",\n"));
+        for index in &self.indexes {
+            statement.push_str(&format!(
+                "\nCREATE {}INDEX {} ON {} ({});",
+                if index.is_unique { "UNIQUE " } else { "" },
+                index.name,
+                self.table_name,
+                index.columns.join(", ")
+            ));
+        }
+        statement
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_table_statement() {
+        let schema = TableSchema {
+            table_name: "users".to_string(),
+            columns: vec![
+                ColumnSchema {
+                    name: "id".to_string(),
+                    data_type: "integer".to_string(),
+                    is_nullable: false,
+                    default: Some("nextval('users_id_seq'::regclass)".to_string()),
+                },
+                ColumnSchema {
+                    name: "email".to_string(),
+                    data_type: "text".to_string(),
+                    is_nullable: true,
+                    default: None,
+                },
+            ],
+            indexes: vec![IndexSchema {
+                name: "users_email_key".to_string(),
+                columns: vec!["email".to_string()],
+                is_unique: true,
+            }],
+        };
+
+        assert_eq!(
+            schema.create_table_statement(),
+            "CREATE TABLE users (\n    id integer NOT NULL DEFAULT nextval('users_id_seq'::regclass),\n    email text\n);\nCREATE UNIQUE INDEX users_email_key ON users (email);"
+        );
+    }
+}
@@ -1,74 +1,153 @@
 use std::sync::Arc;
+use std::time::Instant;
 use async_trait::async_trait;
+use serde_json::Value;
 
-use dfox_core::{db::{mysql::MySqlClient, DbClient}, errors::DbError};
+use dfox_core::models::schema::{RoutineInfo, SessionInfo, TableSchema, TableStats};
+use dfox_core::{db::{mysql::MySqlClient, retry::RetryPolicy, DbClient, Listener}, errors::DbError};
 
-use crate::ui::DatabaseClientUI;
-
-use super::{DatabaseUI, Connect};
+use super::{apply_auto_limit, json_rows_to_lines, returns_rows, with_statement_timeout, Connect, ConnectionParams, DatabaseManager, DatabaseUI, QueryOutput, ResultKind};
 
 pub struct MySqlDatabaseUI {
-    client: DatabaseClientUI,
+    db_manager: Arc<DatabaseManager>,
+    params: ConnectionParams,
 }
 
 impl MySqlDatabaseUI {
-    pub fn new(client: DatabaseClientUI) -> Self {
-        Self { client }
+    pub fn new(db_manager: Arc<DatabaseManager>, params: ConnectionParams) -> Self {
+        Self { db_manager, params }
+    }
+
+    /// Runs `query` on the current connection.
+    async fn run_query(&self, query: &str) -> Result<QueryOutput, DbError> {
+        let mut connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first_mut() {
+            client.set_binary_format(self.params.binary_format);
+            client.set_statement_cache(self.params.statement_cache);
+            let limited_query = self.params.auto_limit.and_then(|limit| apply_auto_limit(query, limit));
+            let query_trimmed = limited_query.as_deref().unwrap_or(query).trim();
+            let query_upper = query_trimmed.to_uppercase();
+            let limit_note = match self.params.auto_limit {
+                Some(limit) if limited_query.is_some() => format!("LIMIT {} applied", limit),
+                _ => String::new(),
+            };
+            let started = Instant::now();
+
+            if !self.params.query_params.is_empty() {
+                let rows = client.query_with_params(query_trimmed, &self.params.query_params).await?;
+                let results = json_rows_to_lines(&rows);
+                let (message, kind) = if !results.is_empty() {
+                    (limit_note, ResultKind::Returned)
+                } else if returns_rows(query_trimmed) {
+                    ("Query returned no results".to_string(), ResultKind::Returned)
+                } else {
+                    // Bound statements go through the row-returning path, which has no count
+                    ("Non-SELECT query executed successfully".to_string(), ResultKind::Affected(None))
+                };
+                return Ok(QueryOutput {
+                    lines: results,
+                    message,
+                    elapsed: started.elapsed(),
+                    kind,
+                    ..QueryOutput::default()
+                });
+            }
+
+            if query_upper.starts_with("CALL") {
+                let mut result_sets = client.query_multi(query_trimmed).await?.into_iter().map(|(column_names, data_rows)| {
+                    let mut lines = vec![column_names.join("\t")];
+                    lines.extend(data_rows.into_iter().map(|row| row.join("\t")));
+                    lines
+                });
+                let lines = result_sets.next().unwrap_or_default();
+                let message = if lines.is_empty() { "Procedure returned no results".to_string() } else { String::new() };
+                return Ok(QueryOutput {
+                    lines,
+                    message,
+                    elapsed: started.elapsed(),
+                    kind: ResultKind::Returned,
+                    result_sets: result_sets.collect(),
+                });
+            }
+
+            if returns_rows(query_trimmed) {
+                let (column_names, data_rows) = client.query_with_column_order(query_trimmed).await?;
+                let elapsed = started.elapsed();
+
+                if column_names.is_empty() {
+                    return Ok(QueryOutput {
+                        message: "Query returned no results".to_string(),
+                        elapsed,
+                        ..QueryOutput::default()
+                    });
+                }
+
+                // Header row followed by tab-separated data rows, same as the Postgres UI
+                let mut results = vec![column_names.join("\t")];
+                results.extend(data_rows.into_iter().map(|row| row.join("\t")));
+
+                Ok(QueryOutput {
+                    lines: results,
+                    message: limit_note,
+                    elapsed,
+                    kind: ResultKind::Returned,
+                    ..QueryOutput::default()
+                })
+            } else {
+                let rows_affected = client.execute(query_trimmed).await?;
+                Ok(QueryOutput {
+                    message: "Non-SELECT query executed successfully".to_string(),
+                    elapsed: started.elapsed(),
+                    kind: ResultKind::Affected(Some(rows_affected)),
+                    ..QueryOutput::default()
+                })
+            }
+        } else {
+            Err(DbError::Connection("No database connection available.".into()))
+        }
     }
 }
 
 #[async_trait]
 impl DatabaseUI for MySqlDatabaseUI {
-    fn db_manager(&self) -> &Arc<super::DatabaseManager> {
-        &self.client.db_manager
+    fn db_manager(&self) -> &Arc<DatabaseManager> {
+        &self.db_manager
     }
 
     fn connection_string(&self) -> String {
-        format!(
-            "mysql://{}:{}@{}:{}/{}",
-            self.client.connection_input.username,
-            self.client.connection_input.password,
-            self.client.connection_input.hostname,
-            self.client.connection_input.port,
-            "mysql"
-        )
+        self.params.url("mysql", self.default_database())
+    }
+
+    fn default_database(&self) -> &'static str {
+        "mysql"
     }
 
-    async fn execute_sql_query(&self, query: &str) -> Result<(Vec<String>, String), DbError> {
+    async fn execute_sql_query(&self, query: &str) -> Result<QueryOutput, DbError> {
+        with_statement_timeout(self.params.statement_timeout, self.run_query(query)).await
+    }
+
+    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
         let connections = self.db_manager().connections.lock().await;
         if let Some(client) = connections.first() {
-            let query_trimmed = query.trim();
-            let query_upper = query_trimmed.to_uppercase();
-
-            if query_upper.starts_with("SELECT") {
-                let rows = client.query(query_trimmed).await?;
-                let results = rows
-                    .into_iter()
-                    .map(|row| row.to_string())
-                    .collect();
-                Ok((results, String::new()))
-            } else {
-                client.execute(query_trimmed).await?;
-                Ok((Vec::new(), "Non-SELECT query executed successfully.".to_string()))
-            }
+            client.describe_table(table_name).await
         } else {
-            Err(DbError::Connection("No database connection available.".into()))
+            Err(DbError::Connection("No database connection found".into()))
         }
     }
 
-    async fn describe_table(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+    async fn fetch_table_stats(&self, table_name: &str) -> Result<TableStats, DbError> {
         let connections = self.db_manager().connections.lock().await;
         if let Some(client) = connections.first() {
-            let schema = client.describe_table(table_name).await?;
-            Ok(schema.columns.into_iter().map(|c| c.name).collect())
+            client.table_stats(table_name).await
         } else {
             Err(DbError::Connection("No database connection found".into()))
         }
     }
 
     async fn fetch_databases(&self) -> Result<Vec<String>, DbError> {
-        let connections = self.db_manager().connections.lock().await;
-        if let Some(client) = connections.first() {
+        let mut connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first_mut() {
+            client.set_show_system_databases(self.params.show_system_databases);
             let databases = client.list_databases().await?;
             Ok(databases)
         } else {
@@ -76,6 +155,42 @@ impl DatabaseUI for MySqlDatabaseUI {
         }
     }
 
+    async fn fetch_server_version(&self) -> Result<String, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.server_version().await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_active_sessions(&self) -> Result<Vec<SessionInfo>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_active_queries().await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn kill_session(&self, pid: i64) -> Result<(), DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.kill_session(pid).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.explain_analyze(query).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
     async fn fetch_tables(&self) -> Result<Vec<String>, DbError> {
         let connections = self.db_manager().connections.lock().await;
         if let Some(client) = connections.first() {
@@ -86,20 +201,60 @@ impl DatabaseUI for MySqlDatabaseUI {
         }
     }
 
-    async fn update_tables(&self) -> Result<(), DbError> {
-        match self.fetch_tables().await {
-            Ok(tables) => {
-                let mut client = self.client.clone();
-                client.tables = tables;
-                client.selected_table = 0;
-                Ok(())
-            }
-            Err(err) => {
-                let mut client = self.client.clone();
-                client.tables = Vec::new();
-                client.selected_table = 0;
-                Err(err)
-            }
+    async fn fetch_routines(&self) -> Result<Vec<RoutineInfo>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_routines().await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn fetch_primary_key(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.primary_key_columns(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_column_names(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_columns(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn listen(&self, channel: &str) -> Result<Box<dyn Listener>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.listen(channel).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn detached_client(&self) -> Result<Box<dyn DbClient + Send + Sync>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            let mut client = client.clone_client();
+            client.set_binary_format(self.params.binary_format);
+            client.set_statement_cache(self.params.statement_cache);
+            Ok(client)
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.execute_with_params(query, params).await
+        } else {
+            Err(DbError::Connection("No database connection available.".into()))
         }
     }
 
@@ -107,16 +262,9 @@ impl DatabaseUI for MySqlDatabaseUI {
         let mut connections = self.db_manager().connections.lock().await;
         connections.clear();
 
-        let connection_string = format!(
-            "mysql://{}:{}@{}:{}/{}",
-            self.client.connection_input.username,
-            self.client.connection_input.password,
-            self.client.connection_input.hostname,
-            self.client.connection_input.port,
-            db_name
-        );
+        let connection_string = self.params.url("mysql", db_name);
 
-        let client = <MySqlClient as Connect>::connect(&connection_string).await?;
+        let client = <MySqlClient as Connect>::connect(&connection_string, &self.db_manager().retry_policy).await?;
         connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
 
         Ok(())
@@ -127,16 +275,24 @@ impl DatabaseUI for MySqlDatabaseUI {
         connections.clear();
 
         let connection_string = self.connection_string();
-        let client = <MySqlClient as Connect>::connect(&connection_string).await?;
+        let client = <MySqlClient as Connect>::connect(&connection_string, &self.db_manager().retry_policy).await?;
         connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
 
         Ok(())
     }
+
+    async fn test_connection(&self) -> Result<String, DbError> {
+        let no_retry = RetryPolicy { max_retries: 0, ..self.db_manager().retry_policy };
+        let client = <MySqlClient as Connect>::connect(&self.connection_string(), &no_retry).await?;
+        let version = client.server_version().await;
+        client.close().await;
+        version
+    }
 }
 
 #[async_trait]
 impl Connect for MySqlClient {
-    async fn connect(database_url: &str) -> Result<Self, DbError> {
-        MySqlClient::connect(database_url).await
+    async fn connect(database_url: &str, retry_policy: &RetryPolicy) -> Result<Self, DbError> {
+        MySqlClient::connect_with_retry(database_url, retry_policy).await
     }
 }
@@ -0,0 +1,269 @@
+use std::sync::Arc;
+use std::time::Instant;
+use async_trait::async_trait;
+use serde_json::Value;
+
+use dfox_core::models::schema::{RoutineInfo, SessionInfo, TableSchema, TableStats};
+use dfox_core::{db::{sqlite::SqliteClient, DbClient, Listener}, errors::DbError};
+
+use super::{apply_auto_limit, json_rows_to_lines, returns_rows, with_statement_timeout, ConnectionParams, DatabaseManager, DatabaseUI, QueryOutput, ResultKind};
+
+/// An in-memory SQLite database to try queries on without a server. Its contents are gone
+/// once the connection is dropped.
+pub struct SqliteDatabaseUI {
+    db_manager: Arc<DatabaseManager>,
+    params: ConnectionParams,
+}
+
+impl SqliteDatabaseUI {
+    pub fn new(db_manager: Arc<DatabaseManager>, params: ConnectionParams) -> Self {
+        Self { db_manager, params }
+    }
+
+    /// Runs `query` on the current connection.
+    async fn run_query(&self, query: &str) -> Result<QueryOutput, DbError> {
+        let mut connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first_mut() {
+            client.set_binary_format(self.params.binary_format);
+            client.set_statement_cache(self.params.statement_cache);
+            let limited_query = self.params.auto_limit.and_then(|limit| apply_auto_limit(query, limit));
+            let query_trimmed = limited_query.as_deref().unwrap_or(query).trim();
+            let limit_note = match self.params.auto_limit {
+                Some(limit) if limited_query.is_some() => format!("LIMIT {} applied", limit),
+                _ => String::new(),
+            };
+            let started = Instant::now();
+
+            if !self.params.query_params.is_empty() {
+                let rows = client.query_with_params(query_trimmed, &self.params.query_params).await?;
+                let results = json_rows_to_lines(&rows);
+                let (message, kind) = if !results.is_empty() {
+                    (limit_note, ResultKind::Returned)
+                } else if returns_rows(query_trimmed) {
+                    ("Query returned no results".to_string(), ResultKind::Returned)
+                } else {
+                    // Bound statements go through the row-returning path, which has no count
+                    ("Non-SELECT query executed successfully".to_string(), ResultKind::Affected(None))
+                };
+                return Ok(QueryOutput {
+                    lines: results,
+                    message,
+                    elapsed: started.elapsed(),
+                    kind,
+                    ..QueryOutput::default()
+                });
+            }
+
+            if returns_rows(query_trimmed) {
+                let (column_names, data_rows) = client.query_with_column_order(query_trimmed).await?;
+                let elapsed = started.elapsed();
+
+                if column_names.is_empty() {
+                    return Ok(QueryOutput {
+                        message: "Query returned no results".to_string(),
+                        elapsed,
+                        ..QueryOutput::default()
+                    });
+                }
+
+                // Header row followed by tab-separated data rows, same as the Postgres UI
+                let mut results = vec![column_names.join("\t")];
+                results.extend(data_rows.into_iter().map(|row| row.join("\t")));
+
+                Ok(QueryOutput {
+                    lines: results,
+                    message: limit_note,
+                    elapsed,
+                    kind: ResultKind::Returned,
+                    ..QueryOutput::default()
+                })
+            } else {
+                let rows_affected = client.execute(query_trimmed).await?;
+                Ok(QueryOutput {
+                    message: "Non-SELECT query executed successfully".to_string(),
+                    elapsed: started.elapsed(),
+                    kind: ResultKind::Affected(Some(rows_affected)),
+                    ..QueryOutput::default()
+                })
+            }
+        } else {
+            Err(DbError::Connection("No database connection available.".into()))
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseUI for SqliteDatabaseUI {
+    fn db_manager(&self) -> &Arc<DatabaseManager> {
+        &self.db_manager
+    }
+
+    fn connection_string(&self) -> String {
+        "sqlite::memory:".to_string()
+    }
+
+    fn default_database(&self) -> &'static str {
+        "main"
+    }
+
+    async fn execute_sql_query(&self, query: &str) -> Result<QueryOutput, DbError> {
+        with_statement_timeout(self.params.statement_timeout, self.run_query(query)).await
+    }
+
+    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.describe_table(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_table_stats(&self, table_name: &str) -> Result<TableStats, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.table_stats(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_databases(&self) -> Result<Vec<String>, DbError> {
+        let mut connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first_mut() {
+            client.set_show_system_databases(self.params.show_system_databases);
+            let databases = client.list_databases().await?;
+            Ok(databases)
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_server_version(&self) -> Result<String, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.server_version().await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_active_sessions(&self) -> Result<Vec<SessionInfo>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_active_queries().await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn kill_session(&self, pid: i64) -> Result<(), DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.kill_session(pid).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn explain_analyze(&self, query: &str) -> Result<Vec<Value>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.explain_analyze(query).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_tables(&self) -> Result<Vec<String>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            let tables = client.list_tables().await?;
+            Ok(tables)
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn fetch_routines(&self) -> Result<Vec<RoutineInfo>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_routines().await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    async fn fetch_primary_key(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.primary_key_columns(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn fetch_column_names(&self, table_name: &str) -> Result<Vec<String>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.list_columns(table_name).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn listen(&self, channel: &str) -> Result<Box<dyn Listener>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.listen(channel).await
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn detached_client(&self) -> Result<Box<dyn DbClient + Send + Sync>, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            let mut client = client.clone_client();
+            client.set_binary_format(self.params.binary_format);
+            client.set_statement_cache(self.params.statement_cache);
+            Ok(client)
+        } else {
+            Err(DbError::Connection("No database connection found".into()))
+        }
+    }
+
+    async fn execute_with_params(&self, query: &str, params: &[Value]) -> Result<u64, DbError> {
+        let connections = self.db_manager().connections.lock().await;
+        if let Some(client) = connections.first() {
+            client.execute_with_params(query, params).await
+        } else {
+            Err(DbError::Connection("No database connection available.".into()))
+        }
+    }
+
+    async fn connect_to_selected_db(&self, _db_name: &str) -> Result<(), DbError> {
+        // "main" is the only database; reconnecting would throw away the scratch tables
+        if self.db_manager().connections.lock().await.is_empty() {
+            return self.connect_to_default_db().await;
+        }
+        Ok(())
+    }
+
+    async fn connect_to_default_db(&self) -> Result<(), DbError> {
+        let mut connections = self.db_manager().connections.lock().await;
+        connections.clear();
+
+        let client = SqliteClient::connect_in_memory().await?;
+        connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
+
+        Ok(())
+    }
+
+    async fn test_connection(&self) -> Result<String, DbError> {
+        let client = SqliteClient::connect_in_memory().await?;
+        let version = client.server_version().await;
+        client.close().await;
+        version
+    }
+}
@@ -0,0 +1,138 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use dfox_core::{db::DbClient, errors::DbError};
+use serde_json::Value;
+
+/// Quotes a CSV field when it contains a separator, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One CSV line for a streamed row. NULL becomes an empty field.
+fn csv_record<'a>(values: impl Iterator<Item = &'a Value>) -> String {
+    values
+        .map(|value| match value {
+            Value::Null => String::new(),
+            Value::String(s) => csv_field(s),
+            other => csv_field(&other.to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Counts the records in CSV text that arrives in chunks: line breaks outside quoted
+/// fields. Doubled quotes inside a field toggle twice and cancel out.
+#[derive(Default)]
+struct CsvRecordCounter {
+    in_quotes: bool,
+}
+
+impl CsvRecordCounter {
+    fn count(&mut self, chunk: &[u8]) -> u64 {
+        let mut records = 0;
+        for &byte in chunk {
+            match byte {
+                b'"' => self.in_quotes = !self.in_quotes,
+                b'\n' if !self.in_quotes => records += 1,
+                _ => {}
+            }
+        }
+        records
+    }
+}
+
+/// File a table export is written to: the table name with anything that is not safe
+/// in a file name replaced by `_`.
+pub fn export_file_name(table: &str) -> String {
+    let name: String = table
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}.csv", name)
+}
+
+/// Streams every row of `quoted_table`, a table name already quoted for the server, into
+/// a CSV file at `path`, one row at a time, so the table never has to fit in memory.
+/// `progress` counts the rows written so far. With `native_copy` the server writes the
+/// CSV itself through `COPY`, which is much faster.
+pub async fn export_table_csv(
+    client: &dyn DbClient,
+    quoted_table: &str,
+    path: &Path,
+    progress: &AtomicU64,
+    native_copy: bool,
+) -> Result<u64, DbError> {
+    let export_error = |e: std::io::Error| DbError::Export(format!("{}: {}", path.display(), e));
+    let mut writer = BufWriter::new(File::create(path).map_err(export_error)?);
+    let query = format!("SELECT * FROM {}", quoted_table);
+
+    if native_copy {
+        let mut counter = CsvRecordCounter::default();
+        let mut records = 0;
+        client
+            .copy_out_csv(&query, &mut |chunk| {
+                writer.write_all(chunk).map_err(export_error)?;
+                records += counter.count(chunk);
+                // Not counting the header line
+                progress.store(records.saturating_sub(1), Ordering::Relaxed);
+                Ok(())
+            })
+            .await?;
+        writer.flush().map_err(export_error)?;
+        return Ok(records.saturating_sub(1));
+    }
+
+    let mut header_written = false;
+    let mut write_row = |row: Value| -> Result<(), DbError> {
+        let Value::Object(row) = row else {
+            return Ok(());
+        };
+        if !header_written {
+            let header: Vec<String> = row.keys().map(|name| csv_field(name)).collect();
+            writeln!(writer, "{}", header.join(",")).map_err(export_error)?;
+            header_written = true;
+        }
+        writeln!(writer, "{}", csv_record(row.values())).map_err(export_error)?;
+        progress.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    };
+    let rows = client.stream_rows(&query, &mut write_row).await?;
+
+    writer.flush().map_err(export_error)?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_record_quotes_and_blanks_nulls() {
+        let row = serde_json::json!({ "id": 7, "note": "a, \"b\"", "deleted_at": null });
+        let record = csv_record(row.as_object().unwrap().values());
+        assert_eq!(record, "7,\"a, \"\"b\"\"\",");
+    }
+
+    #[test]
+    fn test_csv_record_counter_ignores_quoted_line_breaks() {
+        let mut counter = CsvRecordCounter::default();
+        assert_eq!(counter.count(b"id,note\n1,\"two\nlines"), 1);
+        assert_eq!(counter.count(b"\"\n2,\"say \"\"hi\"\"\"\n"), 2);
+    }
+
+    #[test]
+    fn test_export_file_name() {
+        assert_eq!(export_file_name("users"), "users.csv");
+        assert_eq!(export_file_name("audit.log entries"), "audit.log_entries.csv");
+        assert_eq!(export_file_name("../etc/passwd"), ".._etc_passwd.csv");
+    }
+}
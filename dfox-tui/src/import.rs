@@ -0,0 +1,79 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use dfox_core::{db::DbClient, errors::DbError};
+
+/// Splits one CSV line into its fields, unquoting quoted ones.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// The file's columns when `first_line` is a header, i.e. every field names a column of
+/// the table (ignoring case). Returned in file order, spelled as in the table.
+pub fn header_columns(first_line: &str, table_columns: &[String]) -> Option<Vec<String>> {
+    csv_fields(first_line.trim_end_matches(['\r', '\n']))
+        .iter()
+        .map(|field| {
+            table_columns
+                .iter()
+                .find(|column| column.eq_ignore_ascii_case(field.trim()))
+                .cloned()
+        })
+        .collect()
+}
+
+/// Loads the CSV file at `path` into `table` through the server's `COPY`. A header line
+/// decides which columns the file fills; without one the file must hold every column
+/// in table order.
+pub async fn import_table_csv(client: &dyn DbClient, table: &str, path: &Path) -> Result<u64, DbError> {
+    let import_error = |e: std::io::Error| DbError::Import(format!("{}: {}", path.display(), e));
+    let mut first_line = String::new();
+    BufReader::new(File::open(path).map_err(import_error)?)
+        .read_line(&mut first_line)
+        .map_err(import_error)?;
+
+    let table_columns: Vec<String> = client
+        .describe_table(table)
+        .await?
+        .columns
+        .into_iter()
+        .map(|column| column.name)
+        .collect();
+    match header_columns(&first_line, &table_columns) {
+        Some(columns) => client.copy_in_csv(table, &columns, path, true).await,
+        None => client.copy_in_csv(table, &[], path, false).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_columns_matches_table_columns() {
+        let columns = vec!["id".to_string(), "email".to_string(), "note".to_string()];
+        assert_eq!(
+            header_columns("Email,\"id\"\r\n", &columns),
+            Some(vec!["email".to_string(), "id".to_string()])
+        );
+        assert_eq!(header_columns("1,a@b.c,\"x, y\"\n", &columns), None);
+        assert_eq!(csv_fields("1,\"say \"\"hi\"\"\",,"), vec!["1", "say \"hi\"", "", ""]);
+    }
+}
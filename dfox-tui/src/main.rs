@@ -1,13 +1,41 @@
 use std::sync::Arc;
 use std::env;
+use std::time::Duration;
 
-use dfox_core::DbManager;
-use ui::DatabaseClientUI;
+use clap::Parser;
+use cli::Cli;
+use dfox_core::{
+    db::{postgres::notices, retry::RetryPolicy},
+    models::connections::DbType,
+    DbManager,
+};
+use state::LastConnection;
+use ui::{BoolDisplay, ClipboardMethod, DatabaseClientUI, Theme};
+mod cli;
 mod db;
+mod export;
+mod import;
+mod state;
 mod ui;
 
+/// Connection retry settings, overridable with DFOX_CONNECT_RETRIES=<count> and
+/// DFOX_CONNECT_BACKOFF_MS=<initial delay in milliseconds>.
+fn connect_retry_policy() -> RetryPolicy {
+    let mut policy = RetryPolicy::default();
+    if let Some(retries) = env::var("DFOX_CONNECT_RETRIES").ok().and_then(|v| v.parse().ok()) {
+        policy.max_retries = retries;
+    }
+    if let Some(ms) = env::var("DFOX_CONNECT_BACKOFF_MS").ok().and_then(|v| v.parse().ok()) {
+        policy.initial_backoff = Duration::from_millis(ms);
+        policy.max_backoff = policy.max_backoff.max(policy.initial_backoff);
+    }
+    policy
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Cli::parse();
+
     // Load .env file if it exists
     dotenv::dotenv().ok();
     
@@ -17,18 +45,180 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // Initialize logging to file
-    env_logger::Builder::from_default_env()
+    let logger = env_logger::Builder::from_default_env()
         .target(env_logger::Target::Pipe(Box::new(std::fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open("dfox-debug.log")?)))
-        .init();
+        .build();
+    // Postgres notices are captured through the logger so queries can show them
+    notices::init(logger)?;
 
     log::info!("Starting dfox application");
 
-    let db_manager = Arc::new(DbManager::new());
-    let mut tui = DatabaseClientUI::new(db_manager);
-    tui.run_ui().await?;
+    // Command line arguments take precedence over the environment
+    let url = args.url.clone().or_else(|| env::var("DFOX_DATABASE_URL").ok());
+    let db_type = args.db_type.clone().or_else(|| env::var("DFOX_DB_TYPE").ok());
+    let retry_policy = connect_retry_policy();
+
+    if (args.execute.is_some() || args.file.is_some()) && url.is_none() {
+        eprintln!("--execute and --file need a database URL: pass --url or set DFOX_DATABASE_URL");
+        std::process::exit(2);
+    }
+
+    if let (Some(query), Some(url)) = (&args.execute, &url) {
+        let result = match cli::connect(url, db_type.as_deref(), retry_policy).await {
+            Ok(db_manager) => {
+                let result = cli::execute(&db_manager, query, args.format).await;
+                db_manager.disconnect_all().await;
+                result
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let (Some(path), Some(url)) = (&args.file, &url) {
+        let result = match cli::connect(url, db_type.as_deref(), retry_policy).await {
+            Ok(db_manager) => {
+                let mysql = matches!(cli::resolve_db_type(url, db_type.as_deref()), Ok(DbType::MySql));
+                let result = cli::run_file(&db_manager, path, mysql).await;
+                db_manager.disconnect_all().await;
+                result
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut db_manager = DbManager::new();
+    db_manager.retry_policy = retry_policy;
+    let db_manager = Arc::new(db_manager);
+    let mut tui = DatabaseClientUI::new(Arc::clone(&db_manager));
+
+    // DFOX_AUTO_LIMIT=<rows> turns on automatic LIMIT injection for unbounded SELECTs
+    if let Some(rows) = env::var("DFOX_AUTO_LIMIT").ok().and_then(|v| v.parse().ok()) {
+        tui.auto_limit_enabled = true;
+        tui.auto_limit_rows = rows;
+    }
+    // DFOX_SHOW_SYSTEM_DATABASES=1 lists postgres, information_schema, ... as well
+    if env::var("DFOX_SHOW_SYSTEM_DATABASES").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+        tui.show_system_databases = true;
+    }
+    // DFOX_KEEP_EDITOR=0 clears the editor when F1 goes back to the database list
+    if env::var("DFOX_KEEP_EDITOR").is_ok_and(|v| v == "0" || v.eq_ignore_ascii_case("false")) {
+        tui.keep_editor_on_f1 = false;
+    }
+    // DFOX_UPPERCASE_KEYWORDS=1 upper-cases SQL keywords in the editor as they are typed
+    if env::var("DFOX_UPPERCASE_KEYWORDS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+        tui.uppercase_keywords = true;
+    }
+    // DFOX_STATEMENT_CACHE=0 prepares every query afresh instead of reusing the prepared
+    // statement when the same query runs again
+    if env::var("DFOX_STATEMENT_CACHE").is_ok_and(|v| v == "0" || v.eq_ignore_ascii_case("false")) {
+        tui.statement_cache_enabled = false;
+    }
+    // DFOX_STATEMENT_TIMEOUT_SECS=<seconds> abandons queries from the editor that run longer
+    if let Some(limit) = env::var("DFOX_STATEMENT_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .filter(|limit| !limit.is_zero())
+    {
+        tui.statement_timeout = Some(limit);
+    }
+    // DFOX_READ_ONLY_SELECTS=1 runs SELECTs in a read-only transaction on Postgres
+    if env::var("DFOX_READ_ONLY_SELECTS").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")) {
+        tui.read_only_selects = true;
+    }
+    // DFOX_MAX_DISPLAY_ROWS=<rows> keeps more or fewer than 1000 rows from each query
+    if let Some(rows) = env::var("DFOX_MAX_DISPLAY_ROWS").ok().and_then(|v| v.parse().ok()).filter(|&rows| rows > 0) {
+        tui.max_display_rows = rows;
+    }
+    // DFOX_COUNT_TOTAL_ROWS=0 skips counting the full size of truncated results
+    if env::var("DFOX_COUNT_TOTAL_ROWS").is_ok_and(|v| v == "0" || v.eq_ignore_ascii_case("false")) {
+        tui.count_total_rows = false;
+    }
+    // DFOX_BOOL_DISPLAY=symbols|letters shows booleans as ✓/✗ or Y/N
+    if let Some(display) = env::var("DFOX_BOOL_DISPLAY").ok().and_then(|v| BoolDisplay::from_name(&v)) {
+        tui.bool_display = display;
+    }
+    // DFOX_THEME=light switches to colors readable on a light terminal background
+    if let Some(theme) = env::var("DFOX_THEME").ok().and_then(|v| Theme::from_name(&v)) {
+        tui.theme = theme;
+    }
+    // DFOX_CLIPBOARD=osc52 copies through the terminal, e.g. over SSH; system never falls back to it
+    if let Some(method) = env::var("DFOX_CLIPBOARD").ok().and_then(|v| ClipboardMethod::from_name(&v)) {
+        tui.clipboard_method = method;
+    }
+    // DFOX_MAX_COLUMN_WIDTH and DFOX_CELL_TRUNCATE set the starting result width limits
+    if let Some(width) = env::var("DFOX_MAX_COLUMN_WIDTH").ok().and_then(|v| v.parse().ok()) {
+        tui.max_column_width = width;
+    }
+    if let Some(length) = env::var("DFOX_CELL_TRUNCATE").ok().and_then(|v| v.parse().ok()) {
+        tui.cell_truncate_length = length;
+    }
+    // DFOX_FLOAT_PRECISION=<digits> rounds floats in the results; they are shown unrounded by default
+    if let Some(precision) = env::var("DFOX_FLOAT_PRECISION").ok().and_then(|v| v.parse().ok()) {
+        tui.float_precision = Some(precision);
+    }
+    // DFOX_REFRESH_SECS=<seconds> sets how often F8 re-runs the query
+    if let Some(interval) = env::var("DFOX_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .filter(|interval| !interval.is_zero())
+    {
+        tui.auto_refresh_interval = interval;
+    }
+
+    // Start from the database type and server used last time
+    let state_file = state::state_file();
+    if let Some(last) = state_file.as_deref().and_then(LastConnection::load) {
+        tui.selected_db_type = last.db_type.min(3);
+        tui.connection_input.hostname = last.hostname;
+        tui.connection_input.port = last.port;
+        tui.connection_input.username = last.username;
+        tui.recent_databases = last.recent_databases;
+    }
+    tui.notes_file = state::notes_file();
+    if let Some(path) = &tui.notes_file {
+        tui.notes = state::load_texts(path);
+    }
+    tui.snippets_file = state::snippets_file();
+    if let Some(path) = &tui.snippets_file {
+        tui.snippets = state::load_texts(path);
+    }
+
+    if let Some(url) = url {
+        tui.auto_connect(&url, db_type.as_deref()).await;
+    }
+
+    let result = tui.run_ui().await;
+    tui.close_notes();
+    db_manager.disconnect_all().await;
+
+    if let Some(path) = &state_file {
+        let last = LastConnection {
+            db_type: tui.selected_db_type,
+            hostname: tui.connection_input.hostname.clone(),
+            port: tui.connection_input.port.clone(),
+            username: tui.connection_input.username.clone(),
+            recent_databases: tui.recent_databases.clone(),
+        };
+        if let Err(e) = last.save(path) {
+            log::warn!("Could not save the last connection to {}: {}", path.display(), e);
+        }
+    }
+    result?;
 
     log::info!("dfox application finished");
     Ok(())
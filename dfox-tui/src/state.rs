@@ -0,0 +1,145 @@
+use std::{
+    collections::BTreeMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+/// The database type and connection details used last, so the next launch can start
+/// from them, and the databases opened recently on each server. The password is never
+/// stored.
+#[derive(Debug, PartialEq)]
+pub struct LastConnection {
+    pub db_type: usize,
+    pub hostname: String,
+    pub port: String,
+    pub username: String,
+    /// Most recently opened first, keyed by `host:port`.
+    pub recent_databases: BTreeMap<String, Vec<String>>,
+}
+
+impl LastConnection {
+    pub fn load(path: &Path) -> Option<Self> {
+        let state: Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        let field = |name: &str| state[name].as_str().unwrap_or_default().to_string();
+        Some(Self {
+            db_type: state["db_type"].as_u64()? as usize,
+            hostname: field("hostname"),
+            port: field("port"),
+            username: field("username"),
+            recent_databases: state["recent_databases"]
+                .as_object()
+                .map(|servers| {
+                    servers
+                        .iter()
+                        .map(|(server, databases)| {
+                            let databases = databases.as_array().into_iter().flatten();
+                            (server.clone(), databases.filter_map(|db| db.as_str().map(String::from)).collect())
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let state = json!({
+            "db_type": self.db_type,
+            "hostname": self.hostname,
+            "port": self.port,
+            "username": self.username,
+            "recent_databases": self.recent_databases,
+        });
+        fs::write(path, serde_json::to_string_pretty(&state)?)
+    }
+}
+
+/// `$<xdg_var>/dfox/<file>`, falling back to `~/<home_dir>` and, on Windows, `%APPDATA%`
+/// for the directory, unless `override_var` names the file.
+fn user_file(override_var: &str, xdg_var: &str, home_dir: &str, file: &str) -> Option<PathBuf> {
+    if let Some(path) = env::var_os(override_var).filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+    let dir = env::var_os(xdg_var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(home_dir)))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(dir.join("dfox").join(file))
+}
+
+/// Where the last connection is kept: `DFOX_STATE_FILE` if set, otherwise
+/// `$XDG_STATE_HOME/dfox/state.json`, falling back to `~/.local/state` and, on Windows,
+/// `%APPDATA%`.
+pub fn state_file() -> Option<PathBuf> {
+    user_file("DFOX_STATE_FILE", "XDG_STATE_HOME", ".local/state", "state.json")
+}
+
+/// Where saved queries are kept: `DFOX_SNIPPETS_FILE` if set, otherwise
+/// `$XDG_CONFIG_HOME/dfox/snippets.json`, falling back to `~/.config` and, on Windows,
+/// `%APPDATA%`.
+pub fn snippets_file() -> Option<PathBuf> {
+    user_file("DFOX_SNIPPETS_FILE", "XDG_CONFIG_HOME", ".config", "snippets.json")
+}
+
+/// Texts by name, as the notes and saved queries are stored. A missing or unreadable
+/// file gives none.
+pub fn load_texts(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_texts(path: &Path, texts: &BTreeMap<String, String>) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(texts)?)
+}
+
+/// Where the editor notes are kept: `notes.json` next to the state file.
+pub fn notes_file() -> Option<PathBuf> {
+    state_file().map(|path| path.with_file_name("notes.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_connection_round_trip() {
+        let path = env::temp_dir()
+            .join(format!("dfox-state-test-{}", std::process::id()))
+            .join("state.json");
+        let last = LastConnection {
+            db_type: 1,
+            hostname: "db.local".to_string(),
+            port: "3306".to_string(),
+            username: "admin".to_string(),
+            recent_databases: BTreeMap::from([(
+                "db.local:3306".to_string(),
+                vec!["shop".to_string(), "analytics".to_string()],
+            )]),
+        };
+        last.save(&path).unwrap();
+        assert_eq!(LastConnection::load(&path), Some(last));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_texts_round_trip() {
+        let path = env::temp_dir()
+            .join(format!("dfox-notes-test-{}", std::process::id()))
+            .join("notes.json");
+        assert!(load_texts(&path).is_empty());
+        let notes = BTreeMap::from([("admin@db.local:5432".to_string(), "SELECT 1;\n".to_string())]);
+        save_texts(&path, &notes).unwrap();
+        assert_eq!(load_texts(&path), notes);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+// Keywords after which only a table name makes sense
+const TABLE_KEYWORDS: [&str; 5] = ["FROM", "JOIN", "INTO", "UPDATE", "TABLE"];
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Returns the byte offset where the identifier ending at `offset` starts,
+/// together with the identifier itself.
+pub fn current_token(content: &str, offset: usize) -> (usize, &str) {
+    let start = content[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_identifier_char(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    (start, &content[start..offset])
+}
+
+fn previous_word(content: &str, end: usize) -> &str {
+    let trimmed = content[..end].trim_end();
+    let (_, word) = current_token(trimmed, trimmed.len());
+    word
+}
+
+/// Finds the table referenced by the first `FROM <table>` clause, if any.
+pub fn referenced_table(content: &str) -> Option<&str> {
+    let mut words = content.split(|c: char| !is_identifier_char(c)).filter(|w| !w.is_empty());
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("FROM") {
+            return words.next();
+        }
+    }
+    None
+}
+
+/// Builds the completion list for the identifier under the cursor.
+///
+/// Directly after a table keyword only table names are offered; anywhere else the
+/// columns of the table named in the `FROM` clause come first, followed by tables.
+pub fn suggestions(
+    content: &str,
+    offset: usize,
+    tables: &[String],
+    table_columns: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let (start, prefix) = current_token(content, offset);
+    let previous = previous_word(content, start).to_uppercase();
+
+    let mut candidates: Vec<&String> = Vec::new();
+    if !TABLE_KEYWORDS.contains(&previous.as_str()) {
+        if let Some(columns) = referenced_table(content).and_then(|table| table_columns.get(table)) {
+            candidates.extend(columns.iter());
+        }
+    }
+    candidates.extend(tables.iter());
+
+    let prefix = prefix.to_lowercase();
+    let mut result: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate.to_lowercase().starts_with(&prefix) && !result.contains(candidate) {
+            result.push(candidate.clone());
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> HashMap<String, Vec<String>> {
+        let names = ["id", "username", "email"].iter().map(|name| name.to_string()).collect();
+        HashMap::from([("users".to_string(), names)])
+    }
+
+    fn tables() -> Vec<String> {
+        vec!["orders".to_string(), "users".to_string(), "user_roles".to_string()]
+    }
+
+    #[test]
+    fn test_suggests_tables_after_from() {
+        let content = "SELECT * FROM us";
+        let result = suggestions(content, content.len(), &tables(), &columns());
+        assert_eq!(result, vec!["users".to_string(), "user_roles".to_string()]);
+    }
+
+    #[test]
+    fn test_suggests_columns_of_referenced_table() {
+        let content = "SELECT * FROM users WHERE us";
+        let result = suggestions(content, content.len(), &tables(), &columns());
+        assert_eq!(
+            result,
+            vec!["username".to_string(), "users".to_string(), "user_roles".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_current_token() {
+        assert_eq!(current_token("SELECT na", 9), (7, "na"));
+        assert_eq!(current_token("SELECT ", 7), (7, ""));
+    }
+}
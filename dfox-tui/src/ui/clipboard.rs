@@ -0,0 +1,75 @@
+use std::io::{self, Write};
+
+use arboard::Clipboard;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+
+/// How copied text reaches the clipboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ClipboardMethod {
+    /// The system clipboard, falling back to OSC 52 when it can't be reached
+    #[default]
+    Auto,
+    System,
+    /// Ask the terminal to set its clipboard, which works through SSH
+    Osc52,
+}
+
+impl ClipboardMethod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Some(ClipboardMethod::Auto),
+            "system" => Some(ClipboardMethod::System),
+            "osc52" => Some(ClipboardMethod::Osc52),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CopiedVia {
+    System,
+    Terminal,
+}
+
+/// The escape sequence asking the terminal to put `text` on the clipboard.
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", BASE64.encode(text))
+}
+
+fn copy_with_osc52(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
+/// Copies `text` using `method`. Whether the terminal honours OSC 52 can't be checked,
+/// so sending the sequence counts as copied.
+pub fn copy_text(text: String, method: ClipboardMethod) -> Result<CopiedVia, String> {
+    if method == ClipboardMethod::Osc52 {
+        return copy_with_osc52(&text)
+            .map(|()| CopiedVia::Terminal)
+            .map_err(|e| e.to_string());
+    }
+    match Clipboard::new().and_then(|mut ctx| ctx.set_text(text.clone())) {
+        Ok(()) => Ok(CopiedVia::System),
+        Err(e) if method == ClipboardMethod::Auto => {
+            log::warn!("System clipboard unavailable, falling back to OSC 52: {}", e);
+            copy_with_osc52(&text)
+                .map(|()| CopiedVia::Terminal)
+                .map_err(|osc_error| format!("{}; OSC 52: {}", e, osc_error))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("id\t1"), "\x1b]52;c;aWQJMQ==\x07");
+        assert_eq!(ClipboardMethod::from_name("OSC52"), Some(ClipboardMethod::Osc52));
+        assert_eq!(ClipboardMethod::from_name("xclip"), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+
+/// A copy of a query result to compare later runs against, matched row by row on the
+/// value of `key_column`.
+#[derive(Clone, Debug)]
+pub struct ResultSnapshot {
+    pub key_column: String,
+    pub rows: Vec<IndexMap<String, String>>,
+}
+
+/// How a row of the current result differs from the snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowChange {
+    Unchanged,
+    Added,
+    /// The row's key was in the snapshot; holds the columns whose value differs.
+    Changed(Vec<String>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ResultDiff {
+    /// One entry per row of the current result.
+    pub rows: Vec<RowChange>,
+    /// Keys of snapshot rows missing from the current result, in snapshot order.
+    pub removed: Vec<String>,
+}
+
+impl ResultDiff {
+    pub fn added(&self) -> usize {
+        self.rows.iter().filter(|change| **change == RowChange::Added).count()
+    }
+
+    pub fn changed(&self) -> usize {
+        self.rows.iter().filter(|change| matches!(change, RowChange::Changed(_))).count()
+    }
+}
+
+impl ResultSnapshot {
+    /// Compares `current` with the snapshot. Rows without the key column count as added;
+    /// when a key repeats, only its first row is matched.
+    pub fn diff(&self, current: &[IndexMap<String, String>]) -> ResultDiff {
+        let mut previous: HashMap<&str, &IndexMap<String, String>> = HashMap::new();
+        for row in &self.rows {
+            if let Some(key) = row.get(&self.key_column) {
+                previous.entry(key.as_str()).or_insert(row);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let rows = current
+            .iter()
+            .map(|row| {
+                let Some((key, old)) = row
+                    .get(&self.key_column)
+                    .and_then(|key| previous.get(key.as_str()).map(|old| (key, old)))
+                else {
+                    return RowChange::Added;
+                };
+                seen.insert(key.as_str());
+                let changed: Vec<String> = row
+                    .iter()
+                    .filter(|(column, value)| old.get(*column) != Some(*value))
+                    .map(|(column, _)| column.clone())
+                    .collect();
+                if changed.is_empty() {
+                    RowChange::Unchanged
+                } else {
+                    RowChange::Changed(changed)
+                }
+            })
+            .collect();
+
+        let removed = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(&self.key_column))
+            .filter(|key| !seen.contains(key.as_str()))
+            .cloned()
+            .collect();
+
+        ResultDiff { rows, removed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: &[(&str, &str)]) -> IndexMap<String, String> {
+        values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_diff_marks_added_changed_and_removed_rows() {
+        let snapshot = ResultSnapshot {
+            key_column: "id".to_string(),
+            rows: vec![
+                row(&[("id", "1"), ("status", "new")]),
+                row(&[("id", "2"), ("status", "new")]),
+                row(&[("id", "3"), ("status", "new")]),
+            ],
+        };
+        let current = vec![
+            row(&[("id", "1"), ("status", "new")]),
+            row(&[("id", "2"), ("status", "paid")]),
+            row(&[("id", "4"), ("status", "new")]),
+        ];
+
+        let diff = snapshot.diff(&current);
+        assert_eq!(
+            diff.rows,
+            vec![
+                RowChange::Unchanged,
+                RowChange::Changed(vec!["status".to_string()]),
+                RowChange::Added,
+            ]
+        );
+        assert_eq!(diff.removed, vec!["3".to_string()]);
+        assert_eq!((diff.added(), diff.changed()), (1, 1));
+    }
+
+    #[test]
+    fn test_rows_without_key_column_are_added() {
+        let snapshot = ResultSnapshot {
+            key_column: "id".to_string(),
+            rows: vec![row(&[("id", "1")])],
+        };
+        let diff = snapshot.diff(&[row(&[("name", "a")])]);
+        assert_eq!(diff.rows, vec![RowChange::Added]);
+        assert_eq!(diff.removed, vec!["1".to_string()]);
+    }
+}
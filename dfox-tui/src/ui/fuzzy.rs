@@ -0,0 +1,54 @@
+/// Scores `text` against `query` as a case-insensitive subsequence match; lower is
+/// better. Returns `None` when the query characters do not all appear in order.
+pub fn score(text: &str, query: &str) -> Option<usize> {
+    let text = text.to_lowercase();
+    let mut chars = text.char_indices();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.by_ref().find(|(_, c)| *c == q)?;
+        // Gaps between matched characters make a match worse
+        score += last_match.map_or(i, |last| i - last - 1);
+        last_match = Some(i);
+    }
+    Some(score)
+}
+
+/// Items matching `query`, best match first; equally good matches go shortest first,
+/// then in their original order. An empty query keeps everything as it was.
+pub fn rank(items: &[String], query: &str) -> Vec<String> {
+    if query.trim().is_empty() {
+        return items.to_vec();
+    }
+    let mut matches: Vec<(usize, usize, &String)> = items
+        .iter()
+        .filter_map(|item| score(item, query).map(|score| (score, item.chars().count(), item)))
+        .collect();
+    matches.sort_by_key(|(score, len, _)| (*score, *len));
+    matches.into_iter().map(|(_, _, item)| item.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_score_prefers_tighter_matches() {
+        assert_eq!(score("users", "usr"), Some(1));
+        assert_eq!(score("Users", "US"), Some(0));
+        assert!(score("audit_user_sessions", "usr") > score("users", "usr"));
+        assert_eq!(score("orders", "usr"), None);
+    }
+
+    #[test]
+    fn test_rank_orders_by_quality_then_length() {
+        let tables = names(&["audit_user_sessions", "user_roles", "orders", "users"]);
+        assert_eq!(rank(&tables, "usr"), vec!["users", "user_roles", "audit_user_sessions"]);
+        assert_eq!(rank(&tables, ""), tables);
+        assert!(rank(&tables, "xyz").is_empty());
+    }
+}
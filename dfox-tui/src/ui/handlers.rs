@@ -1,25 +1,40 @@
 use std::{
-    io::{self, stdout},
-    process,
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use crossterm::{
-    event::{KeyCode, KeyModifiers, DisableMouseCapture},
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
-    terminal::{disable_raw_mode, LeaveAlternateScreen, Clear, ClearType},
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::layout::Position;
 use ratatui::{prelude::CrosstermBackend, Terminal};
+use tokio::time::timeout;
 
-use crate::db::{DatabaseUI, postgres::PostgresDatabaseUI, mysql::MySqlDatabaseUI};
-use dfox_core::errors::DbError;
-use dfox_core::models::schema::TableSchema;
+use crate::db::{count_from_rows, count_placeholders, count_query, is_mutating_statement, json_rows_to_lines, parse_param_value, statement_at, ConnectionParams, DatabaseUI, QueryOutput, ResultKind, postgres::PostgresDatabaseUI, mysql::MySqlDatabaseUI, sqlite::SqliteDatabaseUI};
+use crate::export::{export_file_name, export_table_csv};
+use crate::import::import_table_csv;
+use crate::state;
+use dfox_core::{db::BinaryFormat, errors::DbError};
+use serde_json::Value;
 
 use super::{
-    components::{FocusedWidget, InputField, ScreenState, MAX_VISIBLE_COLUMNS},
+    autocomplete,
+    clipboard::{self, CopiedVia},
+    diff::ResultSnapshot,
+    fuzzy,
+    help,
+    palette::{self, Command},
+    sql_format,
+    components::{db_type_index, ConnectionInput, CsvImport, FocusedWidget, InputField, NotificationTail, QueryOutcome, ResultSets, RowCount, RunningQuery, ScreenState, StashedQuery, TableExport, CELL_TRUNCATE_STEP, COLUMN_RESIZE_STEP, DISPLAY_ROW_STEPS, MAX_RECENT_DATABASES, MAX_COLUMN_WIDTH, MIN_COLUMN_WIDTH, MAX_UNDO_STEPS, MAX_VISIBLE_COLUMNS},
     DatabaseClientUI, UIHandler, UIRenderer,
 };
 
-use arboard::Clipboard;
 use indexmap::IndexMap;
 
 impl UIHandler for DatabaseClientUI {
@@ -27,172 +42,157 @@ impl UIHandler for DatabaseClientUI {
         self.current_screen = ScreenState::DbTypeSelection
     }
 
-    async fn handle_db_type_selection_input(&mut self, key: KeyCode) {
+    async fn handle_quit_confirmation_input(&mut self, key: KeyCode) -> bool {
         match key {
-            KeyCode::Up => {
-                if self.selected_db_type > 0 {
-                    self.selected_db_type -= 1;
-                }
+            KeyCode::Char('y') | KeyCode::Char('Y') => true,
+            _ => {
+                self.current_screen = self
+                    .previous_screen
+                    .take()
+                    .unwrap_or(ScreenState::TableView);
+                false
             }
-            KeyCode::Down => {
-                if self.selected_db_type < 2 {
-                    self.selected_db_type += 1;
-                }
+        }
+    }
+
+    async fn handle_db_type_selection_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up if self.selected_db_type > 0 => {
+                self.selected_db_type -= 1;
             }
-            KeyCode::Enter => {
-                if self.selected_db_type == 2 {
-                    self.current_screen = ScreenState::MessagePopup;
-                } else {
-                    self.current_screen = ScreenState::ConnectionInput;
-                }
+            KeyCode::Down if self.selected_db_type < 3 => {
+                self.selected_db_type += 1;
             }
+            KeyCode::Enter => match self.selected_db_type {
+                2 => self.current_screen = ScreenState::MessagePopup,
+                3 => self.open_scratch_database().await,
+                _ => self.current_screen = ScreenState::ConnectionInput,
+            },
+            KeyCode::Char('q') if self.has_unsaved_query() => self.prompt_quit_confirmation(),
             KeyCode::Char('q') => {
-                if let Err(e) = disable_raw_mode() {
-                    log::error!("Error disabling raw mode: {}", e);
-                }
-                if let Err(e) = execute!(
-                    stdout(),
-                    LeaveAlternateScreen,
-                    DisableMouseCapture,
-                    Clear(ClearType::All)
-                ) {
-                    log::error!("Error cleaning up terminal: {}", e);
-                }
-                process::exit(0);
+                self.should_quit = true;
             }
             _ => {}
         }
     }
 
     async fn handle_input_event(&mut self, key: KeyCode) -> io::Result<()> {
-        if let Some(_error_message) = &self.connection_error_message {
-            match key {
-                KeyCode::Enter | KeyCode::Esc => {
-                    self.connection_error_message = None;
-                }
-                _ => {}
+        self.connection_input.test_result = None;
+        match key {
+            KeyCode::Esc => {
+                self.connection_input.password_visible = false;
+                self.current_screen = ScreenState::DbTypeSelection;
             }
-        } else {
-            match key {
-                KeyCode::Esc => {
-                    self.current_screen = ScreenState::DbTypeSelection;
-                }
-                KeyCode::Up => {
-                    self.connection_input.current_field = match self.connection_input.current_field
-                    {
-                        InputField::Port => InputField::Hostname,
-                        InputField::Hostname => InputField::Password,
-                        InputField::Password => InputField::Username,
-                        InputField::Username => InputField::Username,
-                    };
-                }
-                KeyCode::Down => {
-                    self.connection_input.current_field = match self.connection_input.current_field
-                    {
-                        InputField::Username => InputField::Password,
-                        InputField::Password => InputField::Hostname,
-                        InputField::Hostname => InputField::Port,
-                        InputField::Port => InputField::Port,
-                    };
-                }
-                _ => match self.connection_input.current_field {
-                    InputField::Username => match key {
-                        KeyCode::Char(c) => self.connection_input.username.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.username.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Password;
-                        }
-                        _ => {}
-                    },
-                    InputField::Password => match key {
-                        KeyCode::Char(c) => self.connection_input.password.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.password.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Hostname;
-                        }
-                        _ => {}
-                    },
-                    InputField::Hostname => match key {
-                        KeyCode::Char(c) => self.connection_input.hostname.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.hostname.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Port;
-                        }
-                        _ => {}
-                    },
-                    InputField::Port => match key {
-                        KeyCode::Char(c) => self.connection_input.port.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.port.pop();
-                        }
-                        KeyCode::Enter => {
-                            let result = match self.selected_db_type {
-                                0 => PostgresDatabaseUI::new(self.clone()).connect_to_default_db().await,
-                                1 => MySqlDatabaseUI::new(self.clone()).connect_to_default_db().await,
-                                _ => Ok(()),
-                            };
-                            if result.is_ok() {
-                                self.current_screen = ScreenState::DatabaseSelection;
-                            }
+            KeyCode::Up => {
+                self.connection_input.current_field = match self.connection_input.current_field
+                {
+                    InputField::Port => InputField::Hostname,
+                    InputField::Hostname => InputField::Password,
+                    InputField::Password => InputField::Username,
+                    InputField::Username => InputField::Username,
+                };
+            }
+            KeyCode::Down => {
+                self.connection_input.current_field = match self.connection_input.current_field
+                {
+                    InputField::Username => InputField::Password,
+                    InputField::Password => InputField::Hostname,
+                    InputField::Hostname => InputField::Port,
+                    InputField::Port => InputField::Port,
+                };
+            }
+            _ => match self.connection_input.current_field {
+                InputField::Username => match key {
+                    KeyCode::Char(c) => self.connection_input.username.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.username.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = InputField::Password;
+                    }
+                    _ => {}
+                },
+                InputField::Password => match key {
+                    KeyCode::Char(c) => self.connection_input.password.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.password.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = InputField::Hostname;
+                    }
+                    _ => {}
+                },
+                InputField::Hostname => match key {
+                    KeyCode::Char(c) => self.connection_input.hostname.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.hostname.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = InputField::Port;
+                    }
+                    _ => {}
+                },
+                InputField::Port => match key {
+                    KeyCode::Char(c) if c.is_ascii_digit() => self.connection_input.port.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.port.pop();
+                    }
+                    KeyCode::Enter => {
+                        if let Err(message) = self.connection_input.parsed_port() {
+                            self.show_error(message);
+                        } else if let Err(err) = self.connect_database(None).await {
+                            self.report_connection_error(&err, None);
+                        } else {
+                            self.connection_input.password_visible = false;
+                            self.refresh_server_version().await;
+                            self.current_screen = ScreenState::DatabaseSelection;
                         }
-                        _ => {}
-                    },
+                    }
+                    _ => {}
                 },
-            }
+            },
         }
         Ok(())
     }
 
     async fn handle_database_selection_input(&mut self, key: KeyCode) -> io::Result<()> {
+        if self.editing_filter && self.edit_list_filter(key) {
+            return Ok(());
+        }
         match key {
-            KeyCode::Up => {
-                if self.selected_database > 0 {
-                    self.selected_database -= 1;
-                    if self.selected_database < self.databases_scroll {
-                        self.databases_scroll = self.selected_database;
-                    }
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Char('?') => self.open_help(),
+            KeyCode::Up if self.selected_database > 0 => {
+                self.selected_database -= 1;
+                if self.selected_database < self.databases_scroll {
+                    self.databases_scroll = self.selected_database;
                 }
             }
-            KeyCode::Down => {
-                if !self.databases.is_empty() && self.selected_database < self.databases.len() - 1 {
-                    self.selected_database += 1;
-                    let visible_height = 20; 
-                    if self.selected_database >= self.databases_scroll + visible_height {
-                        self.databases_scroll = self.selected_database - visible_height + 1;
-                    }
+            KeyCode::Down if !self.databases.is_empty() && self.selected_database < self.databases.len() - 1 => {
+                self.selected_database += 1;
+                let visible_height = self.databases_page_size();
+                if self.selected_database >= self.databases_scroll + visible_height {
+                    self.databases_scroll = self.selected_database - visible_height + 1;
                 }
             }
             KeyCode::Enter => {
                 let cloned = self.databases.clone();
                 if let Some(db_name) = cloned.get(self.selected_database) {
-                    let result = match self.selected_db_type {
-                        0 => PostgresDatabaseUI::new(self.clone()).connect_to_selected_db(db_name).await,
-                        1 => MySqlDatabaseUI::new(self.clone()).connect_to_selected_db(db_name).await,
-                        _ => Ok(()),
-                    };
-                    if let Err(err) = result {
+                    if let Err(err) = self.connect_database(Some(db_name.as_str())).await {
                         log::error!("Error connecting to database: {}", err);
+                        self.report_connection_error(&err, Some(db_name.as_str()));
                     } else {
+                        self.refresh_server_version().await;
                         self.needs_tables_refresh = true;
                         self.current_screen = ScreenState::TableView;
                     }
                 }
             }
+            KeyCode::F(2) => self.needs_db_refresh = true,
+            KeyCode::F(3) => self.toggle_system_databases(),
+            KeyCode::Char('q') if self.has_unsaved_query() => self.prompt_quit_confirmation(),
             KeyCode::Char('q') => {
-                disable_raw_mode()?;
-                execute!(
-                    stdout(),
-                    LeaveAlternateScreen,
-                    DisableMouseCapture,
-                    Clear(ClearType::All)
-                )?;
-                process::exit(0);
+                self.should_quit = true;
             }
             _ => {}
         }
@@ -204,17 +204,50 @@ impl UIHandler for DatabaseClientUI {
         key: KeyCode,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) {
+        if self.editing_filter && self.current_focus == FocusedWidget::TablesList && self.edit_list_filter(key) {
+            return;
+        }
         match key {
+            KeyCode::Char('/') if self.current_focus == FocusedWidget::TablesList && !self.show_routines => {
+                self.editing_filter = true;
+            }
+            KeyCode::Char('?') => self.open_help(),
+            KeyCode::F(2) => self.request_tables_refresh(),
+            KeyCode::F(3) => self.toggle_binary_format(),
+            KeyCode::F(4) => self.toggle_auto_limit(),
+            KeyCode::F(6) => self.toggle_digit_grouping(),
             KeyCode::F(1) => {
-                self.current_screen = ScreenState::DatabaseSelection;
-                self.sql_editor_content.clear();
-                self.sql_query_result.clear();
+                self.return_to_database_selection();
                 if let Err(err) = UIRenderer::render_database_selection_screen(self, terminal).await
                 {
                     log::error!("Error rendering database selection screen: {}", err);
                 }
             }
             KeyCode::Tab => self.cycle_focus(),
+            KeyCode::F(7) => self.toggle_routines_list(),
+            KeyCode::F(8) => self.toggle_auto_refresh(),
+            KeyCode::F(10) => self.open_sessions(),
+            KeyCode::Char('d') if self.current_focus == FocusedWidget::TablesList => {
+                self.copy_create_table_statement()
+            }
+            KeyCode::Char('c') if self.current_focus == FocusedWidget::TablesList => {
+                self.toggle_table_export()
+            }
+            KeyCode::Char('l') if self.current_focus == FocusedWidget::TablesList && !self.show_routines => {
+                self.open_import_prompt()
+            }
+            KeyCode::Char('a') if self.current_focus == FocusedWidget::TablesList && !self.show_routines => {
+                self.open_table_stats()
+            }
+            KeyCode::Up if self.show_routines && self.current_focus == FocusedWidget::TablesList => {
+                self.selected_routine = self.selected_routine.saturating_sub(1);
+            }
+            KeyCode::Down if self.show_routines && self.current_focus == FocusedWidget::TablesList => {
+                self.selected_routine = (self.selected_routine + 1).min(self.routines.len().saturating_sub(1));
+            }
+            KeyCode::Enter if self.show_routines && self.current_focus == FocusedWidget::TablesList => {
+                self.insert_routine_call();
+            }
             KeyCode::Up => {
                 if let FocusedWidget::TablesList = self.current_focus {
                     self.move_selection_up();
@@ -234,7 +267,7 @@ impl UIHandler for DatabaseClientUI {
                 } else if let FocusedWidget::_QueryResult = self.current_focus {
                     if !self.sql_query_result.is_empty() && self.selected_result_row < self.sql_query_result.len().saturating_sub(1) {
                         self.selected_result_row += 1;
-                        let visible_height = 20; // Adjust based on terminal size
+                        let visible_height = self.result_page_size();
                         if self.selected_result_row >= self.sql_result_scroll + visible_height {
                             self.sql_result_scroll = self.selected_result_row.saturating_sub(visible_height - 1);
                         }
@@ -244,9 +277,12 @@ impl UIHandler for DatabaseClientUI {
             }
             KeyCode::Left => {
                 if self.current_focus == FocusedWidget::_QueryResult && !self.sql_query_result.is_empty() {
-                    if self.sql_result_horizontal_scroll > 0 {
-                        self.sql_result_horizontal_scroll = self.sql_result_horizontal_scroll.saturating_sub(1);
-                        self.add_debug_info(format!("Horizontal scroll left to: {}", self.sql_result_horizontal_scroll));
+                    if self.selected_result_column > 0 {
+                        self.selected_result_column -= 1;
+                        if self.selected_result_column < self.sql_result_horizontal_scroll {
+                            self.sql_result_horizontal_scroll = self.selected_result_column;
+                            self.add_debug_info(format!("Horizontal scroll left to: {}", self.sql_result_horizontal_scroll));
+                        }
                     }
                 } else if self.current_focus == FocusedWidget::SqlEditor {
                     if self.sql_editor_cursor_x > 0 {
@@ -265,16 +301,18 @@ impl UIHandler for DatabaseClientUI {
                     };
                     
                     let max_visible_columns = MAX_VISIBLE_COLUMNS; // Should match the value in screens.rs
-                    let max_scroll = if total_columns > max_visible_columns {
-                        total_columns - max_visible_columns
-                    } else {
-                        0
-                    };
+                    let max_scroll = total_columns.saturating_sub(max_visible_columns);
                     
-                    if self.sql_result_horizontal_scroll < max_scroll {
-                        self.sql_result_horizontal_scroll += 1;
-                        self.add_debug_info(format!("Horizontal scroll right to: {} (max: {})", 
-                            self.sql_result_horizontal_scroll, max_scroll));
+                    if self.selected_result_column + 1 < total_columns {
+                        self.selected_result_column += 1;
+                        if self.selected_result_column >= self.sql_result_horizontal_scroll + max_visible_columns {
+                            self.sql_result_horizontal_scroll = std::cmp::min(
+                                self.selected_result_column + 1 - max_visible_columns,
+                                max_scroll,
+                            );
+                            self.add_debug_info(format!("Horizontal scroll right to: {} (max: {})", 
+                                self.sql_result_horizontal_scroll, max_scroll));
+                        }
                     } else {
                         self.add_debug_info(format!("Already at rightmost position: {} columns, {} visible", 
                             total_columns, max_visible_columns));
@@ -284,55 +322,96 @@ impl UIHandler for DatabaseClientUI {
                         .lines()
                         .nth(self.sql_editor_cursor_y)
                         .unwrap_or("");
-                    if self.sql_editor_cursor_x < current_line.len() {
+                    if self.sql_editor_cursor_x < current_line.chars().count() {
                         self.sql_editor_cursor_x += 1;
                     }
                     self.sync_cursor_position();
                 }
             }
-            KeyCode::PageUp => {
-                if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() {
-                    let page_size = 10;
-                    if self.selected_result_row >= page_size {
-                        self.selected_result_row -= page_size;
-                    } else {
-                        self.selected_result_row = 0;
-                    }
-                    self.sql_result_scroll = self.selected_result_row;
-                    self.sync_cursor_position();
-                }
-            }
-            KeyCode::PageDown => {
-                if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() {
-                    let page_size = 10;
-                    let max_row = self.sql_query_result.len().saturating_sub(1);
-                    if self.selected_result_row + page_size <= max_row {
-                        self.selected_result_row += page_size;
-                    } else {
-                        self.selected_result_row = max_row;
-                    }
-                    let visible_height = 20;
-                    if self.selected_result_row >= self.sql_result_scroll + visible_height {
-                        self.sql_result_scroll = self.selected_result_row.saturating_sub(visible_height - 1);
-                    }
-                    self.sync_cursor_position();
-                }
-            }
-            KeyCode::Home => {
-                if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() {
+            KeyCode::PageUp if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() => {
+                let page_size = self.result_page_size();
+                if self.selected_result_row >= page_size {
+                    self.selected_result_row -= page_size;
+                } else {
                     self.selected_result_row = 0;
-                    self.sql_result_scroll = 0;
-                    self.sql_result_horizontal_scroll = 0;
-                    self.sync_cursor_position();
                 }
+                self.sql_result_scroll = self.selected_result_row;
+                self.sync_cursor_position();
             }
-            KeyCode::End => {
-                if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() {
-                    self.selected_result_row = self.sql_query_result.len().saturating_sub(1);
-                    let visible_height = 20;
+            KeyCode::PageDown if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() => {
+                let page_size = self.result_page_size();
+                let max_row = self.sql_query_result.len().saturating_sub(1);
+                if self.selected_result_row + page_size <= max_row {
+                    self.selected_result_row += page_size;
+                } else {
+                    self.selected_result_row = max_row;
+                }
+                let visible_height = page_size;
+                if self.selected_result_row >= self.sql_result_scroll + visible_height {
                     self.sql_result_scroll = self.selected_result_row.saturating_sub(visible_height - 1);
-                    self.sync_cursor_position();
                 }
+                self.sync_cursor_position();
+            }
+            KeyCode::Home if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() => {
+                self.selected_result_row = 0;
+                self.selected_result_column = 0;
+                self.sql_result_scroll = 0;
+                self.sql_result_horizontal_scroll = 0;
+                self.sync_cursor_position();
+            }
+            KeyCode::End if matches!(self.current_focus, FocusedWidget::_QueryResult) && !self.sql_query_result.is_empty() => {
+                self.selected_result_row = self.sql_query_result.len().saturating_sub(1);
+                let visible_height = self.result_page_size();
+                self.sql_result_scroll = self.selected_result_row.saturating_sub(visible_height - 1);
+                self.sync_cursor_position();
+            }
+            KeyCode::Enter if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.open_cell_detail();
+            }
+            KeyCode::Char('x') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.toggle_row_expansion();
+            }
+            KeyCode::Char('i') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.copy_row_as_insert();
+            }
+            KeyCode::Char('e') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.start_cell_edit();
+            }
+            KeyCode::Char('s') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.toggle_result_snapshot();
+            }
+            KeyCode::Char('b') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.cycle_bool_display();
+            }
+            KeyCode::Char('v') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.vertical_results = !self.vertical_results;
+            }
+            KeyCode::Char('<') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_max_column_width(-COLUMN_RESIZE_STEP);
+            }
+            KeyCode::Char('>') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_max_column_width(COLUMN_RESIZE_STEP);
+            }
+            KeyCode::Char('-') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_max_display_rows(false);
+            }
+            KeyCode::Char('+') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_max_display_rows(true);
+            }
+            KeyCode::Char('n') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.show_result_set(true);
+            }
+            KeyCode::Char('p') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.show_result_set(false);
+            }
+            KeyCode::Char('[') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_cell_truncate_length(false);
+            }
+            KeyCode::Char(']') if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.adjust_cell_truncate_length(true);
+            }
+            KeyCode::Delete if matches!(self.current_focus, FocusedWidget::_QueryResult) => {
+                self.confirm_row_delete();
             }
             KeyCode::Enter => {
                 if let FocusedWidget::TablesList = self.current_focus {
@@ -347,24 +426,13 @@ impl UIHandler for DatabaseClientUI {
                         if Some(self.selected_table) == self.expanded_table {
                             self.expanded_table = None;
                         } else {
-                            let result = match self.selected_db_type {
-                                0 => PostgresDatabaseUI::new(self.clone()).describe_table(&selected_table).await,
-                                1 => MySqlDatabaseUI::new(self.clone()).describe_table(&selected_table).await,
-                                _ => Err(DbError::Connection("Unsupported database type".to_string())),
+                            let result = match self.database_ui() {
+                                Some(db_ui) => db_ui.describe_table(&selected_table).await,
+                                None => Err(DbError::Connection("Unsupported database type".to_string())),
                             };
 
                             match result {
-                                Ok(columns) => {
-                                    let table_schema = TableSchema {
-                                        table_name: selected_table.clone(),
-                                        columns: columns.into_iter().map(|name| dfox_core::models::schema::ColumnSchema {
-                                            name,
-                                            data_type: String::new(),
-                                            is_nullable: true,
-                                            default: None,
-                                        }).collect(),
-                                        indexes: Vec::new(),
-                                    };
+                                Ok(table_schema) => {
                                     let table_schema_clone = table_schema.clone();
                                     self.table_schemas.insert(
                                         selected_table.clone(),
@@ -392,366 +460,539 @@ impl UIHandler for DatabaseClientUI {
                     }
                 }
             }
-            KeyCode::Esc => {
-                if let Err(e) = terminal.clear() {
-                    log::error!("Error clearing terminal: {}", e);
-                }
-                if let Err(e) = terminal.show_cursor() {
-                    log::error!("Error showing cursor: {}", e);
-                }
-                if let Err(e) = execute!(
-                    terminal.backend_mut(),
-                    LeaveAlternateScreen,
-                    DisableMouseCapture,
-                    Clear(ClearType::All)
-                ) {
-                    log::error!("Error cleaning up terminal: {}", e);
-                }
-                process::exit(0);
-            }
             _ => {}
         }
     }
 
-    async fn handle_sql_editor_input(
-        &mut self,
-        key: KeyCode,
-        modifiers: KeyModifiers,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    ) {
-        match (key, modifiers) {
-            (KeyCode::Tab, _) => self.cycle_focus(),
-            (KeyCode::F(5), _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-                if !self.sql_editor_content.is_empty() {
-                    self.sql_query_error = None;
-                    let sql_content = self.sql_editor_content.clone();
-                    
-                    let result = match self.selected_db_type {
-                        0 => PostgresDatabaseUI::new(self.clone()).execute_sql_query(&sql_content).await,
-                        1 => MySqlDatabaseUI::new(self.clone()).execute_sql_query(&sql_content).await,
-                        _ => Err(DbError::Connection("Unsupported database type".to_string())),
-                    };
-                    
-                    match result {
-                        Ok((result, success_message)) => {
-                            if !result.is_empty() {
-                                if let Some(first_row) = result.first() {
-                                    // Debug: print first few characters to understand the format
-                                    let debug_len = std::cmp::min(100, first_row.len());
-                                    let debug_slice = if first_row.len() >= debug_len {
-                                        &first_row[..debug_len]
-                                    } else {
-                                        first_row
-                                    };
-                                    self.add_debug_info(format!("First row sample: {:?}", debug_slice));
-                                    
-                                    let headers: Vec<String> = first_row
-                                        .split('\t')
-                                        .map(|s| s.trim().to_string())
-                                        .filter(|s| !s.is_empty())
-                                        .collect();
-                                    
-                                    // Debug: print headers
-                                    self.add_debug_info(format!("Headers found: {:?}", headers));
-                                    
-                                    // Debug: check if first result preserves order
-                                    if !result.is_empty() && result.len() > 1 {
-                                        let first_data_row = &result[1];
-                                        let first_values: Vec<&str> = first_data_row.split('\t').collect();
-                                        self.add_debug_info(format!("First data values (first 5): {:?}", 
-                                            first_values.iter().take(5).collect::<Vec<_>>()));
-                                    }
-                                    
-                                    // Debug: show sample of raw data
-                                    if result.len() > 1 {
-                                        let sample_row = &result[1]; // First data row
-                                        let sample_len = std::cmp::min(200, sample_row.len());
-                                        let sample_slice = if sample_row.len() >= sample_len {
-                                            &sample_row[..sample_len]
-                                        } else {
-                                            sample_row
-                                        };
-                                        self.add_debug_info(format!("Sample data row: {:?}", sample_slice));
-                                        
-                                        // Debug: show character codes for first few characters
-                                        let char_codes: Vec<u32> = sample_row.chars().take(20).map(|c| c as u32).collect();
-                                        self.add_debug_info(format!("First 20 char codes: {:?}", char_codes));
-                                    }
-                                    
-                                    // Limit the number of rows to prevent memory issues
-                                    let max_rows = 1000; // Limit to 1k rows for better performance
-                                    let (limited_result, success_msg) = if result.len() > max_rows + 1 {
-                                        let limited = result.into_iter().take(max_rows + 1).collect::<Vec<_>>();
-                                        (limited, format!("Results limited to {} rows for performance", max_rows))
-                                    } else {
-                                        (result, success_message)
-                                    };
-                                    
-                                    self.sql_query_result = limited_result
-                                        .into_iter()
-                                        .skip(1)
-                                        .enumerate()
-                                        .filter_map(|(row_idx, row)| {
-                                            let values: Vec<&str> = row.split('\t').collect();
-                                            if values.len() >= headers.len() {
-                                                let mut map = IndexMap::new();
-                                                // Insert in the same order as headers appear in SQL result
-                                                for (i, header) in headers.iter().enumerate() {
-                                                    if let Some(value) = values.get(i) {
-                                                        // Try multiple cleaning strategies
-                                                        let cleaned_value = if value.chars().any(|c| (c as u32) < 32 && c != '\t' && c != '\n') {
-                                                            // Strategy 1: Remove only control characters (except tab/newline)
-                                                            value
-                                                                .chars()
-                                                                .filter(|c| (*c as u32) >= 32 || *c == '\t' || *c == '\n')
-                                                                .collect::<String>()
-                                                                .trim()
-                                                                .to_string()
-                                                        } else {
-                                                            // Strategy 2: Keep all printable characters including Unicode
-                                                            value
-                                                                .chars()
-                                                                .filter(|c| {
-                                                                    // Keep all printable characters, including Unicode (Cyrillic, emojis, etc.)
-                                                                    !c.is_control() || *c == '\t' || *c == '\n'
-                                                                })
-                                                                .collect::<String>()
-                                                                .trim()
-                                                                .to_string()
-                                                        };
-                                                        
-                                                        // Replace empty values with NULL
-                                                        let final_value = if cleaned_value.is_empty() {
-                                                            "NULL".to_string()
-                                                        } else {
-                                                            cleaned_value
-                                                        };
-                                                        
-                                                        // Insert in order - this preserves the SQL column order
-                                                        map.insert(header.clone(), final_value);
-                                                    }
-                                                }
-                                                Some(map)
-                                            } else {
-                                                log::warn!("Row {} has {} values but {} headers expected", row_idx, values.len(), headers.len());
-                                                None
-                                            }
-                                        })
-                                        .collect();
-                                        
-                                    self.add_debug_info(format!("Processed {} rows successfully", self.sql_query_result.len()));
-                                    
-                                    // Debug: check order in first IndexMap
-                                    if let Some(first_map) = self.sql_query_result.first() {
-                                        let map_keys: Vec<String> = first_map.keys().cloned().collect();
-                                        self.add_debug_info(format!("IndexMap keys order: {:?}", map_keys.iter().take(5).collect::<Vec<_>>()));
-                                    }
-                                    
-                                    self.sql_query_success_message = Some(success_msg);
-                                } else {
-                                    self.sql_query_result = Vec::new();
-                                    self.sql_query_success_message = Some("Empty result set".to_string());
-                                }
-                            } else {
-                                self.sql_query_result = Vec::new();
-                                self.sql_query_success_message = Some(success_message);
-                            }
-                            self.sql_query_error = None;
-                            self.needs_tables_refresh = true;
-                            // Reset result navigation state
-                            self.selected_result_row = 0;
-                            self.sql_result_scroll = 0;
-                            self.sql_result_horizontal_scroll = 0;
-                        }
-                        Err(err) => {
-                            self.sql_query_error = Some(format!("SQL Error: {}", err));
-                            self.sql_query_result.clear();
-                            // Reset result navigation state
-                            self.selected_result_row = 0;
-                            self.sql_result_scroll = 0;
-                            self.sql_result_horizontal_scroll = 0;
+    async fn handle_mouse_event(&mut self, event: MouseEvent) {
+        let position = Position::new(event.column, event.row);
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.tables_area.contains(position) {
+                    self.current_focus = FocusedWidget::TablesList;
+                    if self.show_routines {
+                        return;
+                    }
+                    if let Some(index) = self.table_index_at(position.y) {
+                        self.selected_table = index;
+                    }
+                } else if self.sql_editor_area.contains(position) {
+                    self.current_focus = FocusedWidget::SqlEditor;
+                    self.sql_editor_cursor_y = self.sql_editor_scroll
+                        + position.y.saturating_sub(self.sql_editor_area.y + 1) as usize;
+                    self.sql_editor_cursor_x = position
+                        .x
+                        .saturating_sub(self.sql_editor_area.x + 1 + self.editor_gutter_width())
+                        as usize;
+                    self.sync_cursor_position();
+                } else if self.sql_result_area.contains(position) {
+                    self.current_focus = FocusedWidget::_QueryResult;
+                    // Data rows start below the border, the header and its bottom margin
+                    let first_row_y = self.sql_result_area.y + 3;
+                    if position.y >= first_row_y {
+                        let row = self.sql_result_scroll + (position.y - first_row_y) as usize;
+                        if row < self.sql_query_result.len() {
+                            self.selected_result_row = row;
                         }
                     }
-                    
-                    // Don't clear the SQL content after execution
-                    // self.sql_editor_content.clear(); // Commented out
                 }
-
-                // Safely update tables without crashing
-                let _: Result<(), ()> = match self.selected_db_type {
-                    0 => {
-                        if let Ok(db_ui) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            PostgresDatabaseUI::new(self.clone())
-                        })) {
-                            let _ = db_ui.update_tables().await;
-                        }
-                        Ok(())
-                    },
-                    1 => {
-                        if let Ok(db_ui) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            MySqlDatabaseUI::new(self.clone())
-                        })) {
-                            let _ = db_ui.update_tables().await;
-                        }
-                        Ok(())
-                    },
-                    _ => Ok(()),
-                };
             }
-            (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-                if let FocusedWidget::_QueryResult = self.current_focus {
-                    if !self.sql_query_result.is_empty() {
-                        // Get headers from IndexMap which preserves insertion order
-                        let headers = if let Some(first_result) = self.sql_query_result.first() {
-                            first_result.keys().cloned().collect::<Vec<String>>()
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+                let down = matches!(event.kind, MouseEventKind::ScrollDown);
+                if self.tables_area.contains(position) {
+                    for _ in 0..MOUSE_SCROLL_LINES {
+                        if down {
+                            self.move_selection_down();
                         } else {
-                            Vec::new()
-                        };
-
-                        let mut clipboard_content = String::new();
-                        
-                        // Add headers
-                        clipboard_content.push_str(&headers.join("\t"));
-                        clipboard_content.push('\n');
-
-                        // Add selected row if it exists
-                        if let Some(row) = self.sql_query_result.get(self.selected_result_row) {
-                            let mut row_values = Vec::new();
-                            for header in &headers {
-                                row_values.push(
-                                    row.get(header)
-                                        .map_or("NULL".to_string(), |v| v.to_string())
-                                );
-                            }
-                            clipboard_content.push_str(&row_values.join("\t"));
-                            clipboard_content.push('\n');
+                            self.move_selection_up();
                         }
-
-                        if let Err(e) = Clipboard::new().and_then(|mut ctx| ctx.set_text(clipboard_content)) {
-                            log::error!("Error copying to clipboard: {}", e);
+                    }
+                } else if self.sql_result_area.contains(position) && !self.sql_query_result.is_empty() {
+                    let visible_height = self.result_page_size();
+                    if down {
+                        self.selected_result_row = std::cmp::min(
+                            self.selected_result_row + MOUSE_SCROLL_LINES,
+                            self.sql_query_result.len() - 1,
+                        );
+                        if self.selected_result_row >= self.sql_result_scroll + visible_height {
+                            self.sql_result_scroll = self.selected_result_row + 1 - visible_height;
+                        }
+                    } else {
+                        self.selected_result_row = self.selected_result_row.saturating_sub(MOUSE_SCROLL_LINES);
+                        if self.selected_result_row < self.sql_result_scroll {
+                            self.sql_result_scroll = self.selected_result_row;
                         }
                     }
                 }
             }
-            (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
-                if let FocusedWidget::_QueryResult = self.current_focus {
-                    if !self.sql_query_result.is_empty() {
-                        // Get headers from IndexMap which preserves insertion order
-                        let headers = if let Some(first_result) = self.sql_query_result.first() {
-                            first_result.keys().cloned().collect::<Vec<String>>()
-                        } else {
-                            Vec::new()
-                        };
-
-                        let mut clipboard_content = String::new();
-                        
-                        // Add headers
-                        clipboard_content.push_str(&headers.join("\t"));
-                        clipboard_content.push('\n');
+            _ => {}
+        }
+    }
 
-                        // Add all rows
-                        for row in &self.sql_query_result {
-                            let mut row_values = Vec::new();
-                            for header in &headers {
-                                row_values.push(
-                                    row.get(header)
-                                        .map_or("NULL".to_string(), |v| v.to_string())
-                                );
-                            }
-                            clipboard_content.push_str(&row_values.join("\t"));
-                            clipboard_content.push('\n');
-                        }
+    async fn handle_cell_detail_input(&mut self, key: KeyCode) {
+        let max_scroll = self.cell_detail_line_count().saturating_sub(1) as u16;
+        let page_size = self.cell_detail_area.height.saturating_sub(2).max(1);
 
-                        if let Err(e) = Clipboard::new().and_then(|mut ctx| ctx.set_text(clipboard_content)) {
-                            log::error!("Error copying to clipboard: {}", e);
-                        }
-                    }
-                }
+        match key {
+            KeyCode::Up => self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(1),
+            KeyCode::Down => {
+                self.cell_detail_scroll = std::cmp::min(self.cell_detail_scroll + 1, max_scroll)
             }
-            (KeyCode::Enter, _) => {
-                if let FocusedWidget::SqlEditor = self.current_focus {
-                    self.sql_editor_content.push('\n');
-                    self.sql_editor_cursor_y += 1;
-                    self.sql_editor_cursor_x = 0;
-                    self.sync_cursor_position();
-                }
+            KeyCode::PageUp => {
+                self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(page_size)
             }
-            (KeyCode::Char(c), _) => {
-                if let FocusedWidget::SqlEditor = self.current_focus {
-                    self.sql_editor_content.push(c);
-                    self.sql_editor_cursor_x += 1;
-                    self.sync_cursor_position();
-                }
+            KeyCode::PageDown => {
+                self.cell_detail_scroll =
+                    std::cmp::min(self.cell_detail_scroll.saturating_add(page_size), max_scroll)
             }
-            (KeyCode::Backspace, _) => {
-                if let FocusedWidget::SqlEditor = self.current_focus {
-                    if !self.sql_editor_content.is_empty() {
-                        self.sql_editor_content.pop();
-                        if self.sql_editor_cursor_x > 0 {
-                            self.sql_editor_cursor_x -= 1;
-                        } else if self.sql_editor_cursor_y > 0 {
-                            self.sql_editor_cursor_y -= 1;
-                            // Calculate cursor position for previous line
-                            let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
-                            if let Some(prev_line) = lines.get(self.sql_editor_cursor_y) {
-                                self.sql_editor_cursor_x = prev_line.len();
-                            } else {
-                                self.sql_editor_cursor_x = 0;
-                            }
-                        } else {
-                            // At the beginning of text, just stay at position 0,0
-                            self.sql_editor_cursor_x = 0;
-                            self.sql_editor_cursor_y = 0;
-                        }
-                        self.sync_cursor_position();
-                    }
-                }
+            KeyCode::Home => self.cell_detail_scroll = 0,
+            KeyCode::End => self.cell_detail_scroll = max_scroll,
+            KeyCode::Char('w') => {
+                self.cell_detail_wrap = !self.cell_detail_wrap;
+                let max_scroll = self.cell_detail_line_count().saturating_sub(1) as u16;
+                self.cell_detail_scroll = std::cmp::min(self.cell_detail_scroll, max_scroll);
             }
-            (KeyCode::Left, _) => {
-                if matches!(self.current_focus, FocusedWidget::SqlEditor) && self.sql_editor_cursor_x > 0 {
-                    self.sql_editor_cursor_x -= 1;
-                }
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.cell_detail = None;
+                self.cell_detail_scroll = 0;
+                self.current_screen = ScreenState::TableView;
             }
-            (KeyCode::Right, _) => {
-                if let FocusedWidget::SqlEditor = self.current_focus {
-                    let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
-                    if let Some(current_line) = lines.get(self.sql_editor_cursor_y) {
-                        if self.sql_editor_cursor_x < current_line.len() {
-                            self.sql_editor_cursor_x += 1;
-                        }
-                    }
+            _ => {}
+        }
+    }
+
+    async fn handle_command_palette_input(&mut self, key: KeyCode) {
+        let commands = palette::filter(&self.command_palette_input);
+        match key {
+            KeyCode::Esc => {
+                self.current_screen = self
+                    .previous_screen
+                    .take()
+                    .unwrap_or(ScreenState::TableView);
+            }
+            KeyCode::Up => self.selected_command = self.selected_command.saturating_sub(1),
+            KeyCode::Down if self.selected_command + 1 < commands.len() => {
+                self.selected_command += 1;
+            }
+            KeyCode::Enter => {
+                self.current_screen = self
+                    .previous_screen
+                    .take()
+                    .unwrap_or(ScreenState::TableView);
+                if let Some(command) = commands.get(self.selected_command) {
+                    self.run_command(*command);
                 }
             }
-            (KeyCode::Up, _) => {
-                if matches!(self.current_focus, FocusedWidget::SqlEditor) && self.sql_editor_cursor_y > 0 {
-                    self.sql_editor_cursor_y -= 1;
+            KeyCode::Backspace => {
+                self.command_palette_input.pop();
+                self.selected_command = 0;
+            }
+            KeyCode::Char(c) => {
+                self.command_palette_input.push(c);
+                self.selected_command = 0;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_param_prompt_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.query_params.clear();
+                self.current_screen = ScreenState::TableView;
+            }
+            KeyCode::Enter => {
+                self.query_params.push(parse_param_value(&self.param_input));
+                self.param_input.clear();
+                if self.query_params.len() == self.param_count {
+                    self.current_screen = ScreenState::TableView;
+                    let query = std::mem::take(&mut self.param_query);
+                    self.run_statement(query);
+                }
+            }
+            KeyCode::Backspace => {
+                self.param_input.pop();
+            }
+            KeyCode::Char(c) => self.param_input.push(c),
+            _ => {}
+        }
+    }
+
+    async fn handle_import_prompt_input(&mut self, key: KeyCode) {
+        let complete = !self.import_path_input.trim().is_empty() && !self.import_table_input.trim().is_empty();
+        let input = if self.import_editing_table {
+            &mut self.import_table_input
+        } else {
+            &mut self.import_path_input
+        };
+        match key {
+            KeyCode::Esc => self.current_screen = ScreenState::TableView,
+            KeyCode::Tab | KeyCode::Up | KeyCode::Down => self.import_editing_table = !self.import_editing_table,
+            KeyCode::Enter if complete => {
+                self.current_screen = ScreenState::TableView;
+                self.start_csv_import();
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    async fn handle_snippet_name_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => self.current_screen = ScreenState::TableView,
+            KeyCode::Enter if !self.snippet_input.trim().is_empty() => {
+                self.current_screen = ScreenState::TableView;
+                self.save_snippet();
+            }
+            KeyCode::Backspace => {
+                self.snippet_input.pop();
+            }
+            KeyCode::Char(c) => self.snippet_input.push(c),
+            _ => {}
+        }
+    }
+
+    async fn handle_snippet_picker_input(&mut self, key: KeyCode) {
+        let names = self.snippet_names();
+        match key {
+            KeyCode::Esc => self.current_screen = ScreenState::TableView,
+            KeyCode::Up => self.selected_snippet = self.selected_snippet.saturating_sub(1),
+            KeyCode::Down if self.selected_snippet + 1 < names.len() => self.selected_snippet += 1,
+            KeyCode::Enter => {
+                if let Some(name) = names.get(self.selected_snippet) {
+                    self.current_screen = ScreenState::TableView;
+                    self.load_snippet(name);
+                }
+            }
+            KeyCode::Delete => {
+                if let Some(name) = names.get(self.selected_snippet) {
+                    self.snippets.remove(name);
+                    self.selected_snippet = self.selected_snippet.min(names.len().saturating_sub(2));
+                    self.write_snippets();
+                }
+            }
+            KeyCode::Backspace => {
+                self.snippet_input.pop();
+                self.selected_snippet = 0;
+            }
+            KeyCode::Char(c) => {
+                self.snippet_input.push(c);
+                self.selected_snippet = 0;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_cell_edit_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => self.current_screen = ScreenState::TableView,
+            KeyCode::Enter => {
+                self.current_screen = ScreenState::TableView;
+                match self.save_cell_edit().await {
+                    Ok(message) => self.sql_query_success_message = Some(message),
+                    Err(message) => self.sql_query_error = Some(message),
+                }
+            }
+            KeyCode::Backspace => {
+                self.cell_edit_input.pop();
+            }
+            KeyCode::Char(c) => self.cell_edit_input.push(c),
+            _ => {}
+        }
+    }
+
+    async fn handle_delete_confirmation_input(&mut self, key: KeyCode) {
+        self.current_screen = ScreenState::TableView;
+        if let KeyCode::Char('y') | KeyCode::Char('Y') = key {
+            match self.delete_selected_row().await {
+                Ok(message) => self.sql_query_success_message = Some(message),
+                Err(message) => self.sql_query_error = Some(message),
+            }
+        }
+    }
+
+    async fn handle_explain_confirmation_input(&mut self, key: KeyCode) {
+        self.current_screen = ScreenState::TableView;
+        if let KeyCode::Char('y') | KeyCode::Char('Y') = key {
+            self.spawn_explain_analyze(self.sql_editor_content.clone());
+        }
+    }
+
+    async fn handle_sessions_input(&mut self, key: KeyCode) {
+        if let Some(pid) = self.pending_session_kill.take() {
+            if let KeyCode::Char('y') | KeyCode::Char('Y') = key {
+                self.kill_session(pid).await;
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Up => self.selected_session = self.selected_session.saturating_sub(1),
+            KeyCode::Down if self.selected_session + 1 < self.sessions.len() => self.selected_session += 1,
+            KeyCode::Char('r') | KeyCode::F(2) => self.needs_sessions_refresh = true,
+            KeyCode::Char('k') | KeyCode::Delete => {
+                self.pending_session_kill = self.sessions.get(self.selected_session).map(|s| s.pid);
+            }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(10) => {
+                self.sessions.clear();
+                self.sessions_message = None;
+                self.current_screen = ScreenState::TableView;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_table_stats_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char('r') | KeyCode::F(2) => self.needs_table_stats_refresh = true,
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('a') => {
+                self.current_screen = ScreenState::TableView;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_help_input(&mut self, key: KeyCode) {
+        let last_line = help::line_count().saturating_sub(1);
+        match key {
+            KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down => self.help_scroll = (self.help_scroll + 1).min(last_line),
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(HELP_PAGE_LINES),
+            KeyCode::PageDown => self.help_scroll = (self.help_scroll + HELP_PAGE_LINES).min(last_line),
+            KeyCode::Home => self.help_scroll = 0,
+            KeyCode::End => self.help_scroll = last_line,
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') => {
+                self.current_screen = self
+                    .previous_screen
+                    .take()
+                    .unwrap_or(ScreenState::TableView);
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_error_input(&mut self, key: KeyCode) {
+        if let KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') = key {
+            self.current_screen = self
+                .previous_screen
+                .take()
+                .unwrap_or(ScreenState::DbTypeSelection);
+        }
+    }
+
+    async fn handle_sql_editor_input(
+        &mut self,
+        key: KeyCode,
+        modifiers: KeyModifiers,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) {
+        if !self.autocomplete_suggestions.is_empty() {
+            let handled = match key {
+                KeyCode::Up => {
+                    self.selected_suggestion = self.selected_suggestion.saturating_sub(1);
+                    true
+                }
+                KeyCode::Down => {
+                    if self.selected_suggestion + 1 < self.autocomplete_suggestions.len() {
+                        self.selected_suggestion += 1;
+                    }
+                    true
+                }
+                KeyCode::Enter | KeyCode::Tab => {
+                    let suggestion = self.autocomplete_suggestions[self.selected_suggestion].clone();
+                    self.push_undo_state(false);
+                    self.apply_completion(&suggestion);
+                    true
+                }
+                KeyCode::Esc => {
+                    self.autocomplete_suggestions.clear();
+                    true
+                }
+                _ => false,
+            };
+            if handled {
+                if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
+                    log::error!("Error rendering UI: {}", err);
+                }
+                return;
+            }
+            self.autocomplete_suggestions.clear();
+        }
+
+        let edits_content = matches!(
+            key,
+            KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Delete | KeyCode::Enter
+        ) && !modifiers.contains(KeyModifiers::CONTROL);
+        if edits_content {
+            self.sql_error_position = None;
+            self.push_undo_state(matches!(key, KeyCode::Char(c) if !c.is_whitespace()));
+        } else {
+            self.coalescing_insert = false;
+        }
+
+        match (key, modifiers) {
+            (KeyCode::Tab, _) => self.cycle_focus(),
+            (KeyCode::Char(' '), KeyModifiers::CONTROL) => self.open_autocomplete().await,
+            (KeyCode::Char('o'), KeyModifiers::CONTROL) => {
+                if let Err(err) = self.edit_in_external_editor(terminal) {
+                    self.sql_query_error = Some(format!("External editor failed: {}", err));
+                }
+            }
+            (KeyCode::Char('f'), KeyModifiers::CONTROL) => self.format_editor_sql(),
+            (KeyCode::Char('n'), KeyModifiers::CONTROL) => self.toggle_notes(),
+            (KeyCode::Char('s'), KeyModifiers::CONTROL) => self.open_snippet_prompt(),
+            (KeyCode::Char('p'), KeyModifiers::CONTROL) => self.open_snippet_picker(),
+            (KeyCode::Char('z'), KeyModifiers::CONTROL) => self.undo_edit(),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => self.redo_edit(),
+            (KeyCode::F(5), KeyModifiers::SHIFT) => self.start_current_statement(),
+            (KeyCode::F(5), _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => self.start_query(),
+            (KeyCode::F(9), _) => self.start_explain_analyze(),
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                if let FocusedWidget::_QueryResult = self.current_focus {
+                    if !self.sql_query_result.is_empty() {
+                        // Get headers from IndexMap which preserves insertion order
+                        let headers = if let Some(first_result) = self.sql_query_result.first() {
+                            first_result.keys().cloned().collect::<Vec<String>>()
+                        } else {
+                            Vec::new()
+                        };
+
+                        let mut clipboard_content = String::new();
+                        
+                        // Add headers
+                        clipboard_content.push_str(&headers.join("\t"));
+                        clipboard_content.push('\n');
+
+                        // Add selected row if it exists
+                        if let Some(row) = self.sql_query_result.get(self.selected_result_row) {
+                            let mut row_values = Vec::new();
+                            for header in &headers {
+                                row_values.push(
+                                    row.get(header)
+                                        .map_or("NULL".to_string(), |v| v.to_string())
+                                );
+                            }
+                            clipboard_content.push_str(&row_values.join("\t"));
+                            clipboard_content.push('\n');
+                        }
+
+                        self.copy_to_clipboard(clipboard_content, "Copied the selected row".to_string());
+                    }
+                }
+            }
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
+                if let FocusedWidget::_QueryResult = self.current_focus {
+                    if !self.sql_query_result.is_empty() {
+                        // Get headers from IndexMap which preserves insertion order
+                        let headers = if let Some(first_result) = self.sql_query_result.first() {
+                            first_result.keys().cloned().collect::<Vec<String>>()
+                        } else {
+                            Vec::new()
+                        };
+
+                        let mut clipboard_content = String::new();
+                        
+                        // Add headers
+                        clipboard_content.push_str(&headers.join("\t"));
+                        clipboard_content.push('\n');
+
+                        // Add all rows
+                        for row in &self.sql_query_result {
+                            let mut row_values = Vec::new();
+                            for header in &headers {
+                                row_values.push(
+                                    row.get(header)
+                                        .map_or("NULL".to_string(), |v| v.to_string())
+                                );
+                            }
+                            clipboard_content.push_str(&row_values.join("\t"));
+                            clipboard_content.push('\n');
+                        }
+
+                        let copied = format!("Copied {} rows", self.sql_query_result.len());
+                        self.copy_to_clipboard(clipboard_content, copied);
+                    }
+                }
+            }
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => self.request_tables_refresh(),
+            (KeyCode::Enter, _) => {
+                if let FocusedWidget::SqlEditor = self.current_focus {
+                    self.uppercase_trailing_keyword();
+                    self.sql_editor_content.push('\n');
+                    self.sql_editor_cursor_y += 1;
+                    self.sql_editor_cursor_x = 0;
+                    self.sync_cursor_position();
+                }
+            }
+            (KeyCode::Char(c), _) => {
+                if let FocusedWidget::SqlEditor = self.current_focus {
+                    if !sql_format::is_word_char(c) {
+                        self.uppercase_trailing_keyword();
+                    }
+                    self.sql_editor_content.push(c);
+                    self.sql_editor_cursor_x += 1;
+                    self.sync_cursor_position();
+                }
+            }
+            (KeyCode::Backspace, _) => {
+                if let FocusedWidget::SqlEditor = self.current_focus {
+                    self.delete_char_before_cursor();
+                }
+            }
+            (KeyCode::Left, KeyModifiers::CONTROL) if matches!(self.current_focus, FocusedWidget::SqlEditor) => {
+                self.move_cursor_word_left();
+            }
+            (KeyCode::Right, KeyModifiers::CONTROL) if matches!(self.current_focus, FocusedWidget::SqlEditor) => {
+                self.move_cursor_word_right();
+            }
+            (KeyCode::Left, _) if matches!(self.current_focus, FocusedWidget::SqlEditor) && self.sql_editor_cursor_x > 0 => {
+                self.sql_editor_cursor_x -= 1;
+            }
+            (KeyCode::Right, _) => {
+                if let FocusedWidget::SqlEditor = self.current_focus {
                     let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
-                    if let Some(line) = lines.get(self.sql_editor_cursor_y) {
-                        self.sql_editor_cursor_x = std::cmp::min(self.sql_editor_cursor_x, line.len());
+                    if let Some(current_line) = lines.get(self.sql_editor_cursor_y) {
+                        if self.sql_editor_cursor_x < current_line.chars().count() {
+                            self.sql_editor_cursor_x += 1;
+                        }
                     }
                 }
             }
+            (KeyCode::Up, _) if matches!(self.current_focus, FocusedWidget::SqlEditor) && self.sql_editor_cursor_y > 0 => {
+                self.sql_editor_cursor_y -= 1;
+                let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
+                if let Some(line) = lines.get(self.sql_editor_cursor_y) {
+                    self.sql_editor_cursor_x = std::cmp::min(self.sql_editor_cursor_x, line.chars().count());
+                }
+            }
             (KeyCode::Down, _) => {
                 if let FocusedWidget::SqlEditor = self.current_focus {
                     let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
                     if self.sql_editor_cursor_y < lines.len().saturating_sub(1) {
                         self.sql_editor_cursor_y += 1;
                         if let Some(line) = lines.get(self.sql_editor_cursor_y) {
-                            self.sql_editor_cursor_x = std::cmp::min(self.sql_editor_cursor_x, line.len());
+                            self.sql_editor_cursor_x = std::cmp::min(self.sql_editor_cursor_x, line.chars().count());
                         }
                     }
                 }
             }
             (KeyCode::F(1), _) => {
-                self.current_screen = ScreenState::DatabaseSelection;
-                self.sql_editor_content.clear();
-                self.sql_query_result.clear();
+                self.return_to_database_selection();
                 if let Err(err) = UIRenderer::render_database_selection_screen(self, terminal).await
                 {
                     log::error!("Error rendering database selection screen: {}", err);
                 }
                 return;
             }
+            (KeyCode::F(2), _) => self.request_tables_refresh(),
+            (KeyCode::F(3), _) => self.toggle_binary_format(),
+            (KeyCode::F(4), _) => self.toggle_auto_limit(),
+            (KeyCode::F(6), _) => self.toggle_digit_grouping(),
+            (KeyCode::F(8), _) => self.toggle_auto_refresh(),
+            (KeyCode::F(10), _) => self.open_sessions(),
             (KeyCode::F(12), _) => {
                 // Show debug information in SQL query result area
                 if !self.debug_info.is_empty() {
@@ -764,6 +1005,7 @@ impl UIHandler for DatabaseClientUI {
                     }
                     self.sql_query_success_message = Some(format!("Debug info ({} messages) - Press F12 again to clear", self.debug_info.len()));
                     self.selected_result_row = 0;
+                    self.selected_result_column = 0;
                     self.sql_result_scroll = 0;
                 } else {
                     // Clear debug display and restore normal result
@@ -773,6 +1015,7 @@ impl UIHandler for DatabaseClientUI {
             }
             _ => {}
         }
+        self.scroll_editor_to_cursor();
         if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
             log::error!("Error rendering UI: {}", err);
         }
@@ -781,6 +1024,7 @@ impl UIHandler for DatabaseClientUI {
 
 impl DatabaseClientUI {
     pub fn cycle_focus(&mut self) {
+        self.editing_filter = false;
         self.current_focus = match self.current_focus {
             FocusedWidget::TablesList => FocusedWidget::SqlEditor,
             FocusedWidget::SqlEditor => FocusedWidget::_QueryResult,
@@ -788,6 +1032,73 @@ impl DatabaseClientUI {
         };
     }
 
+    /// Maps a screen row inside the tables pane to a table index, accounting for
+    /// the column lines of an expanded table.
+    fn table_index_at(&self, y: u16) -> Option<usize> {
+        let mut line = y.checked_sub(self.tables_area.y + 1)? as usize;
+        for index in self.tables_scroll..self.tables.len() {
+            let expanded_lines = if self.expanded_table == Some(index) {
+                self.table_schemas
+                    .get(&self.tables[index])
+                    .map_or(0, |schema| schema.columns.len())
+            } else {
+                0
+            };
+            if line <= expanded_lines {
+                return Some(index);
+            }
+            line -= expanded_lines + 1;
+        }
+        None
+    }
+
+    /// Number of result rows visible in the grid during the last render.
+    pub fn result_page_size(&self) -> usize {
+        // Borders plus the header row and its bottom margin
+        (self.sql_result_area.height as usize).saturating_sub(4).max(1)
+    }
+
+    /// Number of lines visible in the tables list during the last render.
+    pub fn tables_page_size(&self) -> usize {
+        (self.tables_area.height as usize).saturating_sub(2).max(1)
+    }
+
+    /// Number of lines visible in the database list during the last render.
+    pub fn databases_page_size(&self) -> usize {
+        (self.databases_area.height as usize).saturating_sub(2).max(1)
+    }
+
+    /// Opens the selected result cell in the full-screen detail view.
+    pub fn open_cell_detail(&mut self) {
+        let Some(row) = self.sql_query_result.get(self.selected_result_row) else {
+            return;
+        };
+        let Some((column, value)) = row.get_index(self.selected_result_column) else {
+            return;
+        };
+
+        let title = format!("Row {} - {}", self.selected_result_row + 1, column);
+        self.cell_detail = Some((title, format_cell_value(value)));
+        self.cell_detail_scroll = 0;
+        self.current_screen = ScreenState::CellDetail;
+    }
+
+    /// Number of lines the detail view needs for the open cell, counting wrapped lines
+    /// against the width of the last render when wrapping is on.
+    pub fn cell_detail_line_count(&self) -> usize {
+        let Some((_, value)) = &self.cell_detail else {
+            return 0;
+        };
+        let width = self.cell_detail_area.width.saturating_sub(2) as usize;
+        if !self.cell_detail_wrap || width == 0 {
+            return value.lines().count();
+        }
+        value
+            .lines()
+            .map(|line| line.chars().count().div_ceil(width).max(1))
+            .sum()
+    }
+
     pub fn move_selection_up(&mut self) {
         if self.selected_table > 0 {
             self.selected_table -= 1;
@@ -800,7 +1111,7 @@ impl DatabaseClientUI {
     pub fn move_selection_down(&mut self) {
         if self.selected_table < self.tables.len().saturating_sub(1) {
             self.selected_table += 1;
-            let visible_height = 50; 
+            let visible_height = self.tables_page_size();
             if self.selected_table >= self.tables_scroll + visible_height {
                 self.tables_scroll = self.selected_table - visible_height + 1;
             }
@@ -817,14 +1128,13 @@ impl DatabaseClientUI {
         
         // Ensure cursor X is within bounds for current line
         if let Some(current_line) = lines.get(self.sql_editor_cursor_y) {
-            if self.sql_editor_cursor_x > current_line.len() {
-                self.sql_editor_cursor_x = current_line.len();
-            }
+            self.sql_editor_cursor_x = self.sql_editor_cursor_x.min(current_line.chars().count());
         } else {
             // Safety fallback
             self.sql_editor_cursor_x = 0;
             self.sql_editor_cursor_y = 0;
         }
+        self.scroll_editor_to_cursor();
         
         // Additional safety checks for result navigation
         if self.selected_result_row >= self.sql_query_result.len() && !self.sql_query_result.is_empty() {
@@ -841,4 +1151,3341 @@ impl DatabaseClientUI {
             self.sql_result_scroll = max_scroll;
         }
     }
-} 
\ No newline at end of file
+
+    /// Moves the editor cursor to a 1-based character position reported by the server
+    /// and marks it for highlighting. The query is sent trimmed, so leading whitespace in
+    /// the editor is skipped first, as is the text before the statement that ran.
+    pub fn jump_to_error_position(&mut self, position: usize) {
+        let leading = self
+            .sql_editor_content
+            .chars()
+            .skip(self.query_start)
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let target = self.query_start + leading + position.saturating_sub(1);
+
+        let (mut line, mut column) = (0, 0);
+        for c in self.sql_editor_content.chars().take(target) {
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+
+        self.sql_editor_cursor_y = line;
+        self.sql_editor_cursor_x = column;
+        self.sync_cursor_position();
+        self.sql_error_position = Some((self.sql_editor_cursor_y, self.sql_editor_cursor_x));
+        self.current_focus = FocusedWidget::SqlEditor;
+    }
+
+    /// Number of editor lines visible during the last render.
+    pub fn editor_page_size(&self) -> usize {
+        (self.sql_editor_area.height as usize).saturating_sub(2).max(1)
+    }
+
+    /// Width of the line number gutter: the widest number plus a separating space.
+    pub fn editor_gutter_width(&self) -> u16 {
+        let lines = self.sql_editor_content.split('\n').count();
+        lines.to_string().len() as u16 + 1
+    }
+
+    /// Scrolls the editor just enough to keep the cursor line in view.
+    pub fn scroll_editor_to_cursor(&mut self) {
+        let visible_lines = self.editor_page_size();
+        if self.sql_editor_cursor_y < self.sql_editor_scroll {
+            self.sql_editor_scroll = self.sql_editor_cursor_y;
+        } else if self.sql_editor_cursor_y >= self.sql_editor_scroll + visible_lines {
+            self.sql_editor_scroll = self.sql_editor_cursor_y + 1 - visible_lines;
+        }
+    }
+
+    /// Width of a result column: the user's override if one was set with Ctrl+Left/Right,
+    /// otherwise sized to the header and a sample of the values.
+    pub fn result_column_width(&self, header: &str) -> u16 {
+        if let Some(width) = self.column_width_overrides.get(header) {
+            return *width;
+        }
+
+        let header_width = header.len() as u16;
+        let max_content_width = self
+            .sql_query_result
+            .iter()
+            .take(std::cmp::min(50, self.sql_query_result.len())) // Sample fewer rows for performance
+            .map(|row| {
+                row.get(header)
+                    .map_or(4, |v| std::cmp::min(v.len(), self.cell_truncate_length)) as u16
+            })
+            .max()
+            .unwrap_or(header_width);
+
+        // Use reasonable width limits to prevent extreme stretching
+        let optimal_width = std::cmp::max(header_width + 2, max_content_width + 2);
+        let final_width = std::cmp::min(optimal_width, self.max_column_width);
+        std::cmp::max(final_width, 8) // Min 8 chars per column
+    }
+
+    /// Widens (positive `delta`) or narrows the selected result column.
+    pub fn resize_selected_column(&mut self, delta: i32) {
+        let Some(header) = self
+            .sql_query_result
+            .first()
+            .and_then(|row| row.keys().nth(self.selected_result_column))
+            .cloned()
+        else {
+            return;
+        };
+        let width = (self.result_column_width(&header) as i32 + delta)
+            .clamp(MIN_COLUMN_WIDTH as i32, MAX_COLUMN_WIDTH as i32) as u16;
+        self.column_width_overrides.insert(header, width);
+    }
+
+    /// Raises (positive `delta`) or lowers the width automatically sized result columns
+    /// may grow to.
+    pub fn adjust_max_column_width(&mut self, delta: i32) {
+        self.max_column_width = (self.max_column_width as i32 + delta)
+            .clamp(MIN_COLUMN_WIDTH as i32, MAX_COLUMN_WIDTH as i32) as u16;
+        self.sql_query_success_message =
+            Some(format!("Result columns up to {} characters wide", self.max_column_width));
+    }
+
+    /// Raises or lowers how much of each value counts when sizing its column.
+    pub fn adjust_cell_truncate_length(&mut self, grow: bool) {
+        self.cell_truncate_length = if grow {
+            (self.cell_truncate_length + CELL_TRUNCATE_STEP).min(MAX_COLUMN_WIDTH as usize)
+        } else {
+            self.cell_truncate_length.saturating_sub(CELL_TRUNCATE_STEP).max(CELL_TRUNCATE_STEP)
+        };
+        self.sql_query_success_message = Some(format!(
+            "Values truncated after {} characters (columns up to {} wide)",
+            self.cell_truncate_length, self.max_column_width
+        ));
+    }
+
+    /// Moves the number of rows kept from a query to the next larger or smaller step. The
+    /// current result keeps its rows; the new cap applies from the next query.
+    pub fn adjust_max_display_rows(&mut self, grow: bool) {
+        let current = self.max_display_rows;
+        self.max_display_rows = if grow {
+            DISPLAY_ROW_STEPS.into_iter().find(|&rows| rows > current).unwrap_or(current)
+        } else {
+            DISPLAY_ROW_STEPS.into_iter().rev().find(|&rows| rows < current).unwrap_or(current)
+        };
+        self.sql_query_success_message =
+            Some(format!("Keeping up to {} rows from the next query", self.max_display_rows));
+    }
+
+    /// Suspends the TUI, opens the editor content in `$EDITOR` and loads the saved file
+    /// back into the editor once the external process exits.
+    pub fn edit_in_external_editor(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let path = std::env::temp_dir().join(format!("dfox-{}.sql", std::process::id()));
+        std::fs::write(&path, &self.sql_editor_content)?;
+
+        let (program, args) = external_editor_command(std::env::var("EDITOR").ok());
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+
+        let status = std::process::Command::new(&program).args(&args).arg(&path).status();
+
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+        terminal.clear()?;
+
+        let content = match status {
+            Ok(status) if status.success() => std::fs::read_to_string(&path),
+            Ok(status) => Err(io::Error::other(format!("{} exited with {}", program, status))),
+            Err(err) => Err(io::Error::new(err.kind(), format!("{}: {}", program, err))),
+        };
+        let _ = std::fs::remove_file(&path);
+
+        // Editors usually leave a trailing newline that isn't part of the query
+        let content = content?.trim_end_matches(['\n', '\r']).to_string();
+        if content != self.sql_editor_content {
+            self.push_undo_state(false);
+            self.sql_editor_content = content;
+            self.sql_error_position = None;
+        }
+        self.move_cursor_to_end();
+        Ok(())
+    }
+
+    /// Reformats the editor's SQL, see [`sql_format::format_sql`]. The cursor moves to the
+    /// end and undo brings the original back.
+    pub fn format_editor_sql(&mut self) {
+        let formatted = sql_format::format_sql(&self.sql_editor_content, self.selected_db_type == 1);
+        if formatted == self.sql_editor_content {
+            return;
+        }
+        self.push_undo_state(false);
+        self.sql_editor_content = formatted;
+        self.sql_error_position = None;
+        self.move_cursor_to_end();
+    }
+
+    fn move_cursor_to_end(&mut self) {
+        let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
+        self.sql_editor_cursor_y = lines.len() - 1;
+        self.sql_editor_cursor_x = lines[self.sql_editor_cursor_y].chars().count();
+        self.sync_cursor_position();
+    }
+
+    /// Names the notes kept for the current connection; the scratch database shares one.
+    fn notes_profile(&self) -> String {
+        match self.server_key() {
+            Some(server) => format!("{}@{}", self.connection_input.username, server),
+            None => "scratch".to_string(),
+        }
+    }
+
+    /// Switches the editor between the query and the notes of the connection profile.
+    pub fn toggle_notes(&mut self) {
+        if self.stashed_query.is_some() {
+            self.close_notes();
+            return;
+        }
+        let notes = self.notes.get(&self.notes_profile()).cloned().unwrap_or_default();
+        self.stashed_query = Some(StashedQuery {
+            content: std::mem::replace(&mut self.sql_editor_content, notes),
+            cursor_x: self.sql_editor_cursor_x,
+            cursor_y: self.sql_editor_cursor_y,
+            undo: std::mem::take(&mut self.sql_editor_undo),
+            redo: std::mem::take(&mut self.sql_editor_redo),
+        });
+        self.reset_editor_marks();
+        self.move_cursor_to_end();
+    }
+
+    /// Puts the query back in the editor and saves the notes it showed. Does nothing
+    /// while the editor shows the query.
+    pub fn close_notes(&mut self) {
+        let Some(query) = self.stashed_query.take() else {
+            return;
+        };
+        let notes = std::mem::replace(&mut self.sql_editor_content, query.content);
+        let profile = self.notes_profile();
+        if notes.trim().is_empty() {
+            self.notes.remove(&profile);
+        } else {
+            self.notes.insert(profile, notes);
+        }
+        self.sql_editor_cursor_x = query.cursor_x;
+        self.sql_editor_cursor_y = query.cursor_y;
+        self.sql_editor_undo = query.undo;
+        self.sql_editor_redo = query.redo;
+        self.reset_editor_marks();
+        self.sync_cursor_position();
+
+        if let Some(path) = &self.notes_file {
+            if let Err(e) = state::save_texts(path, &self.notes) {
+                self.sql_query_error = Some(format!("Could not save the notes to {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// Asks for a name to save the editor content under.
+    pub fn open_snippet_prompt(&mut self) {
+        if self.sql_editor_content.trim().is_empty() {
+            self.sql_query_success_message = Some("Nothing to save: the editor is empty".to_string());
+            return;
+        }
+        self.snippet_input.clear();
+        self.current_screen = ScreenState::SnippetName;
+    }
+
+    /// Lists the saved queries to pick one for the editor.
+    pub fn open_snippet_picker(&mut self) {
+        if self.snippets.is_empty() {
+            self.sql_query_success_message = Some("No saved queries yet, save one with Ctrl+S".to_string());
+            return;
+        }
+        self.snippet_input.clear();
+        self.selected_snippet = 0;
+        self.current_screen = ScreenState::SnippetPicker;
+    }
+
+    /// Names of the saved queries matching the picker's filter, best match first.
+    pub fn snippet_names(&self) -> Vec<String> {
+        let names: Vec<String> = self.snippets.keys().cloned().collect();
+        fuzzy::rank(&names, &self.snippet_input)
+    }
+
+    /// Saves the editor content under the name typed in the prompt, replacing a saved
+    /// query of the same name.
+    fn save_snippet(&mut self) {
+        let name = self.snippet_input.trim().to_string();
+        let replaced = self
+            .snippets
+            .insert(name.clone(), self.sql_editor_content.trim_end().to_string())
+            .is_some();
+        self.sql_query_success_message = Some(if replaced {
+            format!("Replaced the saved query \"{}\"", name)
+        } else {
+            format!("Saved the query as \"{}\"", name)
+        });
+        self.write_snippets();
+    }
+
+    /// Replaces the editor content with the saved query `name`; undo brings it back.
+    fn load_snippet(&mut self, name: &str) {
+        let Some(sql) = self.snippets.get(name).cloned() else {
+            return;
+        };
+        self.push_undo_state(false);
+        self.sql_editor_content = sql;
+        self.reset_editor_marks();
+        self.move_cursor_to_end();
+        self.current_focus = FocusedWidget::SqlEditor;
+    }
+
+    fn write_snippets(&mut self) {
+        if let Some(path) = &self.snippets_file {
+            if let Err(e) = state::save_texts(path, &self.snippets) {
+                self.sql_query_error = Some(format!("Could not save the queries to {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    fn reset_editor_marks(&mut self) {
+        self.sql_editor_scroll = 0;
+        self.sql_error_position = None;
+        self.coalescing_insert = false;
+        self.autocomplete_suggestions.clear();
+    }
+
+    /// Replaces the query with the notes' statement under the cursor and goes back to
+    /// the query. The replaced query stays on the undo stack.
+    fn load_statement_from_notes(&mut self) {
+        let Some(range) = statement_at(&self.sql_editor_content, self.sql_editor_cursor_offset(), self.selected_db_type == 1) else {
+            self.sql_query_success_message = Some("No statement under the cursor".to_string());
+            return;
+        };
+        let statement = self.sql_editor_content[range].to_string();
+        self.close_notes();
+        self.push_undo_state(false);
+        self.sql_editor_content = statement;
+        self.move_cursor_to_end();
+    }
+
+    /// Upper-cases the keyword just typed when that option is on. Called as a word ends, so
+    /// the undo snapshot for the key still holds the word as typed.
+    fn uppercase_trailing_keyword(&mut self) {
+        if !self.uppercase_keywords {
+            return;
+        }
+        if let Some(start) = sql_format::trailing_keyword(&self.sql_editor_content, self.selected_db_type == 1) {
+            self.sql_editor_content[start..].make_ascii_uppercase();
+        }
+    }
+
+    /// Saves the editor state before an edit. Consecutive word characters share one
+    /// snapshot so undo removes a whole word rather than a single keystroke.
+    pub fn push_undo_state(&mut self, is_word_insert: bool) {
+        let coalesce = is_word_insert && self.coalescing_insert;
+        self.coalescing_insert = is_word_insert;
+        if coalesce {
+            return;
+        }
+
+        self.sql_editor_undo.push((
+            self.sql_editor_content.clone(),
+            self.sql_editor_cursor_x,
+            self.sql_editor_cursor_y,
+        ));
+        if self.sql_editor_undo.len() > MAX_UNDO_STEPS {
+            self.sql_editor_undo.remove(0);
+        }
+        self.sql_editor_redo.clear();
+    }
+
+    pub fn undo_edit(&mut self) {
+        // Snapshots taken before no-op edits (e.g. Backspace at the start) are skipped
+        while let Some(state) = self.sql_editor_undo.pop() {
+            if state.0 != self.sql_editor_content {
+                let current = self.restore_editor_state(state);
+                self.sql_editor_redo.push(current);
+                break;
+            }
+        }
+    }
+
+    pub fn redo_edit(&mut self) {
+        if let Some(state) = self.sql_editor_redo.pop() {
+            let current = self.restore_editor_state(state);
+            self.sql_editor_undo.push(current);
+        }
+    }
+
+    /// Goes back to the database list. The result is dropped; the editor keeps its query
+    /// unless `keep_editor_on_f1` is off.
+    pub fn return_to_database_selection(&mut self) {
+        self.close_notes();
+        if self.keep_editor_on_f1 {
+            self.clear_result();
+        } else {
+            self.clear_workspace();
+        }
+        self.current_screen = ScreenState::DatabaseSelection;
+    }
+
+    /// Empties the editor and the result pane without leaving the table view. The cleared
+    /// query stays on the undo stack.
+    pub fn clear_workspace(&mut self) {
+        self.stop_listening();
+        if !self.sql_editor_content.is_empty() {
+            self.push_undo_state(false);
+        }
+        self.sql_editor_content.clear();
+        self.sql_editor_cursor_x = 0;
+        self.sql_editor_cursor_y = 0;
+        self.sql_editor_scroll = 0;
+        self.sql_error_position = None;
+        self.autocomplete_suggestions.clear();
+        self.clear_result();
+    }
+
+    /// Drops the query result, which belongs to the current database.
+    fn clear_result(&mut self) {
+        self.sql_query_result.clear();
+        self.sql_query_error = None;
+        self.sql_query_success_message = None;
+        self.result_sets = None;
+        self.result_table = None;
+        self.result_kind = None;
+        self.result_snapshot = None;
+        self.result_truncated = false;
+        self.cancel_total_row_count();
+        self.expanded_result_row = None;
+        self.selected_result_row = 0;
+        self.selected_result_column = 0;
+        self.sql_result_scroll = 0;
+        self.sql_result_horizontal_scroll = 0;
+    }
+
+    /// Replaces the editor state, returning the one it replaced.
+    fn restore_editor_state(&mut self, (content, x, y): (String, usize, usize)) -> (String, usize, usize) {
+        self.coalescing_insert = false;
+        let previous = (
+            std::mem::replace(&mut self.sql_editor_content, content),
+            self.sql_editor_cursor_x,
+            self.sql_editor_cursor_y,
+        );
+        self.sql_editor_cursor_x = x;
+        self.sql_editor_cursor_y = y;
+        self.sync_cursor_position();
+        previous
+    }
+
+    /// Byte offset into `sql_editor_content` of the current cursor position.
+    pub fn sql_editor_cursor_offset(&self) -> usize {
+        let mut offset = 0;
+        for (y, line) in self.sql_editor_content.split('\n').enumerate() {
+            if y == self.sql_editor_cursor_y {
+                let x = line
+                    .char_indices()
+                    .nth(self.sql_editor_cursor_x)
+                    .map_or(line.len(), |(i, _)| i);
+                return offset + x;
+            }
+            offset += line.len() + 1;
+        }
+        self.sql_editor_content.len()
+    }
+
+    /// Checks whether the background query has finished and, if so, applies its outcome.
+    pub async fn poll_running_query(&mut self) {
+        let Some(outcome) = self.running_query.as_ref().and_then(RunningQuery::take_result) else {
+            return;
+        };
+        self.running_query = None;
+
+        if self.auto_refresh.is_some() {
+            self.apply_refreshed_result(outcome);
+            return;
+        }
+        self.apply_query_result(outcome);
+        if self.result_truncated && self.count_total_rows {
+            self.start_total_row_count();
+        }
+        self.load_result_table_schema().await;
+
+        // The statement may have created or dropped tables
+        if self.needs_tables_refresh {
+            self.needs_tables_refresh = false;
+            self.refresh_tables().await;
+        }
+    }
+
+    /// Describes the MySQL result's source table unless its schema is cached, so the grid
+    /// can show `TINYINT(1)` columns the driver reports as plain TINYINT as booleans.
+    async fn load_result_table_schema(&mut self) {
+        let Some(table) = self.result_table.clone() else {
+            return;
+        };
+        if self.selected_db_type != 1 || self.table_schemas.contains_key(&table) {
+            return;
+        }
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+        match timeout(Duration::from_secs(5), db_ui.describe_table(&table)).await {
+            Ok(Ok(schema)) => {
+                self.table_schemas.insert(table, schema);
+            }
+            Ok(Err(err)) => log::error!("Error describing {}: {}", table, err),
+            Err(_) => log::error!("Timeout while describing {}", table),
+        }
+    }
+
+    /// Shows the server's active sessions, fetched when the screen is next drawn.
+    pub fn open_sessions(&mut self) {
+        self.selected_session = 0;
+        self.pending_session_kill = None;
+        self.sessions_message = None;
+        self.needs_sessions_refresh = true;
+        self.current_screen = ScreenState::Sessions;
+    }
+
+    pub async fn refresh_sessions(&mut self) {
+        let sessions = match self.database_ui() {
+            Some(db_ui) => timeout(Duration::from_secs(5), db_ui.fetch_active_sessions()).await,
+            None => Ok(Err(DbError::Connection("Unsupported database type".to_string()))),
+        };
+
+        match sessions {
+            Ok(Ok(sessions)) => {
+                self.sessions = sessions;
+                self.selected_session = self.selected_session.min(self.sessions.len().saturating_sub(1));
+            }
+            Ok(Err(err)) => self.sessions_message = Some(format!("Could not list sessions: {}", err)),
+            Err(_) => self.sessions_message = Some("Timeout while listing sessions".to_string()),
+        }
+    }
+
+    /// Shows column stats of the expanded table, computed when the screen is next drawn.
+    /// Stats from an earlier analyze of the same table are kept until then.
+    pub fn open_table_stats(&mut self) {
+        let Some(table) = self
+            .expanded_table
+            .filter(|index| *index == self.selected_table)
+            .and_then(|index| self.tables.get(index))
+        else {
+            self.sql_query_success_message =
+                Some("Expand the table with Enter first to analyze its columns".to_string());
+            return;
+        };
+        if self.table_stats_table.as_ref() != Some(table) {
+            self.table_stats = None;
+            self.table_stats_table = Some(table.clone());
+        }
+        self.table_stats_message = None;
+        self.needs_table_stats_refresh = true;
+        self.current_screen = ScreenState::TableStats;
+    }
+
+    pub async fn refresh_table_stats(&mut self) {
+        let Some(table) = self.table_stats_table.clone() else {
+            return;
+        };
+        // Scans the whole table, so allow far longer than the metadata fetches
+        let stats = match self.database_ui() {
+            Some(db_ui) => timeout(Duration::from_secs(60), db_ui.fetch_table_stats(&table)).await,
+            None => Ok(Err(DbError::Connection("Unsupported database type".to_string()))),
+        };
+
+        match stats {
+            Ok(Ok(stats)) => {
+                self.table_stats = Some(stats);
+                self.table_stats_message = None;
+            }
+            Ok(Err(err)) => self.table_stats_message = Some(format!("Could not analyze {}: {}", table, err)),
+            Err(_) => self.table_stats_message = Some(format!("Timeout while analyzing {}", table)),
+        }
+    }
+
+    /// Terminates session `pid` and lists the sessions again.
+    async fn kill_session(&mut self, pid: i64) {
+        let result = match self.database_ui() {
+            Some(db_ui) => db_ui.kill_session(pid).await,
+            None => Err(DbError::Connection("Unsupported database type".to_string())),
+        };
+        self.sessions_message = Some(match result {
+            Ok(()) => format!("Terminated session {}", pid),
+            Err(err) => format!("Could not terminate session {}: {}", pid, err),
+        });
+        self.needs_sessions_refresh = true;
+    }
+
+    /// Re-fetches the table list and applies it to the sidebar, keeping the selection
+    /// in range.
+    pub async fn refresh_tables(&mut self) {
+        let tables = match self.database_ui() {
+            Some(db_ui) => timeout(Duration::from_secs(5), db_ui.fetch_tables()).await,
+            None => Ok(Ok(Vec::new())),
+        };
+
+        match tables {
+            Ok(Ok(tables)) => {
+                self.table_list_error = None;
+                self.needs_routines_refresh = true;
+                self.set_tables(tables);
+                self.selected_table = self.selected_table.min(self.tables.len().saturating_sub(1));
+                self.tables_scroll = self.tables_scroll.min(self.selected_table);
+                self.last_tables_update = Some(Instant::now());
+            }
+            Ok(Err(e)) => {
+                log::error!("Error fetching tables: {}", e);
+                self.table_list_error = Some(format!("Could not fetch tables: {}", e));
+                self.set_tables(Vec::new());
+            }
+            Err(_) => {
+                log::error!("Timeout while fetching tables");
+                self.table_list_error = Some("Timeout while fetching tables".to_string());
+                self.set_tables(Vec::new());
+            }
+        }
+    }
+
+    /// Applies an auto-refresh result, keeping the selection and scroll position where
+    /// the new rows allow. Refreshing stops at the first error.
+    fn apply_refreshed_result(&mut self, outcome: QueryOutcome) {
+        if !self.apply_result_in_place(outcome) {
+            self.auto_refresh = None;
+            return;
+        }
+        if let Some(message) = &self.sql_query_success_message {
+            self.sql_query_success_message = Some(format!(
+                "{} - refreshing every {}s (F8 stops)",
+                message,
+                self.auto_refresh_interval.as_secs_f64()
+            ));
+        }
+    }
+
+    /// Applies the result of running the shown query again, keeping the selection and
+    /// scroll position where the new rows allow. Returns false when the query failed.
+    fn apply_result_in_place(&mut self, outcome: QueryOutcome) -> bool {
+        let position = (
+            self.selected_result_row,
+            self.selected_result_column,
+            self.sql_result_scroll,
+            self.sql_result_horizontal_scroll,
+        );
+        let needs_tables_refresh = self.needs_tables_refresh;
+        let failed = outcome.is_err();
+        self.apply_query_result(outcome);
+        // Re-running a SELECT cannot change the table list
+        self.needs_tables_refresh = needs_tables_refresh;
+
+        if failed {
+            return false;
+        }
+        let rows = self.sql_query_result.len();
+        let columns = self.sql_query_result.first().map_or(0, IndexMap::len);
+        self.selected_result_row = position.0.min(rows.saturating_sub(1));
+        self.selected_result_column = position.1.min(columns.saturating_sub(1));
+        self.sql_result_scroll = position.2.min(self.selected_result_row);
+        self.sql_result_horizontal_scroll = position.3.min(columns.saturating_sub(1));
+        true
+    }
+
+    fn apply_query_result(&mut self, result: QueryOutcome) {
+        self.expanded_result_row = None;
+        self.result_truncated = false;
+        match result {
+            Ok(QueryOutput { lines: result, message: success_message, elapsed, kind, result_sets }) => {
+                self.result_kind = Some(kind);
+                let success_message = if result_sets.is_empty() {
+                    self.result_sets = None;
+                    success_message
+                } else {
+                    let sets: Vec<Vec<String>> = std::iter::once(result.clone()).chain(result_sets).collect();
+                    let label = result_set_label(0, sets.len());
+                    self.result_sets = Some(ResultSets { sets, current: 0, elapsed });
+                    label
+                };
+                if !result.is_empty() {
+                    if let Some(first_row) = result.first() {
+                        // Debug: print first few characters to understand the format
+                        let debug_len = std::cmp::min(100, first_row.len());
+                        let debug_slice = if first_row.len() >= debug_len {
+                            &first_row[..debug_len]
+                        } else {
+                            first_row
+                        };
+                        self.add_debug_info(format!("First row sample: {:?}", debug_slice));
+                        
+                        let headers: Vec<String> = first_row
+                            .split('\t')
+                            .map(|s| s.trim().to_string())
+                            .filter(|s| !s.is_empty())
+                            .collect();
+                        
+                        // Debug: print headers
+                        self.add_debug_info(format!("Headers found: {:?}", headers));
+                        
+                        // Debug: check if first result preserves order
+                        if !result.is_empty() && result.len() > 1 {
+                            let first_data_row = &result[1];
+                            let first_values: Vec<&str> = first_data_row.split('\t').collect();
+                            self.add_debug_info(format!("First data values (first 5): {:?}", 
+                                first_values.iter().take(5).collect::<Vec<_>>()));
+                        }
+                        
+                        // Debug: show sample of raw data
+                        if result.len() > 1 {
+                            let sample_row = &result[1]; // First data row
+                            let sample_len = std::cmp::min(200, sample_row.len());
+                            let sample_slice = if sample_row.len() >= sample_len {
+                                &sample_row[..sample_len]
+                            } else {
+                                sample_row
+                            };
+                            self.add_debug_info(format!("Sample data row: {:?}", sample_slice));
+                            
+                            // Debug: show character codes for first few characters
+                            let char_codes: Vec<u32> = sample_row.chars().take(20).map(|c| c as u32).collect();
+                            self.add_debug_info(format!("First 20 char codes: {:?}", char_codes));
+                        }
+                        
+                        // Limit the number of rows to prevent memory issues
+                        let max_rows = self.max_display_rows;
+                        self.result_truncated = result.len() > max_rows + 1;
+                        let (limited_result, success_msg) = if self.result_truncated {
+                            let limited = result.into_iter().take(max_rows + 1).collect::<Vec<_>>();
+                            (limited, format!("Results limited to {} rows (-/+ in the results to change)", max_rows))
+                        } else {
+                            (result, success_message)
+                        };
+                        
+                        self.sql_query_result = limited_result
+                            .into_iter()
+                            .skip(1)
+                            .enumerate()
+                            .filter_map(|(row_idx, row)| {
+                                let values: Vec<&str> = row.split('\t').collect();
+                                if values.len() >= headers.len() {
+                                    let mut map = IndexMap::new();
+                                    // Insert in the same order as headers appear in SQL result
+                                    for (i, header) in headers.iter().enumerate() {
+                                        if let Some(value) = values.get(i) {
+                                            // Values are kept as decoded; control characters are
+                                            // only made visible when drawn
+                                            map.insert(header.clone(), value.to_string());
+                                        }
+                                    }
+                                    Some(map)
+                                } else {
+                                    log::warn!("Row {} has {} values but {} headers expected", row_idx, values.len(), headers.len());
+                                    None
+                                }
+                            })
+                            .collect();
+                            
+                        self.add_debug_info(format!("Processed {} rows successfully", self.sql_query_result.len()));
+                        
+                        // Debug: check order in first IndexMap
+                        if let Some(first_map) = self.sql_query_result.first() {
+                            let map_keys: Vec<String> = first_map.keys().cloned().collect();
+                            self.add_debug_info(format!("IndexMap keys order: {:?}", map_keys.iter().take(5).collect::<Vec<_>>()));
+                        }
+                        
+                        let summary = if success_msg.is_empty() {
+                            format!("{} rows", self.sql_query_result.len())
+                        } else {
+                            format!("{} rows - {}", self.sql_query_result.len(), success_msg)
+                        };
+                        self.sql_query_success_message = Some(format!("{} in {}", summary, format_elapsed(elapsed)));
+                    } else {
+                        self.sql_query_result = Vec::new();
+                        self.sql_query_success_message = Some(format!("Empty result set in {}", format_elapsed(elapsed)));
+                    }
+                } else {
+                    self.sql_query_result = Vec::new();
+                    self.sql_query_success_message = Some(format!("{} in {}", success_message, format_elapsed(elapsed)));
+                }
+                self.sql_query_error = None;
+                self.needs_tables_refresh = true;
+                // Reset result navigation state
+                self.selected_result_row = 0;
+                self.selected_result_column = 0;
+                self.sql_result_scroll = 0;
+                self.sql_result_horizontal_scroll = 0;
+            }
+            Err(err) => {
+                self.result_kind = None;
+                self.result_sets = None;
+                if let DbError::Syntax { position: Some(position), .. } = err {
+                    self.jump_to_error_position(position);
+                }
+                self.sql_query_error = Some(describe_query_error(&err));
+                self.sql_query_result.clear();
+                // Reset result navigation state
+                self.selected_result_row = 0;
+                self.selected_result_column = 0;
+                self.sql_result_scroll = 0;
+                self.sql_result_horizontal_scroll = 0;
+            }
+        }
+    }
+
+    /// Shows the next or previous result set of a statement that returned several.
+    pub fn show_result_set(&mut self, forward: bool) {
+        let Some(mut result_sets) = self.result_sets.take() else {
+            return;
+        };
+        let last = result_sets.sets.len() - 1;
+        result_sets.current = if forward {
+            (result_sets.current + 1).min(last)
+        } else {
+            result_sets.current.saturating_sub(1)
+        };
+        let output = QueryOutput {
+            lines: result_sets.sets[result_sets.current].clone(),
+            message: result_set_label(result_sets.current, result_sets.sets.len()),
+            elapsed: result_sets.elapsed,
+            ..QueryOutput::default()
+        };
+        // Nothing ran on the server, so the table list needs no refresh
+        let needs_tables_refresh = self.needs_tables_refresh;
+        self.apply_query_result(Ok(output));
+        self.needs_tables_refresh = needs_tables_refresh;
+        self.result_sets = Some(result_sets);
+    }
+
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::ExecuteQuery => self.start_query(),
+            Command::ExecuteStatement => self.start_current_statement(),
+            Command::AutoRefresh => self.toggle_auto_refresh(),
+            Command::ExplainAnalyze => self.start_explain_analyze(),
+            Command::ShowSessions => self.open_sessions(),
+            Command::AnalyzeTable => self.open_table_stats(),
+            Command::ClearWorkspace => self.clear_workspace(),
+            Command::FormatQuery => self.format_editor_sql(),
+            Command::Notes => self.toggle_notes(),
+            Command::SaveQuery => self.open_snippet_prompt(),
+            Command::SavedQueries => self.open_snippet_picker(),
+            Command::RefreshTables => self.request_tables_refresh(),
+            Command::ToggleBinaryFormat => self.toggle_binary_format(),
+            Command::ToggleAutoLimit => self.toggle_auto_limit(),
+            Command::ToggleDigitGrouping => self.toggle_digit_grouping(),
+            Command::CycleBoolDisplay => self.cycle_bool_display(),
+            Command::ExpandSelectedRow => self.toggle_row_expansion(),
+            Command::SnapshotResult => self.toggle_result_snapshot(),
+            Command::ShowRoutines => self.toggle_routines_list(),
+            Command::CopyCreateTable => self.copy_create_table_statement(),
+            Command::ExportTable => self.toggle_table_export(),
+            Command::ImportCsv => self.open_import_prompt(),
+            Command::CopyRowAsInsert => self.copy_row_as_insert(),
+            Command::EditCell => self.start_cell_edit(),
+            Command::DeleteRow => self.confirm_row_delete(),
+            Command::GoToDatabases => self.return_to_database_selection(),
+            Command::SwitchConnection => {
+                self.current_screen = ScreenState::DbTypeSelection;
+                self.needs_db_refresh = true;
+            }
+            Command::ShowHelp => self.open_help(),
+            Command::Quit if self.has_unsaved_query() => self.prompt_quit_confirmation(),
+            Command::Quit => self.should_quit = true,
+        }
+    }
+
+    /// Shows the selected result row untruncated, wrapping long values, or collapses it again.
+    pub fn toggle_row_expansion(&mut self) {
+        if self.sql_query_result.is_empty() {
+            return;
+        }
+        self.expanded_result_row = if self.expanded_result_row == Some(self.selected_result_row) {
+            None
+        } else {
+            Some(self.selected_result_row)
+        };
+    }
+
+    /// Keeps a copy of the current result, keyed by the selected column, to highlight
+    /// what later runs changed; drops the snapshot when one is already kept.
+    pub fn toggle_result_snapshot(&mut self) {
+        if self.result_snapshot.take().is_some() {
+            self.sql_query_success_message = Some("Snapshot cleared".to_string());
+            return;
+        }
+        let Some(key_column) = self
+            .sql_query_result
+            .first()
+            .and_then(|row| row.keys().nth(self.selected_result_column))
+            .cloned()
+        else {
+            return;
+        };
+        self.sql_query_success_message = Some(format!(
+            "Snapshot of {} rows kept, keyed by {} - re-run the query to compare",
+            self.sql_query_result.len(),
+            key_column
+        ));
+        self.result_snapshot = Some(ResultSnapshot {
+            key_column,
+            rows: self.sql_query_result.clone(),
+        });
+    }
+
+    /// Runs the editor content on a background task. Does nothing while the editor is
+    /// empty or another query is still running.
+    pub fn start_query(&mut self) {
+        if self.stashed_query.is_some() {
+            self.load_statement_from_notes();
+            return;
+        }
+        if self.running_query.is_none() {
+            self.query_start = 0;
+        }
+        self.run_statement(self.sql_editor_content.clone());
+    }
+
+    /// Runs only the `;`-separated statement the cursor is in.
+    pub fn start_current_statement(&mut self) {
+        if self.stashed_query.is_some() {
+            self.load_statement_from_notes();
+            return;
+        }
+        let Some(range) = statement_at(&self.sql_editor_content, self.sql_editor_cursor_offset(), self.selected_db_type == 1) else {
+            self.sql_query_success_message = Some("No statement under the cursor".to_string());
+            return;
+        };
+        if self.running_query.is_none() {
+            self.query_start = self.sql_editor_content[..range.start].chars().count();
+        }
+        self.run_statement(self.sql_editor_content[range].to_string());
+    }
+
+    fn run_statement(&mut self, sql: String) {
+        if sql.is_empty() || self.running_query.is_some() {
+            return;
+        }
+
+        // A query run by hand replaces the one being refreshed
+        self.auto_refresh = None;
+        if let Some(channel) = listen_channel(&sql) {
+            self.start_listening(channel);
+            return;
+        }
+        // Any other statement ends a running LISTEN; UNLISTEN does nothing else
+        if let Some(tail) = self.stop_listening() {
+            if is_unlisten(&sql) {
+                self.sql_query_success_message = Some(format!(
+                    "Stopped listening on {} ({} received)",
+                    tail.channel, tail.received
+                ));
+                return;
+            }
+        }
+
+        // Placeholders are filled in through the parameter prompt before the query runs
+        let param_count = count_placeholders(&sql, self.selected_db_type == 0, self.selected_db_type == 1);
+        if param_count > 0 && self.query_params.len() != param_count {
+            self.query_params.clear();
+            self.param_input.clear();
+            self.param_count = param_count;
+            self.param_query = sql;
+            self.current_screen = ScreenState::ParamPrompt;
+            return;
+        }
+
+        self.sql_query_error = None;
+        self.sql_error_position = None;
+        self.result_table = table_from_query(&sql);
+        self.sql_query_result.clear();
+        self.sql_query_success_message = None;
+        self.spawn_query(sql);
+    }
+
+    /// Runs `sql_content` on a background task; the UI loop applies the outcome.
+    fn spawn_query(&mut self, sql_content: String) {
+        let running = RunningQuery::new();
+        let slot = running.clone();
+        self.cancel_total_row_count();
+        self.last_query = Some((sql_content.clone(), self.query_params.clone()));
+
+        match self.database_ui() {
+            Some(db_ui) => {
+                tokio::spawn(async move {
+                    slot.finish(db_ui.execute_sql_query(&sql_content).await);
+                });
+            }
+            None => slot.finish(Err(DbError::Connection("Unsupported database type".to_string()))),
+        }
+        self.query_params.clear();
+        self.running_query = Some(running);
+    }
+
+    /// Runs the editor's statement under EXPLAIN ANALYZE, asking first when it would
+    /// change data since ANALYZE really executes it.
+    pub fn start_explain_analyze(&mut self) {
+        if self.stashed_query.is_some() {
+            self.load_statement_from_notes();
+            return;
+        }
+        if self.sql_editor_content.trim().is_empty() || self.running_query.is_some() {
+            return;
+        }
+        if count_placeholders(&self.sql_editor_content, self.selected_db_type == 0, self.selected_db_type == 1) > 0 {
+            self.sql_query_error = Some("EXPLAIN ANALYZE does not support bind parameters".to_string());
+            return;
+        }
+        if is_mutating_statement(&self.sql_editor_content) {
+            self.current_screen = ScreenState::ExplainConfirmation;
+            return;
+        }
+        self.spawn_explain_analyze(self.sql_editor_content.clone());
+    }
+
+    /// Runs `sql_content` under EXPLAIN ANALYZE on a background task; the plan is shown
+    /// as the query result.
+    fn spawn_explain_analyze(&mut self, sql_content: String) {
+        self.auto_refresh = None;
+        self.sql_query_error = None;
+        self.sql_error_position = None;
+        self.result_table = None;
+        self.sql_query_result.clear();
+        self.sql_query_success_message = None;
+        // A plan has no rows of its own to count
+        self.cancel_total_row_count();
+        self.last_query = None;
+
+        let running = RunningQuery::new();
+        let slot = running.clone();
+        match self.database_ui() {
+            Some(db_ui) => {
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let outcome = db_ui.explain_analyze(&sql_content).await.map(|plan| QueryOutput {
+                        lines: json_rows_to_lines(&plan),
+                        message: "EXPLAIN ANALYZE - Enter on the plan shows it in full".to_string(),
+                        elapsed: started.elapsed(),
+                        kind: ResultKind::Returned,
+                        ..QueryOutput::default()
+                    });
+                    slot.finish(outcome);
+                });
+            }
+            None => slot.finish(Err(DbError::Connection("Unsupported database type".to_string()))),
+        }
+        self.running_query = Some(running);
+    }
+
+    /// Starts re-running the editor's SELECT every `auto_refresh_interval`, or stops it.
+    pub fn toggle_auto_refresh(&mut self) {
+        if self.auto_refresh.take().is_some() {
+            self.sql_query_success_message = Some("Auto-refresh stopped".to_string());
+            return;
+        }
+
+        let query = self.sql_editor_content.trim().to_string();
+        let first_word = query.split_whitespace().next().unwrap_or_default().to_uppercase();
+        if first_word != "SELECT" && first_word != "WITH" {
+            self.sql_query_error = Some("Auto-refresh only re-runs SELECT queries".to_string());
+            return;
+        }
+        if count_placeholders(&query, self.selected_db_type == 0, self.selected_db_type == 1) > 0 {
+            self.sql_query_error = Some("Auto-refresh does not support query parameters".to_string());
+            return;
+        }
+
+        self.start_query();
+        self.auto_refresh = Some((query, Instant::now()));
+    }
+
+    /// Re-runs the auto-refreshed query once its interval has passed since the last run
+    /// started and no other query is running.
+    pub fn poll_auto_refresh(&mut self) {
+        let Some((query, last_run)) = &self.auto_refresh else {
+            return;
+        };
+        if self.running_query.is_some() || last_run.elapsed() < self.auto_refresh_interval {
+            return;
+        }
+        let query = query.clone();
+        self.auto_refresh = Some((query.clone(), Instant::now()));
+        self.spawn_query(query);
+    }
+
+    /// Subscribes a dedicated connection to `channel` and streams its notifications into
+    /// the result pane until another query runs.
+    pub fn start_listening(&mut self, channel: String) {
+        self.stop_listening();
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+        self.sql_query_error = None;
+        self.sql_error_position = None;
+        self.result_table = None;
+        self.expanded_result_row = None;
+        self.sql_query_result.clear();
+        self.selected_result_row = 0;
+        self.sql_result_scroll = 0;
+
+        let mut tail = NotificationTail::new(channel);
+        let sink = tail.clone();
+        let task = tokio::spawn(async move {
+            let mut listener = match db_ui.listen(&sink.channel).await {
+                Ok(listener) => listener,
+                Err(err) => return sink.push(Err(err)),
+            };
+            loop {
+                let event = listener.recv().await;
+                let failed = event.is_err();
+                sink.push(event);
+                if failed {
+                    break;
+                }
+            }
+        });
+        tail.set_task(task.abort_handle());
+        self.sql_query_success_message = Some(listening_message(&tail));
+        self.notification_tail = Some(tail);
+    }
+
+    /// Ends the LISTEN subscription, if any, returning it.
+    pub fn stop_listening(&mut self) -> Option<NotificationTail> {
+        let tail = self.notification_tail.take()?;
+        tail.stop();
+        Some(tail)
+    }
+
+    /// Appends notifications received since the last tick to the result grid, following
+    /// the newest one unless the user moved the selection away from it.
+    pub fn poll_notifications(&mut self) {
+        let Some(events) = self.notification_tail.as_ref().map(NotificationTail::take_events) else {
+            return;
+        };
+        if events.is_empty() {
+            return;
+        }
+
+        let following = self.selected_result_row + 1 >= self.sql_query_result.len();
+        let received_at = chrono::Local::now().format("%H:%M:%S%.3f").to_string();
+        for event in events {
+            match event {
+                Ok((channel, payload)) => {
+                    self.sql_query_result.push(IndexMap::from([
+                        ("received_at".to_string(), received_at.clone()),
+                        ("channel".to_string(), channel),
+                        ("payload".to_string(), payload),
+                    ]));
+                    if let Some(tail) = self.notification_tail.as_mut() {
+                        tail.received += 1;
+                    }
+                }
+                Err(err) => {
+                    self.stop_listening();
+                    self.sql_query_error = Some(describe_query_error(&err));
+                    return;
+                }
+            }
+        }
+
+        // Only the newest rows are kept, like any other result
+        let excess = self.sql_query_result.len().saturating_sub(self.max_display_rows);
+        self.sql_query_result.drain(..excess);
+        self.selected_result_row = self.selected_result_row.saturating_sub(excess);
+        if following {
+            self.selected_result_row = self.sql_query_result.len() - 1;
+            self.sql_result_scroll = (self.selected_result_row + 1).saturating_sub(self.result_page_size());
+        }
+        if let Some(tail) = &self.notification_tail {
+            self.sql_query_success_message = Some(listening_message(tail));
+        }
+    }
+
+    /// Re-fetches the table list on the next render and drops cached schemas,
+    /// which may be stale if tables changed in another session.
+    pub fn request_tables_refresh(&mut self) {
+        self.needs_tables_refresh = true;
+        self.needs_routines_refresh = true;
+        self.table_schemas.clear();
+        self.column_names.clear();
+        self.expanded_table = None;
+    }
+
+    /// Copies a reconstructed `CREATE TABLE` for the selected table, once its schema has
+    /// been loaded by expanding it.
+    pub fn copy_create_table_statement(&mut self) {
+        let Some(schema) = self
+            .tables
+            .get(self.selected_table)
+            .and_then(|table| self.table_schemas.get(table))
+        else {
+            self.sql_query_success_message =
+                Some("Expand the table with Enter first to load its columns".to_string());
+            return;
+        };
+
+        let statement = schema.create_table_statement();
+        let copied = format!("Copied CREATE TABLE for {}", schema.table_name);
+        self.copy_to_clipboard(statement, copied);
+    }
+
+    /// Puts `text` on the clipboard and reports `copied` in the status line, or that the
+    /// clipboard could not be reached (no display server, headless machine).
+    pub fn copy_to_clipboard(&mut self, text: String, copied: String) {
+        match clipboard::copy_text(text, self.clipboard_method) {
+            Ok(CopiedVia::System) => self.sql_query_success_message = Some(copied),
+            Ok(CopiedVia::Terminal) => {
+                self.sql_query_success_message = Some(format!("{} (sent to the terminal with OSC 52)", copied));
+            }
+            Err(e) => {
+                log::error!("Error copying to clipboard: {}", e);
+                self.sql_query_success_message = Some(format!("Clipboard unavailable: {}", e));
+            }
+        }
+    }
+
+    /// Asks for a CSV file to load, into the selected table unless another is typed.
+    /// Loading uses `COPY`, so it is offered on Postgres only.
+    pub fn open_import_prompt(&mut self) {
+        if self.selected_db_type != 0 {
+            self.sql_query_error = Some("CSV import uses COPY and is only supported on PostgreSQL".to_string());
+            return;
+        }
+        if self.csv_import.is_some() {
+            self.sql_query_error = Some("A CSV import is already running".to_string());
+            return;
+        }
+        let table = self.tables.get(self.selected_table).cloned().unwrap_or_default();
+        self.import_path_input = if table.is_empty() { String::new() } else { export_file_name(&table) };
+        self.import_table_input = table;
+        self.import_editing_table = false;
+        self.current_screen = ScreenState::ImportPrompt;
+    }
+
+    /// Loads the file from the import prompt on a background task.
+    fn start_csv_import(&mut self) {
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+        let import = CsvImport::new(
+            self.import_table_input.trim().to_string(),
+            PathBuf::from(self.import_path_input.trim()),
+        );
+        let sink = import.clone();
+        tokio::spawn(async move {
+            let outcome = match db_ui.detached_client().await {
+                Ok(client) => import_table_csv(client.as_ref(), &sink.table, &sink.path).await,
+                Err(err) => Err(err),
+            };
+            sink.finish(outcome);
+        });
+        self.sql_query_error = None;
+        self.sql_query_success_message =
+            Some(format!("Importing {} into {}…", import.path.display(), import.table));
+        self.csv_import = Some(import);
+    }
+
+    pub fn poll_csv_import(&mut self) {
+        let Some(outcome) = self.csv_import.as_ref().and_then(CsvImport::take_result) else {
+            return;
+        };
+        let Some(import) = self.csv_import.take() else {
+            return;
+        };
+        match outcome {
+            Ok(rows) => {
+                self.sql_query_success_message = Some(format!(
+                    "Imported {} rows into {} from {} in {}",
+                    rows,
+                    import.table,
+                    import.path.display(),
+                    format_elapsed(import.started.elapsed())
+                ));
+            }
+            Err(err) => {
+                self.sql_query_success_message = None;
+                self.sql_query_error = Some(describe_query_error(&err));
+            }
+        }
+    }
+
+    /// Streams the selected table to `<table>.csv` in the working directory on a
+    /// background task, or cancels the export already running.
+    pub fn toggle_table_export(&mut self) {
+        if let Some(export) = self.table_export.take() {
+            export.cancel();
+            self.sql_query_success_message = Some(format!(
+                "Export of {} cancelled after {} rows",
+                export.table,
+                export.rows_written()
+            ));
+            return;
+        }
+        let Some(table) = self.tables.get(self.selected_table).cloned() else {
+            return;
+        };
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+
+        let path = PathBuf::from(export_file_name(&table));
+        let source = self.qualified_table_name(&table);
+        let native_copy = self.selected_db_type == 0;
+        let mut export = TableExport::new(table, path);
+        let sink = export.clone();
+        let task = tokio::spawn(async move {
+            let outcome = match db_ui.detached_client().await {
+                Ok(client) => {
+                    // Without an estimate the export still runs, just without a percentage
+                    if let Ok(Some(rows)) = client.estimated_row_count(&sink.table).await {
+                        sink.set_estimated_rows(rows);
+                    }
+                    export_table_csv(client.as_ref(), &source, &sink.path, sink.progress(), native_copy).await
+                }
+                Err(err) => Err(err),
+            };
+            sink.finish(outcome);
+        });
+        export.set_task(task.abort_handle());
+        self.table_export = Some(export);
+    }
+
+    /// Counts every row of the last query on a background task, for a result that was cut
+    /// at `max_display_rows`. The count can take as long as the query itself.
+    fn start_total_row_count(&mut self) {
+        let (Some((sql, params)), Some(db_ui)) = (self.last_query.clone(), self.database_ui()) else {
+            return;
+        };
+        let mut count = RowCount::new();
+        let sink = count.clone();
+        let task = tokio::spawn(async move {
+            let outcome = match db_ui.detached_client().await {
+                Ok(client) => client.query_with_params(&count_query(&sql), &params).await.and_then(|rows| {
+                    count_from_rows(&rows).ok_or_else(|| DbError::General("COUNT(*) returned no number".into()))
+                }),
+                Err(err) => Err(err),
+            };
+            sink.finish(outcome);
+        });
+        count.set_task(task.abort_handle());
+        self.total_row_count = Some(count);
+    }
+
+    /// Stops counting the previous result's rows and forgets its total.
+    fn cancel_total_row_count(&mut self) {
+        if let Some(count) = self.total_row_count.take() {
+            count.cancel();
+        }
+        self.result_total_rows = None;
+    }
+
+    /// Picks up the total once counted; a failed count just leaves it unknown.
+    pub fn poll_total_row_count(&mut self) {
+        let Some(outcome) = self.total_row_count.as_ref().and_then(RowCount::take_result) else {
+            return;
+        };
+        self.total_row_count = None;
+        match outcome {
+            Ok(total) => self.result_total_rows = Some(total),
+            Err(err) => log::warn!("Could not count the result's rows: {}", err),
+        }
+    }
+
+    /// Reports a finished table export; a progress bar is shown in the status line meanwhile.
+    pub fn poll_table_export(&mut self) {
+        let Some(outcome) = self.table_export.as_ref().and_then(TableExport::take_result) else {
+            return;
+        };
+        let Some(export) = self.table_export.take() else {
+            return;
+        };
+        match outcome {
+            Ok(rows) => {
+                self.sql_query_success_message = Some(format!(
+                    "Exported {} rows of {} to {} in {}",
+                    rows,
+                    export.table,
+                    export.path.display(),
+                    format_elapsed(export.started.elapsed())
+                ));
+            }
+            Err(err) => self.sql_query_error = Some(describe_query_error(&err)),
+        }
+    }
+
+    /// Opens the inline editor for the selected cell, prefilled with its current value.
+    pub fn start_cell_edit(&mut self) {
+        let Some(value) = self
+            .sql_query_result
+            .get(self.selected_result_row)
+            .and_then(|row| row.values().nth(self.selected_result_column))
+        else {
+            return;
+        };
+        if self.result_table.is_none() {
+            self.sql_query_success_message =
+                Some("Rows can only be edited for queries that read a single table".to_string());
+            return;
+        }
+        self.cell_edit_input = value.clone();
+        self.current_screen = ScreenState::CellEdit;
+    }
+
+    /// Credentials and query settings handed to the database adapter.
+    pub fn connection_params(&self) -> ConnectionParams {
+        ConnectionParams {
+            username: self.connection_input.username.clone(),
+            password: self.connection_input.password.clone(),
+            hostname: self.connection_input.hostname.clone(),
+            port: self.connection_input.port.clone(),
+            binary_format: self.binary_format,
+            auto_limit: self.auto_limit_enabled.then_some(self.auto_limit_rows),
+            query_params: self.query_params.clone(),
+            show_system_databases: self.show_system_databases,
+            statement_cache: self.statement_cache_enabled,
+            statement_timeout: self.statement_timeout,
+            read_only_selects: self.read_only_selects,
+        }
+    }
+
+    /// Adapter for the connected database type.
+    pub fn database_ui(&self) -> Option<Box<dyn DatabaseUI + Send + Sync>> {
+        let db_manager = Arc::clone(&self.db_manager);
+        match self.selected_db_type {
+            0 => Some(Box::new(PostgresDatabaseUI::new(db_manager, self.connection_params()))),
+            1 => Some(Box::new(MySqlDatabaseUI::new(db_manager, self.connection_params()))),
+            3 => Some(Box::new(SqliteDatabaseUI::new(db_manager, self.connection_params()))),
+            _ => None,
+        }
+    }
+
+    /// Primary key of the result's source table, plus column types used to cast bound
+    /// values on Postgres. Fails unless every key column is part of the result.
+    async fn result_row_key(
+        &self,
+        db_ui: &(dyn DatabaseUI + Send + Sync),
+        table: &str,
+    ) -> Result<(Vec<String>, HashMap<String, String>), String> {
+        let primary_key = db_ui
+            .fetch_primary_key(table)
+            .await
+            .map_err(|e| describe_query_error(&e))?;
+        if primary_key.is_empty() {
+            return Err(format!("{} has no primary key, so its rows can't be changed", table));
+        }
+        let columns = self.sql_query_result.first();
+        if let Some(missing) = primary_key
+            .iter()
+            .find(|column| columns.is_none_or(|row| !row.contains_key(*column)))
+        {
+            return Err(format!("Include the primary key column {} in the query to change rows", missing));
+        }
+
+        let types = if self.selected_db_type == 0 {
+            db_ui
+                .describe_table(table)
+                .await
+                .map(|schema| {
+                    schema
+                        .columns
+                        .into_iter()
+                        .map(|column| (column.name, column.data_type))
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+        Ok((primary_key, types))
+    }
+
+    /// Writes the edited cell back with an `UPDATE` keyed on the row's primary key.
+    pub async fn save_cell_edit(&mut self) -> Result<String, String> {
+        let table = self
+            .result_table
+            .clone()
+            .ok_or_else(|| "Rows can only be edited for queries that read a single table".to_string())?;
+        let row = self
+            .sql_query_result
+            .get(self.selected_result_row)
+            .cloned()
+            .ok_or_else(|| "No row selected".to_string())?;
+        let column = row
+            .keys()
+            .nth(self.selected_result_column)
+            .cloned()
+            .ok_or_else(|| "No column selected".to_string())?;
+        let db_ui = self
+            .database_ui()
+            .ok_or_else(|| "Unsupported database type".to_string())?;
+        let (primary_key, types) = self.result_row_key(db_ui.as_ref(), &table).await?;
+
+        let statement = update_statement(
+            &self.qualified_table_name(&table),
+            &column,
+            &primary_key,
+            &types,
+            self.selected_db_type == 0,
+            self.selected_db_type == 1,
+        );
+        let new_value = if self.cell_edit_input == "NULL" {
+            Value::Null
+        } else {
+            Value::String(self.cell_edit_input.clone())
+        };
+        let mut params = vec![new_value];
+        params.extend(primary_key.iter().map(|key| Value::String(row[key].clone())));
+        let updated = db_ui
+            .execute_with_params(&statement, &params)
+            .await
+            .map_err(|e| describe_query_error(&e))?;
+        match updated {
+            1 => {}
+            0 => return Err(format!("No row in {} has this key anymore; run the query again", table)),
+            n => return Err(format!("Updated {} rows in {} instead of 1; run the query again", n, table)),
+        }
+
+        if let Some(cell) = self
+            .sql_query_result
+            .get_mut(self.selected_result_row)
+            .and_then(|row| row.get_mut(&column))
+        {
+            *cell = self.cell_edit_input.clone();
+        }
+        Ok(format!("Updated {} in {}", column, table))
+    }
+
+    /// Asks for confirmation before deleting the selected result row.
+    pub fn confirm_row_delete(&mut self) {
+        if self.sql_query_result.get(self.selected_result_row).is_none() {
+            return;
+        }
+        if self.result_table.is_none() {
+            self.sql_query_success_message =
+                Some("Rows can only be deleted for queries that read a single table".to_string());
+            return;
+        }
+        self.current_screen = ScreenState::DeleteConfirmation;
+    }
+
+    /// Deletes the selected row with a `DELETE` keyed on its primary key, then runs the
+    /// query again to show what is left.
+    pub async fn delete_selected_row(&mut self) -> Result<String, String> {
+        let table = self
+            .result_table
+            .clone()
+            .ok_or_else(|| "Rows can only be deleted for queries that read a single table".to_string())?;
+        let row = self
+            .sql_query_result
+            .get(self.selected_result_row)
+            .cloned()
+            .ok_or_else(|| "No row selected".to_string())?;
+        let db_ui = self
+            .database_ui()
+            .ok_or_else(|| "Unsupported database type".to_string())?;
+        let (primary_key, types) = self.result_row_key(db_ui.as_ref(), &table).await?;
+
+        let statement = delete_statement(
+            &self.qualified_table_name(&table),
+            &primary_key,
+            &types,
+            self.selected_db_type == 0,
+            self.selected_db_type == 1,
+        );
+        let params: Vec<Value> = primary_key
+            .iter()
+            .map(|key| Value::String(row[key].clone()))
+            .collect();
+        let deleted = db_ui
+            .execute_with_params(&statement, &params)
+            .await
+            .map_err(|e| describe_query_error(&e))?;
+
+        self.rerun_last_query().await;
+        match deleted {
+            1 => Ok(format!("Deleted 1 row from {}", table)),
+            0 => Err(format!("No row in {} has this key anymore", table)),
+            n => Err(format!("Deleted {} rows from {} instead of 1", n, table)),
+        }
+    }
+
+    /// Runs the shown query again with its parameters and puts the fresh rows in the grid.
+    async fn rerun_last_query(&mut self) {
+        let Some((sql, params)) = self.last_query.clone() else {
+            return;
+        };
+        self.query_params = params;
+        let db_ui = self.database_ui();
+        self.query_params.clear();
+        let Some(db_ui) = db_ui else {
+            return;
+        };
+        self.cancel_total_row_count();
+        let outcome = db_ui.execute_sql_query(&sql).await;
+        self.apply_result_in_place(outcome);
+    }
+
+    /// Copies an `INSERT` statement that recreates the selected result row.
+    pub fn copy_row_as_insert(&mut self) {
+        let Some(row) = self.sql_query_result.get(self.selected_result_row) else {
+            return;
+        };
+        let Some(table) = &self.result_table else {
+            self.sql_query_success_message =
+                Some("Can't tell which table this result came from - query a single table".to_string());
+            return;
+        };
+
+        let statement = insert_statement(table, row, self.selected_db_type == 1);
+        let copied = format!("Copied INSERT INTO {}", table);
+        self.copy_to_clipboard(statement, copied);
+    }
+
+    /// Switches the sidebar between tables and stored functions/procedures.
+    pub fn toggle_routines_list(&mut self) {
+        self.show_routines = !self.show_routines;
+        self.current_focus = FocusedWidget::TablesList;
+    }
+
+    /// Puts a call to the selected routine into the editor, with the cursor between
+    /// the parentheses ready for arguments.
+    pub fn insert_routine_call(&mut self) {
+        let Some(routine) = self.routines.get(self.selected_routine) else {
+            return;
+        };
+        let call = routine_call(&routine.name, &routine.routine_type);
+        self.push_undo_state(false);
+        self.sql_editor_cursor_x = call.len() - 2;
+        self.sql_editor_cursor_y = 0;
+        self.sql_editor_content = call;
+        self.current_focus = FocusedWidget::SqlEditor;
+    }
+
+    /// Fetches the database list. When the server fails or takes longer than 5 seconds the
+    /// list is emptied and the reason shown in its place.
+    pub async fn refresh_databases(&mut self) {
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+        let error = match timeout(Duration::from_secs(5), db_ui.fetch_databases()).await {
+            Ok(Ok(databases)) => {
+                self.database_list_error = None;
+                self.set_databases(databases);
+                self.last_db_update = Some(Instant::now());
+                return;
+            }
+            Ok(Err(err)) => format!("Could not fetch databases: {}", err),
+            Err(_) => "Timeout while fetching databases".to_string(),
+        };
+        log::error!("{}", error);
+        self.database_list_error = Some(error);
+        self.set_databases(Vec::new());
+    }
+
+    /// Replaces the fetched databases, keeping those that match the filter on screen.
+    /// Databases opened recently on the connected server move to the top.
+    pub fn set_databases(&mut self, mut databases: Vec<String>) {
+        if let Some(recent) = self.server_key().and_then(|server| self.recent_databases.get(&server)) {
+            databases.sort_by_key(|db| recent.iter().position(|r| r == db).unwrap_or(usize::MAX));
+        }
+        self.all_databases = databases;
+        self.databases = fuzzy::rank(&self.all_databases, &self.database_filter);
+        self.selected_database = self.selected_database.min(self.databases.len().saturating_sub(1));
+        self.databases_scroll = self.databases_scroll.min(self.selected_database);
+    }
+
+    /// Identifies the connected server for the recent databases list; `None` for the
+    /// scratch database.
+    pub fn server_key(&self) -> Option<String> {
+        (self.selected_db_type != 3)
+            .then(|| format!("{}:{}", self.connection_input.hostname, self.connection_input.port))
+    }
+
+    pub fn is_recent_database(&self, db: &str) -> bool {
+        self.server_key()
+            .and_then(|server| self.recent_databases.get(&server))
+            .is_some_and(|recent| recent.iter().any(|r| r == db))
+    }
+
+    /// Puts `db` first among the server's recent databases and reorders the list.
+    fn record_recent_database(&mut self, db: &str) {
+        let Some(server) = self.server_key() else {
+            return;
+        };
+        let recent = self.recent_databases.entry(server).or_default();
+        recent.retain(|r| r != db);
+        recent.insert(0, db.to_string());
+        recent.truncate(MAX_RECENT_DATABASES);
+        let databases = std::mem::take(&mut self.all_databases);
+        self.set_databases(databases);
+        self.selected_database = 0;
+        self.databases_scroll = 0;
+    }
+
+    /// Replaces the fetched tables, keeping those that match the filter on screen.
+    pub fn set_tables(&mut self, tables: Vec<String>) {
+        self.all_tables = tables;
+        self.tables = fuzzy::rank(&self.all_tables, &self.table_filter);
+    }
+
+    /// Handles a key typed into the filter box of the database or tables list, narrowing
+    /// the list to fuzzy matches, best first, and starting its selection over. Returns
+    /// false for keys that should still navigate the list; Enter also stops editing and
+    /// then selects.
+    fn edit_list_filter(&mut self, key: KeyCode) -> bool {
+        let on_databases = matches!(self.current_screen, ScreenState::DatabaseSelection);
+        let filter = if on_databases {
+            &mut self.database_filter
+        } else {
+            &mut self.table_filter
+        };
+        match key {
+            KeyCode::Char(c) => filter.push(c),
+            KeyCode::Backspace => {
+                filter.pop();
+            }
+            KeyCode::Esc => {
+                filter.clear();
+                self.editing_filter = false;
+            }
+            KeyCode::Enter => {
+                self.editing_filter = false;
+                return false;
+            }
+            _ => return false,
+        }
+
+        if on_databases {
+            self.databases = fuzzy::rank(&self.all_databases, &self.database_filter);
+            self.selected_database = 0;
+            self.databases_scroll = 0;
+        } else {
+            self.tables = fuzzy::rank(&self.all_tables, &self.table_filter);
+            self.selected_table = 0;
+            self.tables_scroll = 0;
+            self.expanded_table = None;
+        }
+        true
+    }
+
+    /// Shows or hides the server's own databases in the database list and re-fetches it.
+    pub fn toggle_system_databases(&mut self) {
+        self.show_system_databases = !self.show_system_databases;
+        self.selected_database = 0;
+        self.databases_scroll = 0;
+        self.needs_db_refresh = true;
+    }
+
+    /// Switches binary columns between base64 and hex; applies to the next executed query.
+    pub fn toggle_binary_format(&mut self) {
+        let (format, name) = match self.binary_format {
+            BinaryFormat::Base64 => (BinaryFormat::Hex, "hex"),
+            BinaryFormat::Hex => (BinaryFormat::Base64, "base64"),
+        };
+        self.binary_format = format;
+        self.sql_query_success_message = Some(format!(
+            "Binary columns will be shown as {} - re-run the query to apply",
+            name
+        ));
+    }
+
+    pub fn toggle_auto_limit(&mut self) {
+        self.auto_limit_enabled = !self.auto_limit_enabled;
+        self.sql_query_success_message = Some(if self.auto_limit_enabled {
+            format!("Auto LIMIT {} enabled for unbounded SELECTs", self.auto_limit_rows)
+        } else {
+            "Auto LIMIT disabled".to_string()
+        });
+    }
+
+    pub fn toggle_digit_grouping(&mut self) {
+        self.group_digits = !self.group_digits;
+        self.sql_query_success_message = Some(if self.group_digits {
+            "Thousands separators enabled".to_string()
+        } else {
+            "Thousands separators disabled".to_string()
+        });
+    }
+
+    pub fn cycle_bool_display(&mut self) {
+        self.bool_display = self.bool_display.next();
+        self.sql_query_success_message = Some(format!(
+            "Booleans shown as {}/{}",
+            self.bool_display.render(true),
+            self.bool_display.render(false)
+        ));
+    }
+
+    /// Connects straight from a database URL, skipping the type and connection screens.
+    /// Lands on the table view when the URL names a database, on the database list
+    /// otherwise, and on the prefilled connection screen if connecting fails.
+    pub async fn auto_connect(&mut self, url: &str, db_type: Option<&str>) {
+        let Some((input, database)) = ConnectionInput::from_url(url) else {
+            log::error!("Ignoring malformed database URL");
+            return;
+        };
+        let scheme = url.split("://").next().unwrap_or_default();
+        let Some(db_type) = db_type.map_or_else(|| db_type_index(scheme), db_type_index) else {
+            log::error!("Unknown database type for auto-connect");
+            return;
+        };
+        if db_type == 2 {
+            log::warn!("SQLite auto-connect is not supported yet");
+            return;
+        }
+
+        self.selected_db_type = db_type;
+        self.connection_input = input;
+        match self.connect_database(database.as_deref()).await {
+            Ok(()) => {
+                self.refresh_server_version().await;
+                if database.is_some() {
+                    self.needs_tables_refresh = true;
+                    self.current_screen = ScreenState::TableView;
+                } else {
+                    self.current_screen = ScreenState::DatabaseSelection;
+                }
+            }
+            Err(err) => {
+                log::error!("Auto-connect failed: {}", err);
+                self.current_screen = ScreenState::ConnectionInput;
+                self.report_connection_error(&err, database.as_deref());
+            }
+        }
+    }
+
+    /// Opens a fresh in-memory SQLite database and goes straight to the table view.
+    pub async fn open_scratch_database(&mut self) {
+        if let Err(err) = self.connect_database(None).await {
+            log::error!("Could not open the scratch database: {}", err);
+            self.show_error(format!("Could not open the scratch database: {}", err));
+            return;
+        }
+        self.refresh_server_version().await;
+        self.needs_tables_refresh = true;
+        self.current_screen = ScreenState::TableView;
+    }
+
+    /// Connects to `db_name`, or to the server's default database, and records which
+    /// database is open once connected.
+    pub async fn connect_database(&mut self, db_name: Option<&str>) -> Result<(), DbError> {
+        let Some(db_ui) = self.database_ui() else {
+            return Ok(());
+        };
+        match db_name {
+            Some(db_name) => db_ui.connect_to_selected_db(db_name).await?,
+            None => db_ui.connect_to_default_db().await?,
+        }
+        self.current_database = Some(db_name.unwrap_or(db_ui.default_database()).to_string());
+        if let Some(db_name) = db_name {
+            self.record_recent_database(db_name);
+        }
+        Ok(())
+    }
+
+    /// Tries the entered connection details on a connection of its own and keeps the
+    /// outcome for the connection screen, which stays open either way.
+    pub async fn test_connection(&mut self) {
+        let result = match (self.connection_input.parsed_port(), self.database_ui()) {
+            (Err(message), _) => Err(message),
+            (Ok(_), None) => Err("Unsupported database type".to_string()),
+            (Ok(_), Some(db_ui)) => match timeout(Duration::from_secs(10), db_ui.test_connection()).await {
+                Ok(Ok(version)) => Ok(format!("Connected: {}", version)),
+                Ok(Err(err)) => Err(self.describe_connection_error(&err, None)),
+                Err(_) => Err("Connection test timed out after 10 s".to_string()),
+            },
+        };
+        self.connection_input.test_result = Some(result);
+    }
+
+    /// The message shown when connecting to `db_name`, or the default database, fails.
+    fn describe_connection_error(&self, err: &DbError, db_name: Option<&str>) -> String {
+        match err {
+            DbError::DatabaseNotFound(_) => {
+                let default_database = self.database_ui().map(|db_ui| db_ui.default_database());
+                format!("Database '{}' does not exist", db_name.or(default_database).unwrap_or_default())
+            }
+            DbError::Authentication(_) => format!(
+                "Authentication failed for user '{}': retype the password",
+                self.connection_input.username
+            ),
+            _ => format!("Connection failed: {}", err),
+        }
+    }
+
+    /// Shows why connecting failed on the error screen. A rejected password is cleared and
+    /// dismissing the error leads to the password field, ready to be typed again.
+    fn report_connection_error(&mut self, err: &DbError, db_name: Option<&str>) {
+        let message = self.describe_connection_error(err, db_name);
+        if let DbError::Authentication(_) = err {
+            self.connection_input.password.clear();
+            self.connection_input.password_visible = false;
+            self.connection_input.current_field = InputField::Password;
+            self.current_screen = ScreenState::ConnectionInput;
+        }
+        self.show_error(message);
+    }
+
+    /// `table` quoted for the server and qualified with the open database where the
+    /// server allows it (MySQL), so generated queries name the table unambiguously.
+    /// Postgres databases cannot qualify table names, and a `schema.table` name keeps its
+    /// own qualifier.
+    pub fn qualified_table_name(&self, table: &str) -> String {
+        let mysql = self.selected_db_type == 1;
+        match (&self.current_database, table.split_once('.')) {
+            (_, Some((schema, name))) if !schema.is_empty() && !name.is_empty() => {
+                format!("{}.{}", quote_identifier(schema, mysql), quote_identifier(name, mysql))
+            }
+            (Some(database), _) if mysql => {
+                format!("{}.{}", quote_identifier(database, mysql), quote_identifier(table, mysql))
+            }
+            _ => quote_identifier(table, mysql),
+        }
+    }
+
+    pub async fn refresh_server_version(&mut self) {
+        let result = match self.database_ui() {
+            Some(db_ui) => db_ui.fetch_server_version().await,
+            None => Err(DbError::Connection("Unsupported database type".to_string())),
+        };
+        self.server_version = match result {
+            Ok(version) => Some(version),
+            Err(err) => {
+                log::warn!("Could not detect server version: {}", err);
+                None
+            }
+        };
+    }
+
+    pub async fn open_autocomplete(&mut self) {
+        self.sync_cursor_position();
+        self.load_completion_columns().await;
+        let suggestions = autocomplete::suggestions(
+            &self.sql_editor_content,
+            self.sql_editor_cursor_offset(),
+            &self.all_tables,
+            &self.column_names,
+        );
+
+        // A single match needs no popup
+        if suggestions.len() == 1 {
+            self.apply_completion(&suggestions[0]);
+        } else {
+            self.autocomplete_suggestions = suggestions;
+            self.selected_suggestion = 0;
+        }
+    }
+
+    /// Makes sure the column names of the table in the editor's `FROM` clause are known,
+    /// taking them from a described table or fetching just the names.
+    async fn load_completion_columns(&mut self) {
+        let Some(table) = autocomplete::referenced_table(&self.sql_editor_content).map(String::from) else {
+            return;
+        };
+        if self.column_names.contains_key(&table) {
+            return;
+        }
+        if let Some(schema) = self.table_schemas.get(&table) {
+            let names = schema.columns.iter().map(|column| column.name.clone()).collect();
+            self.column_names.insert(table, names);
+            return;
+        }
+        let Some(db_ui) = self.database_ui() else {
+            return;
+        };
+        match timeout(Duration::from_secs(2), db_ui.fetch_column_names(&table)).await {
+            Ok(Ok(names)) => {
+                self.column_names.insert(table, names);
+            }
+            Ok(Err(err)) => log::warn!("Could not list the columns of {}: {}", table, err),
+            Err(_) => log::warn!("Timeout while listing the columns of {}", table),
+        }
+    }
+
+    /// Replaces the identifier under the cursor with `completion`.
+    pub fn apply_completion(&mut self, completion: &str) {
+        let offset = self.sql_editor_cursor_offset();
+        let (start, token) = autocomplete::current_token(&self.sql_editor_content, offset);
+        let token_chars = token.chars().count();
+
+        self.sql_editor_content.replace_range(start..offset, completion);
+        self.sql_editor_cursor_x = self.sql_editor_cursor_x - token_chars + completion.chars().count();
+        self.autocomplete_suggestions.clear();
+        self.sync_cursor_position();
+    }
+
+    /// Moves the cursor to the start of the previous word, or to the end of the previous
+    /// line when it is already at the start of one.
+    pub fn move_cursor_word_left(&mut self) {
+        self.sync_cursor_position();
+        let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
+        if self.sql_editor_cursor_x == 0 {
+            if self.sql_editor_cursor_y > 0 {
+                self.sql_editor_cursor_y -= 1;
+                self.sql_editor_cursor_x = lines[self.sql_editor_cursor_y].chars().count();
+            }
+            return;
+        }
+        self.sql_editor_cursor_x = previous_word_start(lines[self.sql_editor_cursor_y], self.sql_editor_cursor_x);
+    }
+
+    /// Moves the cursor to the start of the next word, stopping at the end of the line
+    /// before wrapping to the next one.
+    pub fn move_cursor_word_right(&mut self) {
+        self.sync_cursor_position();
+        let lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
+        let line = lines[self.sql_editor_cursor_y];
+        if self.sql_editor_cursor_x >= line.chars().count() {
+            if self.sql_editor_cursor_y + 1 < lines.len() {
+                self.sql_editor_cursor_y += 1;
+                self.sql_editor_cursor_x = 0;
+            }
+            return;
+        }
+        self.sql_editor_cursor_x = next_word_start(line, self.sql_editor_cursor_x);
+    }
+
+    pub fn delete_char_before_cursor(&mut self) {
+        self.sync_cursor_position();
+        let offset = self.sql_editor_cursor_offset();
+        let Some(removed) = self.sql_editor_content[..offset].chars().next_back() else {
+            return;
+        };
+
+        if self.sql_editor_cursor_x > 0 {
+            self.sql_editor_cursor_x -= 1;
+        } else {
+            // Joining with the previous line puts the cursor where that line used to end
+            self.sql_editor_cursor_y -= 1;
+            self.sql_editor_cursor_x = self
+                .sql_editor_content
+                .split('\n')
+                .nth(self.sql_editor_cursor_y)
+                .map_or(0, |line| line.chars().count());
+        }
+        self.sql_editor_content.remove(offset - removed.len_utf8());
+        self.sync_cursor_position();
+    }
+}
+
+const MOUSE_SCROLL_LINES: usize = 3;
+const HELP_PAGE_LINES: usize = 10;
+
+fn result_set_label(current: usize, count: usize) -> String {
+    format!("result set {} of {} (n/p for the others)", current + 1, count)
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed.as_millis() < 1000 {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Character index of the first word after column `x` of `line`, or the line's end.
+fn next_word_start(line: &str, x: usize) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    let mut x = x.min(chars.len());
+    while x < chars.len() && is_word_char(chars[x]) {
+        x += 1;
+    }
+    while x < chars.len() && !is_word_char(chars[x]) {
+        x += 1;
+    }
+    x
+}
+
+/// Character index of the start of the word before column `x` of `line`, or `0`.
+fn previous_word_start(line: &str, x: usize) -> usize {
+    let chars: Vec<char> = line.chars().collect();
+    let mut x = x.min(chars.len());
+    while x > 0 && !is_word_char(chars[x - 1]) {
+        x -= 1;
+    }
+    while x > 0 && is_word_char(chars[x - 1]) {
+        x -= 1;
+    }
+    x
+}
+
+/// The channel a `LISTEN <channel>` statement subscribes to. Unquoted names are
+/// lowercased the way Postgres folds identifiers.
+fn listen_channel(query: &str) -> Option<String> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    let (keyword, channel) = query.split_once(char::is_whitespace)?;
+    if !keyword.eq_ignore_ascii_case("LISTEN") {
+        return None;
+    }
+    let channel = channel.trim();
+    if channel.len() >= 2 && channel.starts_with('"') && channel.ends_with('"') {
+        Some(channel[1..channel.len() - 1].replace("\"\"", "\""))
+    } else if !channel.is_empty() && !channel.contains(char::is_whitespace) {
+        Some(channel.to_lowercase())
+    } else {
+        None
+    }
+}
+
+fn is_unlisten(query: &str) -> bool {
+    query
+        .split_whitespace()
+        .next()
+        .is_some_and(|word| word.trim_end_matches(';').eq_ignore_ascii_case("UNLISTEN"))
+}
+
+fn listening_message(tail: &NotificationTail) -> String {
+    format!(
+        "Listening on {} ({} received) - run another query or UNLISTEN to stop",
+        tail.channel, tail.received
+    )
+}
+
+/// Turns a failed query into the message shown in the result pane.
+fn describe_query_error(err: &DbError) -> String {
+    match err {
+        DbError::Syntax {
+            message,
+            position: Some(position),
+        } => format!("Syntax error at position {}: {}", position, message),
+        DbError::PermissionDenied(_) => format!(
+            "{}\nHint: the connected user lacks privileges for this statement; check its grants",
+            err
+        ),
+        DbError::Syntax { .. }
+        | DbError::NotFound(_)
+        | DbError::DatabaseNotFound(_)
+        | DbError::Connection(_)
+        | DbError::Import(_)
+        | DbError::Timeout(_) => err.to_string(),
+        _ => format!("SQL Error: {}", err),
+    }
+}
+
+/// The table a query reads from, if it selects from exactly one table without joins
+/// or subqueries.
+fn table_from_query(query: &str) -> Option<String> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let upper: Vec<String> = words.iter().map(|w| w.to_uppercase()).collect();
+    if upper.first().map(String::as_str) != Some("SELECT") || upper.iter().any(|w| w == "JOIN") {
+        return None;
+    }
+
+    let from = upper.iter().position(|w| w == "FROM")?;
+    let table = words.get(from + 1)?.trim_end_matches(';');
+    if table.is_empty() || table.starts_with('(') || table.contains(',') {
+        return None;
+    }
+    Some(table.to_string())
+}
+
+/// Builds an `INSERT` for one result row. Numbers and NULL are written as-is, everything
+/// else as a quoted string; MySQL additionally needs backslashes escaped.
+fn insert_statement(table: &str, row: &IndexMap<String, String>, mysql: bool) -> String {
+    let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    let values: Vec<String> = row
+        .values()
+        .map(|value| {
+            if value == "NULL" || is_plain_number(value) {
+                value.clone()
+            } else {
+                let escaped = if mysql { value.replace('\\', "\\\\") } else { value.clone() };
+                format!("'{}'", escaped.replace('\'', "''"))
+            }
+        })
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({});",
+        table,
+        columns.join(", "),
+        values.join(", ")
+    )
+}
+
+/// Whether a value is a number written the way SQL would print it, so that text such
+/// as zip codes with leading zeros keeps its quotes.
+fn is_plain_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    !leading_zero
+        && digits.chars().next().is_some_and(|c| c.is_ascii_digit())
+        && value.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+/// Bind placeholder number `n`: `$n` cast to the column's full type on Postgres (text
+/// parameters aren't implicitly converted there, and a bare `character` would cut the
+/// value to one character), `?` elsewhere.
+fn placeholder(n: usize, data_type: Option<&String>, numbered: bool) -> String {
+    match data_type {
+        _ if !numbered => "?".to_string(),
+        Some(data_type) if !data_type.is_empty() => format!("${}::{}", n, data_type),
+        _ => format!("${}", n),
+    }
+}
+
+/// Quotes an identifier with backticks on MySQL and double quotes elsewhere, doubling
+/// the quote character inside it.
+fn quote_identifier(name: &str, mysql: bool) -> String {
+    let quote = if mysql { '`' } else { '"' };
+    format!("{0}{1}{0}", quote, name.replace(quote, &format!("{0}{0}", quote)))
+}
+
+/// `WHERE` condition matching a row by its primary key, numbering placeholders from `first`.
+fn key_condition(
+    primary_key: &[String],
+    types: &HashMap<String, String>,
+    first: usize,
+    numbered: bool,
+    mysql: bool,
+) -> String {
+    primary_key
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            format!(
+                "{} = {}",
+                quote_identifier(column, mysql),
+                placeholder(first + i, types.get(column), numbered)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// `UPDATE` of one column of `table`, a name already quoted for the server, with the new
+/// value bound first and the key values after it.
+fn update_statement(
+    table: &str,
+    column: &str,
+    primary_key: &[String],
+    types: &HashMap<String, String>,
+    numbered: bool,
+    mysql: bool,
+) -> String {
+    format!(
+        "UPDATE {} SET {} = {} WHERE {}",
+        table,
+        quote_identifier(column, mysql),
+        placeholder(1, types.get(column), numbered),
+        key_condition(primary_key, types, 2, numbered, mysql)
+    )
+}
+
+/// `DELETE` of the row matching the bound primary key values.
+fn delete_statement(
+    table: &str,
+    primary_key: &[String],
+    types: &HashMap<String, String>,
+    numbered: bool,
+    mysql: bool,
+) -> String {
+    format!(
+        "DELETE FROM {} WHERE {}",
+        table,
+        key_condition(primary_key, types, 1, numbered, mysql)
+    )
+}
+
+/// Statement that invokes a routine: procedures are `CALL`ed, functions selected.
+fn routine_call(name: &str, routine_type: &str) -> String {
+    if routine_type.eq_ignore_ascii_case("PROCEDURE") {
+        format!("CALL {}();", name)
+    } else {
+        format!("SELECT {}();", name)
+    }
+}
+
+/// Splits `$EDITOR` into a program and its arguments (e.g. `code --wait`), falling back
+/// to the platform's default editor when it is unset or empty.
+fn external_editor_command(editor: Option<String>) -> (String, Vec<String>) {
+    let editor = editor.filter(|value| !value.trim().is_empty()).unwrap_or_else(|| {
+        if cfg!(windows) { "notepad" } else { "vi" }.to_string()
+    });
+    let mut parts = editor.split_whitespace().map(str::to_string);
+    let program = parts.next().unwrap_or_default();
+    (program, parts.collect())
+}
+
+/// Pretty-prints values that parse as a JSON object or array, leaving anything else untouched.
+fn format_cell_value(value: &str) -> String {
+    let trimmed = value.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(value) {
+            if let Ok(pretty) = serde_json::to_string_pretty(&json) {
+                return pretty;
+            }
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use dfox_core::{models::schema::{RoutineInfo, SessionInfo}, DbManager};
+    use ratatui::layout::Rect;
+
+    use super::*;
+
+    fn editor_with(content: &str, cursor_x: usize, cursor_y: usize) -> DatabaseClientUI {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_focus = FocusedWidget::SqlEditor;
+        ui.sql_editor_content = content.to_string();
+        ui.sql_editor_cursor_x = cursor_x;
+        ui.sql_editor_cursor_y = cursor_y;
+        ui
+    }
+
+    #[test]
+    fn test_backspace_in_middle_of_line() {
+        let mut ui = editor_with("SELECT * FROM users", 8, 0);
+
+        ui.delete_char_before_cursor();
+
+        assert_eq!(ui.sql_editor_content, "SELECT  FROM users");
+        assert_eq!(ui.sql_editor_cursor_x, 7);
+        assert_eq!(ui.sql_editor_cursor_y, 0);
+    }
+
+    #[test]
+    fn test_backspace_at_line_start_joins_lines() {
+        let mut ui = editor_with("SELECT *\nFROM users", 0, 1);
+
+        ui.delete_char_before_cursor();
+
+        assert_eq!(ui.sql_editor_content, "SELECT *FROM users");
+        assert_eq!(ui.sql_editor_cursor_x, 8);
+        assert_eq!(ui.sql_editor_cursor_y, 0);
+    }
+
+    #[test]
+    fn test_backspace_at_start_of_buffer_is_noop() {
+        let mut ui = editor_with("SELECT 1", 0, 0);
+
+        ui.delete_char_before_cursor();
+
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        assert_eq!(ui.sql_editor_cursor_x, 0);
+    }
+
+    #[test]
+    fn test_cursor_stays_within_a_non_ascii_line() {
+        let mut ui = editor_with("SELECT 'é'", 12, 0);
+
+        ui.sync_cursor_position();
+        assert_eq!(ui.sql_editor_cursor_x, 10);
+
+        ui.delete_char_before_cursor();
+        assert_eq!(ui.sql_editor_content, "SELECT 'é");
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(Duration::from_millis(13)), "13ms");
+        assert_eq!(format_elapsed(Duration::from_millis(1500)), "1.50s");
+    }
+
+    #[test]
+    fn test_jump_to_error_position_skips_leading_whitespace() {
+        let mut ui = editor_with("  SELECT *\nFORM users", 0, 0);
+
+        // The server saw "SELECT *\nFORM users" and points at FORM
+        ui.jump_to_error_position(10);
+
+        assert_eq!((ui.sql_editor_cursor_y, ui.sql_editor_cursor_x), (1, 0));
+        assert_eq!(ui.sql_error_position, Some((1, 0)));
+    }
+
+    #[test]
+    fn test_undo_reverts_a_typed_word_and_redo_reapplies_it() {
+        let mut ui = editor_with("", 0, 0);
+        for c in "SELECT 1".chars() {
+            ui.push_undo_state(!c.is_whitespace());
+            ui.sql_editor_content.push(c);
+        }
+        assert_eq!(ui.sql_editor_undo.len(), 3);
+
+        ui.undo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT ");
+        ui.undo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT");
+        ui.redo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT ");
+
+        // A new edit drops the redo history
+        ui.push_undo_state(false);
+        assert!(ui.sql_editor_redo.is_empty());
+    }
+
+    #[test]
+    fn test_word_boundaries_skip_punctuation() {
+        let line = "SELECT u.name, count(*) FROM users";
+
+        assert_eq!(next_word_start(line, 0), 7);
+        assert_eq!(next_word_start(line, 7), 9);
+        assert_eq!(next_word_start(line, 9), 15);
+        assert_eq!(next_word_start(line, 15), 24);
+        assert_eq!(next_word_start(line, 29), line.len());
+        assert_eq!(previous_word_start(line, line.len()), 29);
+        assert_eq!(previous_word_start(line, 24), 15);
+        assert_eq!(previous_word_start(line, 3), 0);
+    }
+
+    #[test]
+    fn test_word_movement_crosses_line_boundaries() {
+        let mut ui = editor_with("SELECT id\nFROM users", 7, 0);
+
+        ui.move_cursor_word_right();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (9, 0));
+        ui.move_cursor_word_right();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (0, 1));
+        ui.move_cursor_word_right();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (5, 1));
+
+        ui.move_cursor_word_left();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (0, 1));
+        ui.move_cursor_word_left();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (9, 0));
+        ui.move_cursor_word_left();
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y), (7, 0));
+    }
+
+    #[test]
+    fn test_editor_scrolls_to_keep_cursor_visible() {
+        let query = (1..=40).map(|i| format!("-- line {}", i)).collect::<Vec<_>>().join("\n");
+        let mut ui = editor_with(&query, 0, 0);
+        ui.sql_editor_area = Rect::new(0, 0, 80, 12);
+
+        ui.sql_editor_cursor_y = 39;
+        ui.sync_cursor_position();
+        assert_eq!(ui.sql_editor_scroll, 30);
+
+        ui.sql_editor_cursor_y = 35;
+        ui.sync_cursor_position();
+        assert_eq!(ui.sql_editor_scroll, 30);
+
+        ui.sql_editor_cursor_y = 4;
+        ui.sync_cursor_position();
+        assert_eq!(ui.sql_editor_scroll, 4);
+    }
+
+    #[test]
+    fn test_editor_gutter_fits_widest_line_number() {
+        assert_eq!(editor_with("", 0, 0).editor_gutter_width(), 2);
+        assert_eq!(editor_with(&"\n".repeat(9), 0, 0).editor_gutter_width(), 3);
+    }
+
+    #[test]
+    fn test_listen_channel() {
+        assert_eq!(listen_channel("LISTEN orders;"), Some("orders".to_string()));
+        assert_eq!(listen_channel("  listen Orders"), Some("orders".to_string()));
+        assert_eq!(listen_channel("LISTEN \"Order Events\""), Some("Order Events".to_string()));
+        assert_eq!(listen_channel("LISTEN a b"), None);
+        assert_eq!(listen_channel("SELECT 'LISTEN x'"), None);
+        assert!(is_unlisten("UNLISTEN *;"));
+        assert!(!is_unlisten("LISTEN x"));
+    }
+
+    #[test]
+    fn test_poll_table_export_reports_outcome_once_finished() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let export = TableExport::new("events".to_string(), PathBuf::from("events.csv"));
+        ui.table_export = Some(export.clone());
+
+        export.progress().fetch_add(2, std::sync::atomic::Ordering::Relaxed);
+        ui.poll_table_export();
+        assert_eq!(ui.table_export.as_ref().map(TableExport::rows_written), Some(2));
+
+        export.finish(Ok(3));
+        ui.poll_table_export();
+        assert!(ui.table_export.is_none());
+        assert!(ui
+            .sql_query_success_message
+            .as_deref()
+            .is_some_and(|m| m.starts_with("Exported 3 rows of events to events.csv")));
+    }
+
+    #[tokio::test]
+    async fn test_total_row_count_is_picked_up_and_cleared_by_the_next_query() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let count = RowCount::new();
+        count.finish(Ok(48213));
+        ui.total_row_count = Some(count);
+
+        ui.poll_total_row_count();
+        assert_eq!(ui.result_total_rows, Some(48213));
+        assert!(ui.total_row_count.is_none());
+
+        ui.spawn_query("SELECT * FROM orders".to_string());
+        assert_eq!(ui.result_total_rows, None);
+        assert_eq!(ui.last_query, Some(("SELECT * FROM orders".to_string(), Vec::new())));
+    }
+
+    #[test]
+    fn test_poll_notifications_appends_rows_and_stops_on_error() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let tail = NotificationTail::new("orders".to_string());
+        ui.notification_tail = Some(tail.clone());
+
+        tail.push(Ok(("orders".to_string(), "{\"id\":1}".to_string())));
+        tail.push(Ok(("orders".to_string(), "{\"id\":2}".to_string())));
+        ui.poll_notifications();
+
+        assert_eq!(ui.sql_query_result.len(), 2);
+        assert_eq!(ui.sql_query_result[1]["payload"], "{\"id\":2}");
+        assert_eq!(ui.selected_result_row, 1);
+        assert_eq!(ui.notification_tail.as_ref().map(|t| t.received), Some(2));
+
+        tail.push(Err(DbError::Connection("closed".into())));
+        ui.poll_notifications();
+
+        assert!(ui.notification_tail.is_none());
+        assert!(ui.sql_query_error.is_some());
+    }
+
+    #[test]
+    fn test_auto_refresh_only_accepts_plain_selects() {
+        let mut ui = editor_with("DELETE FROM jobs", 0, 0);
+        ui.toggle_auto_refresh();
+        assert!(ui.auto_refresh.is_none());
+        assert!(ui.sql_query_error.is_some());
+
+        let mut ui = editor_with("SELECT * FROM jobs WHERE id = $1", 0, 0);
+        ui.selected_db_type = 0;
+        ui.toggle_auto_refresh();
+        assert!(ui.auto_refresh.is_none());
+    }
+
+    #[test]
+    fn test_auto_refresh_reruns_after_interval_and_keeps_position() {
+        let mut ui = editor_with("SELECT count(*) FROM jobs", 0, 0);
+        // No adapter for this type, so queries fail without touching the network
+        ui.selected_db_type = 2;
+        ui.auto_refresh_interval = Duration::from_secs(60);
+        ui.auto_refresh = Some(("SELECT count(*) FROM jobs".to_string(), Instant::now()));
+
+        ui.poll_auto_refresh();
+        assert!(ui.running_query.is_none());
+
+        ui.auto_refresh = Some(("SELECT count(*) FROM jobs".to_string(), Instant::now() - ui.auto_refresh_interval));
+        ui.poll_auto_refresh();
+        assert!(ui.running_query.is_some());
+        ui.running_query = None;
+
+        let lines = |n: usize| {
+            let mut lines = vec!["id\tstate".to_string()];
+            lines.extend((0..n).map(|i| format!("{}\tqueued", i)));
+            lines
+        };
+        ui.apply_refreshed_result(Ok(QueryOutput { lines: lines(10), ..QueryOutput::default() }));
+        ui.selected_result_row = 7;
+        ui.selected_result_column = 1;
+        ui.sql_result_scroll = 5;
+
+        ui.apply_refreshed_result(Ok(QueryOutput { lines: lines(10), ..QueryOutput::default() }));
+        assert_eq!((ui.selected_result_row, ui.selected_result_column, ui.sql_result_scroll), (7, 1, 5));
+        ui.apply_refreshed_result(Ok(QueryOutput { lines: lines(3), ..QueryOutput::default() }));
+        assert_eq!((ui.selected_result_row, ui.sql_result_scroll), (2, 2));
+
+        ui.apply_refreshed_result(Err(DbError::Connection("gone".into())));
+        assert!(ui.auto_refresh.is_none());
+    }
+
+    #[test]
+    fn test_clear_workspace_keeps_table_view_and_allows_undo() {
+        let mut ui = editor_with("SELECT 1\nFROM t", 3, 1);
+        ui.current_screen = ScreenState::TableView;
+        ui.sql_query_result = vec![IndexMap::from([("a".to_string(), "1".to_string())])];
+        ui.sql_query_error = Some("boom".into());
+        ui.selected_result_row = 0;
+        ui.sql_editor_scroll = 1;
+
+        ui.clear_workspace();
+
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.sql_editor_content.is_empty());
+        assert!(ui.sql_query_result.is_empty());
+        assert!(ui.sql_query_error.is_none());
+        assert_eq!((ui.sql_editor_cursor_x, ui.sql_editor_cursor_y, ui.sql_editor_scroll), (0, 0, 0));
+
+        ui.undo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT 1\nFROM t");
+    }
+
+    #[test]
+    fn test_returning_to_database_list_keeps_the_query() {
+        let mut ui = editor_with("SELECT 1", 8, 0);
+        ui.current_screen = ScreenState::TableView;
+        ui.sql_query_result = vec![IndexMap::from([("a".to_string(), "1".to_string())])];
+
+        ui.return_to_database_selection();
+        assert!(matches!(ui.current_screen, ScreenState::DatabaseSelection));
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        assert!(ui.sql_query_result.is_empty());
+
+        ui.keep_editor_on_f1 = false;
+        ui.return_to_database_selection();
+        assert!(ui.sql_editor_content.is_empty());
+    }
+
+    #[test]
+    fn test_notes_swap_with_the_query_and_load_a_statement() {
+        let mut ui = editor_with("SELECT 1", 8, 0);
+        ui.toggle_notes();
+        assert!(ui.sql_editor_content.is_empty());
+
+        ui.sql_editor_content = "SELECT 2;\nSELECT 3;".to_string();
+        ui.sql_editor_cursor_y = 1;
+        ui.sql_editor_cursor_x = 3;
+        ui.start_query();
+        assert!(ui.stashed_query.is_none());
+        assert!(ui.running_query.is_none());
+        assert_eq!(ui.sql_editor_content, "SELECT 3;");
+        assert_eq!(ui.notes.get(&ui.notes_profile()).map(String::as_str), Some("SELECT 2;\nSELECT 3;"));
+
+        ui.undo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        ui.toggle_notes();
+        assert_eq!(ui.sql_editor_content, "SELECT 2;\nSELECT 3;");
+        ui.sql_editor_content = "-- naïve\nSELECT 'ü'".to_string();
+        ui.toggle_notes();
+        ui.toggle_notes();
+        assert_eq!((ui.sql_editor_cursor_y, ui.sql_editor_cursor_x), (1, 10));
+    }
+
+    #[test]
+    fn test_saved_queries_are_recalled_by_name() {
+        let mut ui = editor_with("SELECT * FROM pg_stat_statements\n", 0, 0);
+        ui.open_snippet_prompt();
+        assert!(matches!(ui.current_screen, ScreenState::SnippetName));
+        ui.snippet_input = " top 10 slow queries ".to_string();
+        ui.save_snippet();
+        ui.snippets.insert("table sizes".to_string(), "SELECT 2".to_string());
+
+        ui.sql_editor_content.clear();
+        ui.open_snippet_picker();
+        ui.snippet_input = "slow".to_string();
+        assert_eq!(ui.snippet_names(), vec!["top 10 slow queries".to_string()]);
+        ui.load_snippet("top 10 slow queries");
+        assert_eq!(ui.sql_editor_content, "SELECT * FROM pg_stat_statements");
+        assert_eq!(ui.sql_editor_cursor_x, 32);
+    }
+
+    #[test]
+    fn test_resize_selected_column_overrides_auto_width() {
+        let mut ui = editor_with("", 0, 0);
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "1".to_string());
+        row.insert("note".to_string(), "x".repeat(100));
+        ui.sql_query_result.push(row);
+        assert_eq!(ui.result_column_width("note"), 40);
+
+        ui.selected_result_column = 1;
+        ui.resize_selected_column(4);
+        assert_eq!(ui.result_column_width("note"), 44);
+        assert_eq!(ui.result_column_width("id"), 8);
+
+        ui.selected_result_column = 0;
+        ui.resize_selected_column(-100);
+        assert_eq!(ui.result_column_width("id"), MIN_COLUMN_WIDTH);
+    }
+
+    #[test]
+    fn test_width_limits_apply_to_auto_sized_columns() {
+        let mut ui = editor_with("", 0, 0);
+        let mut row = IndexMap::new();
+        row.insert("note".to_string(), "x".repeat(100));
+        ui.sql_query_result.push(row);
+
+        ui.adjust_max_column_width(80);
+        assert_eq!(ui.max_column_width, 120);
+        // Only the first 50 characters count until the truncation length is raised
+        assert_eq!(ui.result_column_width("note"), 52);
+        for _ in 0..5 {
+            ui.adjust_cell_truncate_length(true);
+        }
+        assert_eq!(ui.result_column_width("note"), 102);
+
+        ui.adjust_max_column_width(-1000);
+        assert_eq!(ui.result_column_width("note"), 8);
+    }
+
+    #[tokio::test]
+    async fn test_query_with_placeholders_prompts_for_each_param() {
+        let mut ui = editor_with("SELECT * FROM users WHERE id = $1 AND name = $2", 0, 0);
+        ui.start_query();
+        assert!(matches!(ui.current_screen, ScreenState::ParamPrompt));
+        assert_eq!(ui.param_count, 2);
+        assert!(ui.running_query.is_none());
+
+        for c in "7".chars() {
+            ui.handle_param_prompt_input(KeyCode::Char(c)).await;
+        }
+        ui.handle_param_prompt_input(KeyCode::Enter).await;
+        assert_eq!(ui.query_params, vec![serde_json::Value::from(7)]);
+        assert!(matches!(ui.current_screen, ScreenState::ParamPrompt));
+
+        ui.handle_param_prompt_input(KeyCode::Esc).await;
+        assert!(ui.query_params.is_empty());
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+    }
+
+    #[test]
+    fn test_current_statement_runs_only_the_one_under_the_cursor() {
+        let mut ui = editor_with("SELECT 1;\n\nSELECT * FROM users WHERE id = $1;", 5, 2);
+        ui.start_current_statement();
+        assert!(matches!(ui.current_screen, ScreenState::ParamPrompt));
+        assert_eq!(ui.param_count, 1);
+        assert_eq!(ui.param_query, "SELECT * FROM users WHERE id = $1;");
+        assert_eq!(ui.query_start, 11);
+
+        // Positions the server reports are relative to the statement that ran
+        ui.jump_to_error_position(8);
+        assert_eq!((ui.sql_editor_cursor_y, ui.sql_editor_cursor_x), (2, 7));
+    }
+
+    #[test]
+    fn test_insert_routine_call() {
+        let mut ui = editor_with("", 0, 0);
+        ui.routines = vec![
+            RoutineInfo {
+                name: "total_sales".to_string(),
+                routine_type: "FUNCTION".to_string(),
+                return_type: Some("numeric".to_string()),
+            },
+            RoutineInfo {
+                name: "archive_orders".to_string(),
+                routine_type: "PROCEDURE".to_string(),
+                return_type: None,
+            },
+        ];
+
+        ui.insert_routine_call();
+        assert_eq!(ui.sql_editor_content, "SELECT total_sales();");
+        assert_eq!(ui.sql_editor_cursor_x, "SELECT total_sales(".len());
+
+        ui.selected_routine = 1;
+        ui.insert_routine_call();
+        assert_eq!(ui.sql_editor_content, "CALL archive_orders();");
+        ui.undo_edit();
+        assert_eq!(ui.sql_editor_content, "SELECT total_sales();");
+    }
+
+    #[test]
+    fn test_table_from_query() {
+        assert_eq!(table_from_query("SELECT * FROM users WHERE id = 1"), Some("users".to_string()));
+        assert_eq!(table_from_query("select id from public.users;"), Some("public.users".to_string()));
+        assert_eq!(table_from_query("SELECT * FROM a JOIN b ON a.id = b.id"), None);
+        assert_eq!(table_from_query("SELECT * FROM (SELECT 1) t"), None);
+        assert_eq!(table_from_query("DELETE FROM users"), None);
+    }
+
+    #[test]
+    fn test_insert_statement_quotes_values_by_type() {
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "7".to_string());
+        row.insert("name".to_string(), "O'Brien \\ co".to_string());
+        row.insert("zip".to_string(), "02134".to_string());
+        row.insert("note".to_string(), "NULL".to_string());
+
+        assert_eq!(
+            insert_statement("users", &row, false),
+            "INSERT INTO users (id, name, zip, note) VALUES (7, 'O''Brien \\ co', '02134', NULL);"
+        );
+        assert_eq!(
+            insert_statement("users", &row, true),
+            "INSERT INTO users (id, name, zip, note) VALUES (7, 'O''Brien \\\\ co', '02134', NULL);"
+        );
+    }
+
+    #[test]
+    fn test_update_statement() {
+        let primary_key = vec!["order_id".to_string(), "line".to_string()];
+        let types = HashMap::from([
+            ("order_id".to_string(), "integer".to_string()),
+            ("line".to_string(), "integer".to_string()),
+            ("sku".to_string(), "character(10)".to_string()),
+            ("status".to_string(), "order_status".to_string()),
+        ]);
+
+        assert_eq!(
+            update_statement("\"line_items\"", "sku", &primary_key, &types, true, false),
+            "UPDATE \"line_items\" SET \"sku\" = $1::character(10) WHERE \"order_id\" = $2::integer AND \"line\" = $3::integer"
+        );
+        assert_eq!(
+            update_statement("\"line_items\"", "status", &primary_key, &types, true, false),
+            "UPDATE \"line_items\" SET \"status\" = $1::order_status WHERE \"order_id\" = $2::integer AND \"line\" = $3::integer"
+        );
+        assert_eq!(
+            update_statement("\"line_items\"", "note", &primary_key, &types, true, false),
+            "UPDATE \"line_items\" SET \"note\" = $1 WHERE \"order_id\" = $2::integer AND \"line\" = $3::integer"
+        );
+        assert_eq!(
+            update_statement("`shop`.`line_items`", "sku", &primary_key, &HashMap::new(), false, true),
+            "UPDATE `shop`.`line_items` SET `sku` = ? WHERE `order_id` = ? AND `line` = ?"
+        );
+    }
+
+    #[test]
+    fn test_delete_statement() {
+        let primary_key = vec!["id".to_string()];
+        let types = HashMap::from([("id".to_string(), "bigint".to_string())]);
+        assert_eq!(
+            delete_statement("users", &primary_key, &types, true, false),
+            "DELETE FROM users WHERE \"id\" = $1::bigint"
+        );
+        assert_eq!(
+            delete_statement("users", &primary_key, &types, false, true),
+            "DELETE FROM users WHERE `id` = ?"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_declining_delete_keeps_the_row() {
+        let mut ui = editor_with("", 0, 0);
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "1".to_string());
+        ui.sql_query_result.push(row);
+        ui.result_table = Some("users".to_string());
+
+        ui.confirm_row_delete();
+        assert!(matches!(ui.current_screen, ScreenState::DeleteConfirmation));
+        ui.handle_delete_confirmation_input(KeyCode::Char('n')).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert_eq!(ui.sql_query_result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_runs_the_query_again() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.selected_db_type = 3;
+        ui.handle_db_type_selection_input(KeyCode::Enter).await;
+        let db_ui = ui.database_ui().unwrap();
+        db_ui.execute_sql_query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)").await.unwrap();
+        db_ui.execute_sql_query("INSERT INTO notes VALUES (1, 'a'), (2, 'b')").await.unwrap();
+        ui.last_query = Some(("SELECT * FROM notes ORDER BY id".to_string(), Vec::new()));
+        ui.rerun_last_query().await;
+        ui.result_table = Some("notes".to_string());
+        ui.selected_result_row = 1;
+
+        // Rows added since the query ran show up after the delete
+        db_ui.execute_sql_query("INSERT INTO notes VALUES (3, 'c')").await.unwrap();
+        ui.confirm_row_delete();
+        ui.handle_delete_confirmation_input(KeyCode::Char('y')).await;
+        assert_eq!(ui.sql_query_success_message.as_deref(), Some("Deleted 1 row from notes"));
+        let ids: Vec<&str> = ui.sql_query_result.iter().map(|row| row["id"].as_str()).collect();
+        assert_eq!(ids, ["1", "3"]);
+        assert_eq!(ui.selected_result_row, 1);
+
+        db_ui.execute_sql_query("DELETE FROM notes WHERE id = 3").await.unwrap();
+        ui.confirm_row_delete();
+        ui.handle_delete_confirmation_input(KeyCode::Char('y')).await;
+        assert!(ui.sql_query_error.as_deref().is_some_and(|error| error.starts_with("No row in notes")));
+        assert_eq!(ui.sql_query_result.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_explain_analyze_confirms_mutating_statements() {
+        let mut ui = editor_with("DELETE FROM users WHERE id = 1", 0, 0);
+        ui.start_explain_analyze();
+        assert!(matches!(ui.current_screen, ScreenState::ExplainConfirmation));
+        assert!(ui.running_query.is_none());
+
+        ui.handle_explain_confirmation_input(KeyCode::Char('n')).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.running_query.is_none());
+
+        let mut ui = editor_with("SELECT * FROM users", 0, 0);
+        ui.start_explain_analyze();
+        assert!(!matches!(ui.current_screen, ScreenState::ExplainConfirmation));
+        assert!(ui.running_query.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_screen_confirms_before_killing() {
+        let mut ui = editor_with("", 0, 0);
+        ui.open_sessions();
+        assert!(matches!(ui.current_screen, ScreenState::Sessions));
+        assert!(ui.needs_sessions_refresh);
+
+        ui.sessions = [41, 42]
+            .into_iter()
+            .map(|pid| SessionInfo { pid, user: None, state: None, query: None, seconds: None })
+            .collect();
+        ui.handle_sessions_input(KeyCode::Down).await;
+        ui.handle_sessions_input(KeyCode::Char('k')).await;
+        assert_eq!(ui.pending_session_kill, Some(42));
+
+        // Anything but y cancels
+        ui.handle_sessions_input(KeyCode::Char('n')).await;
+        assert_eq!(ui.pending_session_kill, None);
+        assert!(ui.sessions_message.is_none());
+
+        ui.handle_sessions_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.sessions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_help_returns_to_previous_screen() {
+        let mut ui = editor_with("", 0, 0);
+        ui.current_screen = ScreenState::DatabaseSelection;
+        ui.handle_database_selection_input(KeyCode::Char('?')).await.unwrap();
+        assert!(matches!(ui.current_screen, ScreenState::Help));
+
+        ui.handle_help_input(KeyCode::End).await;
+        assert_eq!(ui.help_scroll, help::line_count() - 1);
+        ui.handle_help_input(KeyCode::Down).await;
+        assert_eq!(ui.help_scroll, help::line_count() - 1);
+
+        ui.handle_help_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::DatabaseSelection));
+    }
+
+    #[test]
+    fn test_table_stats_need_an_expanded_table() {
+        let mut ui = editor_with("", 0, 0);
+        ui.current_screen = ScreenState::TableView;
+        ui.tables = vec!["users".to_string(), "orders".to_string()];
+        ui.open_table_stats();
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(!ui.needs_table_stats_refresh);
+
+        ui.expanded_table = Some(1);
+        ui.selected_table = 1;
+        ui.open_table_stats();
+        assert!(matches!(ui.current_screen, ScreenState::TableStats));
+        assert!(ui.needs_table_stats_refresh);
+        assert_eq!(ui.table_stats_table.as_deref(), Some("orders"));
+    }
+
+    #[test]
+    fn test_result_snapshot_is_keyed_by_selected_column() {
+        let mut ui = editor_with("", 0, 0);
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "1".to_string());
+        row.insert("email".to_string(), "a@example.com".to_string());
+        ui.sql_query_result.push(row);
+        ui.selected_result_column = 1;
+
+        ui.toggle_result_snapshot();
+        let snapshot = ui.result_snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.key_column, "email");
+        assert_eq!(snapshot.rows, ui.sql_query_result);
+
+        ui.toggle_result_snapshot();
+        assert!(ui.result_snapshot.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cell_edit_requires_single_table_result() {
+        let mut ui = editor_with("", 0, 0);
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "1".to_string());
+        ui.sql_query_result.push(row);
+        ui.current_screen = ScreenState::TableView;
+
+        ui.start_cell_edit();
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+
+        ui.result_table = Some("users".to_string());
+        ui.start_cell_edit();
+        assert!(matches!(ui.current_screen, ScreenState::CellEdit));
+        assert_eq!(ui.cell_edit_input, "1");
+
+        ui.handle_cell_edit_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+    }
+
+    #[tokio::test]
+    async fn test_cell_edit_fails_when_the_row_is_gone() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.selected_db_type = 3;
+        ui.handle_db_type_selection_input(KeyCode::Enter).await;
+        let db_ui = ui.database_ui().unwrap();
+        db_ui.execute_sql_query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)").await.unwrap();
+        db_ui.execute_sql_query("INSERT INTO notes VALUES (1, 'a')").await.unwrap();
+
+        let row = IndexMap::from([("id".to_string(), "1".to_string()), ("body".to_string(), "a".to_string())]);
+        ui.sql_query_result = vec![row];
+        ui.result_table = Some("notes".to_string());
+        ui.selected_result_column = 1;
+        ui.cell_edit_input = "b".to_string();
+        assert!(ui.save_cell_edit().await.is_ok());
+        assert_eq!(ui.sql_query_result[0]["body"], "b");
+
+        db_ui.execute_sql_query("DELETE FROM notes").await.unwrap();
+        ui.cell_edit_input = "c".to_string();
+        assert!(ui.save_cell_edit().await.unwrap_err().starts_with("No row in notes"));
+        assert_eq!(ui.sql_query_result[0]["body"], "b");
+    }
+
+    #[tokio::test]
+    async fn test_import_prompt_defaults_to_selected_table() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.tables = vec!["orders".to_string()];
+        ui.open_import_prompt();
+        assert!(matches!(ui.current_screen, ScreenState::ImportPrompt));
+        assert_eq!((ui.import_path_input.as_str(), ui.import_table_input.as_str()), ("orders.csv", "orders"));
+
+        ui.handle_import_prompt_input(KeyCode::Tab).await;
+        ui.handle_import_prompt_input(KeyCode::Char('2')).await;
+        assert_eq!(ui.import_table_input, "orders2");
+        ui.handle_import_prompt_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+
+        // MySQL has no COPY
+        ui.selected_db_type = 1;
+        ui.open_import_prompt();
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.sql_query_error.is_some());
+    }
+
+    #[test]
+    fn test_max_display_rows_steps_between_presets() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.adjust_max_display_rows(true);
+        ui.adjust_max_display_rows(true);
+        assert_eq!(ui.max_display_rows, 10_000);
+
+        // A cap set from the environment moves to the nearest step
+        ui.max_display_rows = 2000;
+        ui.adjust_max_display_rows(false);
+        assert_eq!(ui.max_display_rows, 1000);
+        ui.max_display_rows = 100;
+        ui.adjust_max_display_rows(false);
+        assert_eq!(ui.max_display_rows, 100);
+    }
+
+    #[test]
+    fn test_result_sets_page_with_n_and_p() {
+        let lines = |set: &[&str]| set.iter().map(|line| line.to_string()).collect::<Vec<_>>();
+        let mut ui = editor_with("CALL report()", 0, 0);
+        ui.apply_query_result(Ok(QueryOutput {
+            lines: lines(&["region", "north"]),
+            result_sets: vec![lines(&["total", "3", "4"])],
+            ..QueryOutput::default()
+        }));
+        assert_eq!(ui.sql_query_result[0]["region"], "north");
+        assert!(ui.sql_query_success_message.as_deref().unwrap().contains("result set 1 of 2"));
+
+        ui.needs_tables_refresh = false;
+        ui.show_result_set(true);
+        ui.show_result_set(true);
+        assert_eq!(ui.sql_query_result.len(), 2);
+        assert_eq!(ui.sql_query_result[1]["total"], "4");
+        assert!(!ui.needs_tables_refresh);
+
+        ui.show_result_set(false);
+        assert_eq!(ui.sql_query_result[0]["region"], "north");
+    }
+
+    #[test]
+    fn test_external_editor_command() {
+        assert_eq!(
+            external_editor_command(Some("code --wait".to_string())),
+            ("code".to_string(), vec!["--wait".to_string()])
+        );
+        let fallback = if cfg!(windows) { "notepad" } else { "vi" };
+        assert_eq!(external_editor_command(None).0, fallback);
+        assert_eq!(external_editor_command(Some("  ".to_string())).0, fallback);
+    }
+
+    #[test]
+    fn test_describe_query_error() {
+        let syntax = DbError::Syntax {
+            message: "syntax error at or near \"FORM\"".to_string(),
+            position: Some(10),
+        };
+        assert_eq!(
+            describe_query_error(&syntax),
+            "Syntax error at position 10: syntax error at or near \"FORM\""
+        );
+
+        let denied = DbError::PermissionDenied("permission denied for table users".to_string());
+        assert!(describe_query_error(&denied).starts_with("Permission denied: permission denied for table users\nHint:"));
+
+        let other = DbError::General("boom".to_string());
+        assert_eq!(describe_query_error(&other), "SQL Error: Error: boom");
+    }
+
+    #[test]
+    fn test_describe_connection_error_names_missing_database() {
+        let ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let missing = DbError::DatabaseNotFound("database \"shpo\" does not exist".to_string());
+        assert_eq!(ui.describe_connection_error(&missing, Some("shpo")), "Database 'shpo' does not exist");
+        assert_eq!(ui.describe_connection_error(&missing, None), "Database 'postgres' does not exist");
+
+        let refused = DbError::Connection("refused".to_string());
+        assert_eq!(ui.describe_connection_error(&refused, None), "Connection failed: Connection error: refused");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_password_is_cleared_for_retry() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.connection_input.username = "admin".to_string();
+        ui.connection_input.password = "hunter3".to_string();
+        ui.connection_input.current_field = InputField::Port;
+
+        let rejected = DbError::Authentication("password authentication failed for user \"admin\"".to_string());
+        ui.current_screen = ScreenState::DatabaseSelection;
+        ui.report_connection_error(&rejected, None);
+        assert!(matches!(
+            &ui.current_screen,
+            ScreenState::Error(message) if message == "Authentication failed for user 'admin': retype the password"
+        ));
+        assert!(ui.connection_input.password.is_empty());
+        assert!(matches!(ui.connection_input.current_field, InputField::Password));
+
+        ui.handle_error_input(KeyCode::Enter).await;
+        assert!(matches!(ui.current_screen, ScreenState::ConnectionInput));
+    }
+
+    #[tokio::test]
+    async fn test_connection_test_reports_inline_and_clears_on_edit() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::ConnectionInput;
+        ui.connection_input.current_field = InputField::Port;
+
+        ui.test_connection().await;
+        assert!(matches!(ui.current_screen, ScreenState::ConnectionInput));
+        assert_eq!(ui.connection_input.test_result, Some(Err("Port is required".to_string())));
+
+        ui.handle_input_event(KeyCode::Char('5')).await.unwrap();
+        assert_eq!(ui.connection_input.test_result, None);
+    }
+
+    #[tokio::test]
+    async fn test_error_screen_returns_to_previous_screen() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::DatabaseSelection;
+        ui.show_error("Connection failed: refused");
+        ui.show_error("Connection failed: refused again");
+
+        ui.handle_error_input(KeyCode::Char('x')).await;
+        assert!(matches!(ui.current_screen, ScreenState::Error(_)));
+        ui.handle_error_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::DatabaseSelection));
+    }
+
+    #[test]
+    fn test_format_cell_value_pretty_prints_json() {
+        assert_eq!(
+            format_cell_value(r#"{"id":1,"tags":["a"]}"#),
+            "{\n  \"id\": 1,\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+        assert_eq!(format_cell_value("{not json"), "{not json");
+        assert_eq!(format_cell_value("42"), "42");
+    }
+
+    #[tokio::test]
+    async fn test_enter_opens_selected_cell() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let mut row = IndexMap::new();
+        row.insert("id".to_string(), "7".to_string());
+        row.insert("payload".to_string(), r#"{"ok":true}"#.to_string());
+        ui.sql_query_result = vec![row];
+        ui.current_focus = FocusedWidget::_QueryResult;
+        ui.selected_result_column = 1;
+
+        ui.open_cell_detail();
+
+        assert!(matches!(ui.current_screen, ScreenState::CellDetail));
+        let (title, value) = ui.cell_detail.clone().unwrap();
+        assert_eq!(title, "Row 1 - payload");
+        assert_eq!(value, "{\n  \"ok\": true\n}");
+
+        ui.handle_cell_detail_input(KeyCode::Esc).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.cell_detail.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cell_detail_scrolls_wrapped_lines() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.cell_detail = Some(("Row 1 - body".to_string(), "x".repeat(50)));
+        ui.cell_detail_area = Rect::new(0, 0, 12, 5);
+        ui.current_screen = ScreenState::CellDetail;
+
+        // 50 chars in a 10 column wide pane wrap onto 5 lines
+        assert_eq!(ui.cell_detail_line_count(), 5);
+        ui.handle_cell_detail_input(KeyCode::End).await;
+        assert_eq!(ui.cell_detail_scroll, 4);
+
+        ui.handle_cell_detail_input(KeyCode::Char('w')).await;
+        assert!(!ui.cell_detail_wrap);
+        assert_eq!(ui.cell_detail_line_count(), 1);
+        assert_eq!(ui.cell_detail_scroll, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_database_fetch_shows_error_instead_of_list() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.set_databases(vec!["stale".to_string()]);
+
+        // Nothing is connected, so the fetch fails
+        ui.refresh_databases().await;
+        assert!(ui.databases.is_empty());
+        let error = ui.database_list_error.clone().unwrap();
+        assert!(error.starts_with("Could not fetch databases"), "{}", error);
+    }
+
+    #[test]
+    fn test_recent_databases_float_to_the_top() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.connection_input.hostname = "db.local".to_string();
+        ui.connection_input.port = "5432".to_string();
+        ui.set_databases(vec!["analytics".to_string(), "billing".to_string(), "shop".to_string()]);
+
+        ui.record_recent_database("billing");
+        ui.record_recent_database("shop");
+        assert_eq!(ui.databases, vec!["shop", "billing", "analytics"]);
+        assert!(ui.is_recent_database("billing") && !ui.is_recent_database("analytics"));
+
+        // Another server has its own list
+        ui.connection_input.port = "5433".to_string();
+        ui.set_databases(vec!["analytics".to_string(), "shop".to_string()]);
+        assert_eq!(ui.databases, vec!["analytics", "shop"]);
+    }
+
+    #[tokio::test]
+    async fn test_database_filter_narrows_list_as_you_type() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::DatabaseSelection;
+        ui.set_databases(vec!["shop".to_string(), "Analytics".to_string(), "shop_archive".to_string()]);
+        ui.selected_database = 2;
+
+        for key in [KeyCode::Char('/'), KeyCode::Char('S'), KeyCode::Char('h')] {
+            ui.handle_database_selection_input(key).await.unwrap();
+        }
+        assert_eq!(ui.databases, vec!["shop", "shop_archive"]);
+        assert_eq!(ui.selected_database, 0);
+
+        // 'q' is part of the filter while typing, not quit
+        ui.handle_database_selection_input(KeyCode::Char('q')).await.unwrap();
+        assert!(!ui.should_quit);
+        assert!(ui.databases.is_empty());
+
+        ui.handle_database_selection_input(KeyCode::Esc).await.unwrap();
+        assert!(!ui.editing_filter);
+        assert_eq!(ui.databases.len(), 3);
+    }
+
+    #[test]
+    fn test_qualified_table_name_uses_current_mysql_database() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_database = Some("shop".to_string());
+        assert_eq!(ui.qualified_table_name("Orders"), "\"Orders\"");
+        assert_eq!(ui.qualified_table_name("sales.order\"s"), "\"sales\".\"order\"\"s\"");
+
+        ui.selected_db_type = 1;
+        assert_eq!(ui.qualified_table_name("orders"), "`shop`.`orders`");
+        assert_eq!(ui.qualified_table_name("archive.ord`ers"), "`archive`.`ord``ers`");
+    }
+
+    #[test]
+    fn test_set_tables_keeps_filter_applied() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.table_filter = "order".to_string();
+        ui.set_tables(vec!["users".to_string(), "orders".to_string(), "order_items".to_string()]);
+        assert_eq!(ui.tables, vec!["orders", "order_items"]);
+        assert_eq!(ui.all_tables.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_key_sets_refresh_flags() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.needs_db_refresh = false;
+        ui.handle_database_selection_input(KeyCode::F(2)).await.unwrap();
+        assert!(ui.needs_db_refresh);
+
+        ui.needs_db_refresh = false;
+        ui.selected_database = 3;
+        ui.handle_database_selection_input(KeyCode::F(3)).await.unwrap();
+        assert!(ui.show_system_databases);
+        assert!(ui.needs_db_refresh);
+        assert_eq!(ui.selected_database, 0);
+
+        ui.needs_tables_refresh = false;
+        ui.expanded_table = Some(0);
+        ui.request_tables_refresh();
+        assert!(ui.needs_tables_refresh);
+        assert!(ui.expanded_table.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_tables_updates_live_state() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.set_tables(vec!["orders".to_string(), "users".to_string()]);
+        ui.selected_table = 1;
+        ui.tables_scroll = 1;
+        ui.needs_routines_refresh = false;
+
+        ui.refresh_tables().await;
+
+        assert!(ui.tables.is_empty());
+        assert_eq!(ui.selected_table, 0);
+        assert_eq!(ui.tables_scroll, 0);
+        assert!(ui.needs_routines_refresh);
+        assert!(ui.last_tables_update.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_command_palette_filters_and_runs_selected_command() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::TableView;
+        ui.open_command_palette();
+        assert!(matches!(ui.current_screen, ScreenState::CommandPalette));
+
+        for c in "hex".chars() {
+            ui.handle_command_palette_input(KeyCode::Char(c)).await;
+        }
+        ui.handle_command_palette_input(KeyCode::Enter).await;
+
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert_eq!(ui.binary_format, BinaryFormat::Hex);
+    }
+
+    #[tokio::test]
+    async fn test_demo_opens_scratch_database() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.selected_db_type = 3;
+        ui.handle_db_type_selection_input(KeyCode::Enter).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert_eq!(ui.current_database.as_deref(), Some("main"));
+
+        let db_ui = ui.database_ui().unwrap();
+        db_ui.execute_sql_query("CREATE TABLE notes (body TEXT)").await.unwrap();
+        let output = db_ui.execute_sql_query("INSERT INTO notes VALUES ('a'), ('b')").await.unwrap();
+        assert_eq!(output.kind, ResultKind::Affected(Some(2)));
+
+        // Picking "main" from the database list keeps the scratch tables
+        ui.connect_database(Some("main")).await.unwrap();
+        assert_eq!(db_ui.fetch_tables().await.unwrap(), vec!["notes"]);
+    }
+
+    #[tokio::test]
+    async fn test_quit_sets_should_quit() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+
+        ui.handle_db_type_selection_input(KeyCode::Char('q')).await;
+
+        assert!(ui.should_quit);
+    }
+
+    #[tokio::test]
+    async fn test_quit_with_unsaved_query_asks_first() {
+        let mut ui = editor_with("SELECT 1", 8, 0);
+        ui.current_screen = ScreenState::DatabaseSelection;
+
+        ui.handle_database_selection_input(KeyCode::Char('q')).await.unwrap();
+        assert!(!ui.should_quit);
+        assert!(matches!(ui.current_screen, ScreenState::QuitConfirmation));
+
+        assert!(!ui.handle_quit_confirmation_input(KeyCode::Char('n')).await);
+        assert!(matches!(ui.current_screen, ScreenState::DatabaseSelection));
+    }
+}
@@ -0,0 +1,134 @@
+/// Every keybinding, grouped by where it applies, as (keys, description).
+pub const KEYBINDINGS: [(&str, &[(&str, &str)]); 8] = [
+    (
+        "Everywhere",
+        &[
+            ("?", "Show this help (outside the SQL editor)"),
+            ("Ctrl+K", "Open the command palette"),
+            ("Esc / q", "Quit, asking first when the editor holds a query"),
+        ],
+    ),
+    (
+        "Connection details",
+        &[
+            ("↑/↓", "Move between fields"),
+            ("Enter", "Next field, or connect from the port"),
+            ("Ctrl+R", "Show or hide the password"),
+            ("Ctrl+T", "Test the connection without leaving the screen"),
+        ],
+    ),
+    (
+        "Database list",
+        &[
+            ("↑/↓", "Select a database"),
+            ("Enter", "Connect to the selected database"),
+            ("/", "Filter the list as you type"),
+            ("F2", "Refresh the database list"),
+            ("F3", "Show or hide system databases"),
+        ],
+    ),
+    (
+        "Table view",
+        &[
+            ("Tab", "Move focus between tables, editor and results"),
+            ("F1", "Return to database selection"),
+            ("F2 / Ctrl+R", "Refresh the table list"),
+            ("F3", "Toggle binary columns between base64 and hex"),
+            ("F4", "Toggle automatic LIMIT"),
+            ("F6", "Toggle thousands separators"),
+            ("F7", "Switch the sidebar between tables and routines"),
+            ("F8", "Re-run the current SELECT periodically"),
+            ("F10", "List active server sessions"),
+            ("F12", "Toggle debug information"),
+            ("Ctrl+L", "Clear the editor and the result"),
+        ],
+    ),
+    (
+        "Tables list",
+        &[
+            ("↑/↓", "Select a table"),
+            ("Enter", "Expand or collapse the table's columns"),
+            ("/", "Filter the list as you type"),
+            ("a", "Analyze the expanded table's columns"),
+            ("d", "Copy CREATE TABLE of the selected table"),
+            ("c", "Export the selected table to CSV"),
+            ("l", "Load a CSV file into a table (Postgres)"),
+        ],
+    ),
+    (
+        "SQL editor",
+        &[
+            ("F5 / Ctrl+E", "Execute the query"),
+            ("Shift+F5", "Execute only the statement under the cursor"),
+            ("F9", "Run the query under EXPLAIN ANALYZE"),
+            ("Ctrl+Space", "Complete a table or column name"),
+            ("Ctrl+F", "Format the query, one clause per line"),
+            ("Ctrl+N", "Switch between the query and your notes for this server"),
+            ("Ctrl+S", "Save the query under a name"),
+            ("Ctrl+P", "Pick a saved query for the editor (Del deletes it)"),
+            ("Ctrl+←/→", "Move a word at a time"),
+            ("Ctrl+Z / Ctrl+Y", "Undo / redo"),
+            ("Ctrl+O", "Edit the query in $EDITOR"),
+        ],
+    ),
+    (
+        "Results",
+        &[
+            ("↑/↓ ←/→", "Select a row and column"),
+            ("Page Up/Down, Home/End", "Scroll by page, jump to the start or end"),
+            ("Enter", "Show the full value of the cell"),
+            ("x", "Expand the selected row"),
+            ("v", "Show one record at a time as column: value lines"),
+            ("Ctrl+←/→", "Narrow / widen the selected column"),
+            ("< / >", "Lower / raise the width limit for all columns"),
+            ("[ / ]", "Lower / raise the value length used for sizing"),
+            ("- / +", "Keep fewer / more rows from the next query"),
+            ("n / p", "Next / previous result set of a CALL"),
+            ("Ctrl+C / Ctrl+A", "Copy the selected row / all rows"),
+            ("i", "Copy the selected row as INSERT"),
+            ("e", "Edit the selected cell"),
+            ("Del", "Delete the selected row"),
+            ("s", "Snapshot the result to diff later runs"),
+            ("b", "Cycle how booleans are shown"),
+        ],
+    ),
+    (
+        "Sessions and column stats",
+        &[
+            ("↑/↓", "Select a session"),
+            ("k / Del", "Terminate the selected session"),
+            ("r / F2", "Refresh"),
+            ("Esc / q", "Back to the table view"),
+        ],
+    ),
+];
+
+/// Lines the help screen takes: a title per group, its bindings and a blank line.
+pub fn line_count() -> usize {
+    KEYBINDINGS.iter().map(|(_, bindings)| bindings.len() + 2).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::palette::COMMANDS;
+
+    #[test]
+    fn test_help_lists_every_palette_shortcut() {
+        let keys: Vec<&str> = KEYBINDINGS
+            .iter()
+            .flat_map(|(_, bindings)| bindings.iter())
+            .flat_map(|(keys, _)| keys.split(" / "))
+            .collect();
+        for command in COMMANDS {
+            let shortcut = command.shortcut();
+            if !shortcut.is_empty() {
+                assert!(
+                    keys.iter().any(|key| key.split(", ").any(|k| k == shortcut)),
+                    "{} is missing from the help",
+                    shortcut
+                );
+            }
+        }
+    }
+}
@@ -1,16 +1,27 @@
+mod autocomplete;
+mod clipboard;
 mod components;
+mod diff;
+mod fuzzy;
 mod handlers;
+mod help;
+mod palette;
 mod screens;
+mod sql_format;
+mod theme;
 
 use std::io;
 
-pub use components::DatabaseClientUI;
-use crossterm::event::{KeyCode, KeyModifiers};
+pub use clipboard::ClipboardMethod;
+pub use components::{BoolDisplay, DatabaseClientUI};
+pub use theme::Theme;
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent};
 use dfox_core::models::schema::TableSchema;
 use ratatui::{prelude::CrosstermBackend, Terminal};
 
 pub trait UIHandler {
     async fn handle_message_popup_input(&mut self);
+    async fn handle_quit_confirmation_input(&mut self, key: KeyCode) -> bool;
     async fn handle_db_type_selection_input(&mut self, key: KeyCode);
     async fn handle_input_event(&mut self, key: KeyCode) -> io::Result<()>;
     async fn handle_database_selection_input(&mut self, key: KeyCode) -> io::Result<()>;
@@ -19,6 +30,20 @@ pub trait UIHandler {
         key: KeyCode,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     );
+    async fn handle_mouse_event(&mut self, event: MouseEvent);
+    async fn handle_cell_detail_input(&mut self, key: KeyCode);
+    async fn handle_command_palette_input(&mut self, key: KeyCode);
+    async fn handle_param_prompt_input(&mut self, key: KeyCode);
+    async fn handle_import_prompt_input(&mut self, key: KeyCode);
+    async fn handle_snippet_name_input(&mut self, key: KeyCode);
+    async fn handle_snippet_picker_input(&mut self, key: KeyCode);
+    async fn handle_cell_edit_input(&mut self, key: KeyCode);
+    async fn handle_delete_confirmation_input(&mut self, key: KeyCode);
+    async fn handle_explain_confirmation_input(&mut self, key: KeyCode);
+    async fn handle_sessions_input(&mut self, key: KeyCode);
+    async fn handle_table_stats_input(&mut self, key: KeyCode);
+    async fn handle_help_input(&mut self, key: KeyCode);
+    async fn handle_error_input(&mut self, key: KeyCode);
     async fn handle_sql_editor_input(
         &mut self,
         key: KeyCode,
@@ -32,6 +57,14 @@ pub trait UIRenderer {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()>;
+    async fn render_quit_confirmation(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
+    async fn render_error_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
     async fn render_db_type_selection_screen(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
@@ -48,6 +81,22 @@ pub trait UIRenderer {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()>;
+    async fn render_cell_detail(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
+    async fn render_sessions_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
+    async fn render_table_stats_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
+    async fn render_help_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
     async fn render_table_schema(
         &self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
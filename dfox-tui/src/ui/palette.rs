@@ -0,0 +1,167 @@
+use super::fuzzy;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    ExecuteQuery,
+    ExecuteStatement,
+    ExplainAnalyze,
+    AutoRefresh,
+    ClearWorkspace,
+    FormatQuery,
+    Notes,
+    SaveQuery,
+    SavedQueries,
+    RefreshTables,
+    ToggleBinaryFormat,
+    ToggleAutoLimit,
+    ToggleDigitGrouping,
+    CycleBoolDisplay,
+    ExpandSelectedRow,
+    SnapshotResult,
+    ShowRoutines,
+    CopyCreateTable,
+    ExportTable,
+    ImportCsv,
+    AnalyzeTable,
+    ShowSessions,
+    CopyRowAsInsert,
+    EditCell,
+    DeleteRow,
+    GoToDatabases,
+    SwitchConnection,
+    ShowHelp,
+    Quit,
+}
+
+pub const COMMANDS: [Command; 29] = [
+    Command::ExecuteQuery,
+    Command::ExecuteStatement,
+    Command::ExplainAnalyze,
+    Command::AutoRefresh,
+    Command::ClearWorkspace,
+    Command::FormatQuery,
+    Command::Notes,
+    Command::SaveQuery,
+    Command::SavedQueries,
+    Command::RefreshTables,
+    Command::ToggleBinaryFormat,
+    Command::ToggleAutoLimit,
+    Command::ToggleDigitGrouping,
+    Command::CycleBoolDisplay,
+    Command::ExpandSelectedRow,
+    Command::SnapshotResult,
+    Command::ShowRoutines,
+    Command::CopyCreateTable,
+    Command::ExportTable,
+    Command::ImportCsv,
+    Command::AnalyzeTable,
+    Command::ShowSessions,
+    Command::CopyRowAsInsert,
+    Command::EditCell,
+    Command::DeleteRow,
+    Command::GoToDatabases,
+    Command::SwitchConnection,
+    Command::ShowHelp,
+    Command::Quit,
+];
+
+impl Command {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::ExecuteQuery => "Execute query",
+            Command::ExecuteStatement => "Execute statement under cursor",
+            Command::ExplainAnalyze => "Explain analyze query (actual timings)",
+            Command::AutoRefresh => "Auto-refresh query",
+            Command::ClearWorkspace => "Clear editor and result",
+            Command::FormatQuery => "Format query (one clause per line)",
+            Command::Notes => "Open or close the notes for this server",
+            Command::SaveQuery => "Save the query under a name",
+            Command::SavedQueries => "Saved queries",
+            Command::RefreshTables => "Refresh tables",
+            Command::ToggleBinaryFormat => "Toggle binary format (base64/hex)",
+            Command::ToggleAutoLimit => "Toggle auto LIMIT",
+            Command::ToggleDigitGrouping => "Toggle thousands separators",
+            Command::CycleBoolDisplay => "Cycle boolean display (true/✓/Y)",
+            Command::ExpandSelectedRow => "Expand selected row",
+            Command::SnapshotResult => "Snapshot result to diff later runs",
+            Command::ShowRoutines => "Switch tables/routines list",
+            Command::CopyCreateTable => "Copy CREATE TABLE of selected table",
+            Command::ExportTable => "Export selected table to CSV",
+            Command::ImportCsv => "Import CSV file into a table",
+            Command::AnalyzeTable => "Analyze expanded table (column stats)",
+            Command::ShowSessions => "Show active server sessions",
+            Command::CopyRowAsInsert => "Copy selected row as INSERT",
+            Command::EditCell => "Edit selected cell",
+            Command::DeleteRow => "Delete selected row",
+            Command::GoToDatabases => "Go to databases",
+            Command::SwitchConnection => "Switch connection",
+            Command::ShowHelp => "Show keybindings (help)",
+            Command::Quit => "Quit",
+        }
+    }
+
+    pub fn shortcut(&self) -> &'static str {
+        match self {
+            Command::ExecuteQuery => "F5",
+            Command::ExecuteStatement => "Shift+F5",
+            Command::ExplainAnalyze => "F9",
+            Command::AutoRefresh => "F8",
+            Command::ClearWorkspace => "Ctrl+L",
+            Command::FormatQuery => "Ctrl+F",
+            Command::Notes => "Ctrl+N",
+            Command::SaveQuery => "Ctrl+S",
+            Command::SavedQueries => "Ctrl+P",
+            Command::RefreshTables => "F2",
+            Command::ToggleBinaryFormat => "F3",
+            Command::ToggleAutoLimit => "F4",
+            Command::ToggleDigitGrouping => "F6",
+            Command::CycleBoolDisplay => "b",
+            Command::ExpandSelectedRow => "x",
+            Command::SnapshotResult => "s",
+            Command::ShowRoutines => "F7",
+            Command::CopyCreateTable => "d",
+            Command::ExportTable => "c",
+            Command::ImportCsv => "l",
+            Command::AnalyzeTable => "a",
+            Command::ShowSessions => "F10",
+            Command::CopyRowAsInsert => "i",
+            Command::EditCell => "e",
+            Command::DeleteRow => "Del",
+            Command::GoToDatabases => "F1",
+            Command::SwitchConnection => "",
+            Command::ShowHelp => "?",
+            Command::Quit => "Esc",
+        }
+    }
+}
+
+/// Commands matching `query`, best match first. An empty query lists everything.
+pub fn filter(query: &str) -> Vec<Command> {
+    let mut matches: Vec<(usize, Command)> = COMMANDS
+        .iter()
+        .filter_map(|command| fuzzy::score(command.label(), query).map(|score| (score, *command)))
+        .collect();
+    matches.sort_by_key(|(score, _)| *score);
+    matches.into_iter().map(|(_, command)| command).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_query_lists_all_commands() {
+        assert_eq!(filter(""), COMMANDS.to_vec());
+    }
+
+    #[test]
+    fn test_fuzzy_filter_ranks_tighter_matches_first() {
+        let result = filter("tog");
+        assert_eq!(result.len(), 3);
+        assert!(result.iter().all(|c| c.label().starts_with("Toggle")));
+
+        assert_eq!(filter("exq").first(), Some(&Command::ExecuteQuery));
+        assert_eq!(filter("databases"), vec![Command::GoToDatabases]);
+        assert!(filter("zzz").is_empty());
+    }
+}
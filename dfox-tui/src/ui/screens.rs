@@ -1,22 +1,30 @@
-use dfox_core::models::schema::TableSchema;
+use dfox_core::{db::mysql::is_boolean_type, models::schema::{ColumnSchema, TableSchema}};
+use indexmap::IndexMap;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
-use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Row, Table, Wrap};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Gauge, List, ListItem, Paragraph, Row, Table, Wrap};
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, time::Duration};
+use std::{borrow::Cow, io, time::Duration};
 use tokio::time::timeout;
 
-use crate::db::{DatabaseUI, postgres::PostgresDatabaseUI, mysql::MySqlDatabaseUI};
-
-use super::components::{DatabaseType, FocusedWidget, MAX_VISIBLE_COLUMNS};
+use crate::db::ResultKind;
+use super::diff::RowChange;
+use super::components::{BoolDisplay, DatabaseType, FocusedWidget, ScreenState, TableExport, MAX_VISIBLE_COLUMNS};
+use super::{help, palette, theme::Theme};
 use super::{DatabaseClientUI, UIRenderer};
 
+/// Rows sampled when deciding whether a result column holds numbers.
+const NUMERIC_SAMPLE_ROWS: usize = 100;
+
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
 impl UIRenderer for DatabaseClientUI {
     async fn render_message_popup(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         terminal.draw(|f| {
             let size = f.area();
             let chunks = Layout::default()
@@ -48,11 +56,11 @@ impl UIRenderer for DatabaseClientUI {
 
             let help_message = vec![Line::from(vec![Span::styled(
                 "Press any key to return.",
-                Style::default().fg(Color::White),
+                Style::default().fg(theme.text),
             )])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
@@ -62,14 +70,126 @@ impl UIRenderer for DatabaseClientUI {
         Ok(())
     }
 
+    async fn render_quit_confirmation(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        terminal.draw(|f| {
+            let size = f.area();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(30),
+                        Constraint::Percentage(40),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(10),
+                    ]
+                    .as_ref(),
+                )
+                .split(size);
+
+            let popup_area = centered_rect(50, chunks[1]);
+
+            let block = Block::default()
+                .title("Quit")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.border_focused))
+                .title_alignment(Alignment::Center);
+
+            let message = Paragraph::new("You have unsaved query text. Quit anyway?")
+                .block(block)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+
+            f.render_widget(message, popup_area);
+
+            let help_message = vec![Line::from(vec![
+                Span::styled(
+                    "y",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to quit, "),
+                Span::styled(
+                    "any other key",
+                    Style::default()
+                        .fg(theme.success)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to go back"),
+            ])];
+
+            let help_paragraph = Paragraph::new(help_message)
+                .style(Style::default().fg(theme.text))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+
+            f.render_widget(help_paragraph, chunks[2]);
+        })?;
+
+        Ok(())
+    }
+
+    async fn render_error_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        let ScreenState::Error(message) = &self.current_screen else {
+            return Ok(());
+        };
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(30),
+                        Constraint::Percentage(40),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(10),
+                    ]
+                    .as_ref(),
+                )
+                .split(f.area());
+
+            let block = Block::default()
+                .title("Error")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.error))
+                .title_alignment(Alignment::Center);
+
+            let error_paragraph = Paragraph::new(message.as_str())
+                .block(block)
+                .style(Style::default().fg(theme.text))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+
+            f.render_widget(error_paragraph, centered_rect(60, chunks[1]));
+
+            let help_message = Line::from(vec![
+                Span::styled(
+                    "Enter/Esc",
+                    Style::default().fg(theme.key).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to go back"),
+            ]);
+            f.render_widget(Paragraph::new(help_message).alignment(Alignment::Center), chunks[2]);
+        })?;
+
+        Ok(())
+    }
+
     async fn render_db_type_selection_screen(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         let db_types = [
             DatabaseType::Postgres,
             DatabaseType::MySQL,
             DatabaseType::SQLite,
+            DatabaseType::Demo,
         ];
         let db_type_list: Vec<ListItem> = db_types
             .iter()
@@ -79,13 +199,11 @@ impl UIRenderer for DatabaseClientUI {
 
                 if i == self.selected_db_type {
                     ListItem::new(db).style(
-                        Style::default()
-                            .bg(Color::Yellow)
-                            .fg(Color::Black)
+                        theme.selected()
                             .add_modifier(Modifier::BOLD),
                     )
                 } else {
-                    ListItem::new(db).style(Style::default().fg(Color::White))
+                    ListItem::new(db).style(Style::default().fg(theme.text))
                 }
             })
             .collect();
@@ -113,9 +231,7 @@ impl UIRenderer for DatabaseClientUI {
                 .title_alignment(Alignment::Center);
 
             let db_type_widget = List::new(db_type_list).block(block).highlight_style(
-                Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
+                theme.selected()
                     .add_modifier(Modifier::BOLD),
             );
 
@@ -125,33 +241,33 @@ impl UIRenderer for DatabaseClientUI {
                 Span::styled(
                     "Up",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.key)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("/"),
                 Span::styled(
                     "Down",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.key)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to navigate, "),
                 Span::styled(
                     "Enter",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to select, "),
                 Span::styled(
                     "q",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
@@ -165,6 +281,7 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         terminal.draw(|f| {
             let size = f.area();
             let vertical_chunks = Layout::default()
@@ -189,10 +306,14 @@ impl UIRenderer for DatabaseClientUI {
 
             let mut content = [
                 format!("Username: {}", self.connection_input.username),
-                format!(
-                    "Password: {}",
-                    "*".repeat(self.connection_input.password.len())
-                ),
+                if self.connection_input.password_visible {
+                    format!("Password: {}", self.connection_input.password)
+                } else {
+                    format!(
+                        "Password: {}",
+                        "*".repeat(self.connection_input.password.chars().count())
+                    )
+                },
                 format!("Hostname: {}", self.connection_input.hostname),
                 format!("Port: {}", self.connection_input.port),
             ];
@@ -205,58 +326,64 @@ impl UIRenderer for DatabaseClientUI {
 
             let input_paragraph = Paragraph::new(content.join("\n"))
                 .block(block)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.text))
                 .alignment(Alignment::Left)
                 .wrap(Wrap { trim: true });
 
             f.render_widget(input_paragraph, horizontal_layout);
 
-            if let Some(error_message) = &self.connection_error_message {
-                let error_block = Block::default()
-                    .title("Error")
-                    .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::Red))
-                    .title_alignment(Alignment::Center);
-
-                let error_paragraph = Paragraph::new(error_message.clone())
-                    .block(error_block)
-                    .style(Style::default().fg(Color::White))
-                    .alignment(Alignment::Center)
-                    .wrap(Wrap { trim: true });
-
-                let error_area = centered_rect(50, vertical_chunks[1]);
-                f.render_widget(Clear, error_area);
-                f.render_widget(error_paragraph, error_area);
-            } else {
-                let help_message = vec![Line::from(vec![
-                    Span::styled(
-                        "Enter",
-                        Style::default()
-                            .fg(Color::Green)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" to confirm input, "),
-                    Span::styled(
-                        "Up/Down",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" to navigate fields, "),
-                    Span::styled(
-                        "Esc",
-                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                    ),
-                    Span::raw(" to go back"),
-                ])];
+            let mut help_message = Vec::new();
+            match &self.connection_input.test_result {
+                Some(Ok(message)) => help_message.push(Line::styled(message.clone(), Style::default().fg(theme.success))),
+                Some(Err(message)) => help_message.push(Line::styled(message.clone(), Style::default().fg(theme.error))),
+                None => {}
+            }
+            help_message.push(Line::from(vec![
+                Span::styled(
+                    "Enter",
+                    Style::default()
+                        .fg(theme.success)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to confirm input, "),
+                Span::styled(
+                    "Up/Down",
+                    Style::default()
+                        .fg(theme.key)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to navigate fields, "),
+                Span::styled(
+                    "Ctrl+R",
+                    Style::default()
+                        .fg(theme.key)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(if self.connection_input.password_visible {
+                    " to hide the password, "
+                } else {
+                    " to show the password, "
+                }),
+                Span::styled(
+                    "Ctrl+T",
+                    Style::default()
+                        .fg(theme.key)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to test the connection, "),
+                Span::styled(
+                    "Esc",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to go back"),
+            ]));
 
-                let help_paragraph = Paragraph::new(help_message)
-                    .style(Style::default().fg(Color::White))
-                    .alignment(Alignment::Center)
-                    .wrap(Wrap { trim: true });
+            let help_paragraph = Paragraph::new(help_message)
+                .style(Style::default().fg(theme.text))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
 
-                f.render_widget(help_paragraph, vertical_chunks[2]);
-            }
+            f.render_widget(help_paragraph, vertical_chunks[2]);
         })?;
 
         Ok(())
@@ -266,42 +393,12 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         if self.needs_db_refresh {
-            match self.selected_db_type {
-                0 => {
-                    let db_ui = PostgresDatabaseUI::new(self.clone());
-                    match timeout(Duration::from_secs(5), db_ui.fetch_databases()).await {
-                        Ok(Ok(databases)) => {
-                            self.databases = databases;
-                            self.last_db_update = Some(std::time::Instant::now());
-                            self.needs_db_refresh = false;
-                        }
-                        Ok(Err(err)) => {
-                            eprintln!("Error fetching databases: {}", err);
-                        }
-                        Err(_) => {
-                            eprintln!("Timeout while fetching databases");
-                        }
-                    }
-                }
-                1 => {
-                    let db_ui = MySqlDatabaseUI::new(self.clone());
-                    match timeout(Duration::from_secs(5), db_ui.fetch_databases()).await {
-                        Ok(Ok(databases)) => {
-                            self.databases = databases;
-                            self.last_db_update = Some(std::time::Instant::now());
-                            self.needs_db_refresh = false;
-                        }
-                        Ok(Err(e)) => {
-                            self.databases = vec!["Error fetching databases: {}".to_string(), e.to_string()];
-                        }
-                        Err(_) => {
-                            self.databases = vec!["Timeout while fetching databases".to_string()];
-                        }
-                    }
-                }
-                _ => (),
-            }
+            // Fetch once per request: the UI redraws on every tick, so leaving the flag set
+            // after a failure would retry the fetch several times a second.
+            self.needs_db_refresh = false;
+            self.refresh_databases().await;
         }
 
         let visible_databases: Vec<ListItem> = self
@@ -309,21 +406,23 @@ impl UIRenderer for DatabaseClientUI {
             .iter()
             .enumerate()
             .skip(self.databases_scroll)
-            .take(20) 
             .map(|(i, db)| {
+                let mut line = Line::from(db.clone());
+                if self.is_recent_database(db) {
+                    line.push_span(Span::styled(" (recent)", Style::default().fg(theme.muted)));
+                }
                 if i == self.selected_database {
-                    ListItem::new(db.clone()).style(
-                        Style::default()
-                            .bg(Color::Yellow)
-                            .fg(Color::Black)
+                    ListItem::new(line).style(
+                        theme.selected()
                             .add_modifier(Modifier::BOLD),
                     )
                 } else {
-                    ListItem::new(db.clone()).style(Style::default().fg(Color::White))
+                    ListItem::new(line).style(Style::default().fg(theme.text))
                 }
             })
             .collect();
 
+        let mut databases_area = self.databases_area;
         terminal.draw(|f| {
             let size = f.area();
 
@@ -340,57 +439,89 @@ impl UIRenderer for DatabaseClientUI {
                 .split(size);
 
             let horizontal_layout = centered_rect(50, chunks[1]);
+            databases_area = horizontal_layout;
 
             let block = Block::default()
-                .title(format!("Select Database ({}/{})", self.selected_database + 1, self.databases.len()))
+                .title(format!(
+                    "Select Database ({}/{}){}",
+                    self.selected_database + 1,
+                    self.databases.len(),
+                    filter_title(&self.database_filter, self.editing_filter)
+                ))
                 .borders(Borders::ALL)
                 .title_alignment(Alignment::Center);
 
-            let db_list_widget = List::new(visible_databases).block(block).highlight_style(
-                Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            );
-
-            f.render_widget(db_list_widget, horizontal_layout);
+            if let Some(error) = &self.database_list_error {
+                let error_widget = Paragraph::new(format!("{}\n\nPress F2 to try again", error))
+                    .block(block)
+                    .style(Style::default().fg(theme.error))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true });
+                f.render_widget(error_widget, horizontal_layout);
+            } else {
+                let db_list_widget = List::new(visible_databases).block(block).highlight_style(
+                    theme.selected()
+                        .add_modifier(Modifier::BOLD),
+                );
+                f.render_widget(db_list_widget, horizontal_layout);
+            }
 
             let help_message = vec![Line::from(vec![
                 Span::styled(
                     "Up",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.key)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("/"),
                 Span::styled(
                     "Down",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.key)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to navigate, "),
                 Span::styled(
                     "Enter",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to select, "),
+                Span::styled(
+                    "/",
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to filter, "),
+                Span::styled(
+                    "F2",
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to refresh, "),
+                Span::styled(
+                    "F3",
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(if self.show_system_databases {
+                    " to hide system databases, "
+                } else {
+                    " to show system databases, "
+                }),
                 Span::styled(
                     "q",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
             f.render_widget(help_paragraph, chunks[2]);
         })?;
+        self.databases_area = databases_area;
 
         Ok(())
     }
@@ -399,42 +530,47 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         if self.needs_tables_refresh {
-            let tables = match self.selected_db_type {
-                0 => {
-                    let db_ui = PostgresDatabaseUI::new(self.clone());
-                    timeout(Duration::from_secs(5), db_ui.fetch_tables()).await
-                }
-                1 => {
-                    let db_ui = MySqlDatabaseUI::new(self.clone());
-                    timeout(Duration::from_secs(5), db_ui.fetch_tables()).await
-                }
-                _ => Ok(Ok(Vec::new())),
+            self.needs_tables_refresh = false;
+            self.refresh_tables().await;
+        }
+
+        if self.show_routines && self.needs_routines_refresh {
+            self.needs_routines_refresh = false;
+            let routines = match self.database_ui() {
+                Some(db_ui) => timeout(Duration::from_secs(5), db_ui.fetch_routines()).await,
+                None => Ok(Ok(Vec::new())),
             };
 
-            match tables {
-                Ok(Ok(tables)) => {
-                    self.tables = tables;
-                    self.last_tables_update = Some(std::time::Instant::now());
-                    self.needs_tables_refresh = false;
-                }
+            match routines {
+                Ok(Ok(routines)) => self.routines = routines,
                 Ok(Err(e)) => {
-                    eprintln!("Error fetching tables: {}", e);
-                    self.tables = Vec::new();
+                    log::error!("Error fetching routines: {}", e);
+                    self.routines = Vec::new();
                 }
                 Err(_) => {
-                    eprintln!("Timeout while fetching tables");
-                    self.tables = Vec::new();
+                    log::error!("Timeout while fetching routines");
+                    self.routines = Vec::new();
                 }
             }
+            self.selected_routine = self.selected_routine.min(self.routines.len().saturating_sub(1));
         }
 
+        let mut pane_areas = None;
         terminal.draw(|f| {
             let size = f.area();
 
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(95), Constraint::Percentage(5)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                        Constraint::Length(2),
+                    ]
+                    .as_ref(),
+                )
                 .split(size);
 
             let main_chunks = Layout::default()
@@ -446,17 +582,18 @@ impl UIRenderer for DatabaseClientUI {
                 .direction(Direction::Vertical)
                 .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
                 .split(main_chunks[1]);
+            pane_areas = Some((main_chunks[0], right_chunks[0], right_chunks[1]));
 
-            let visible_tables: Vec<ListItem> = self.tables
+            let mut visible_tables: Vec<ListItem> = self.tables
                 .iter()
                 .enumerate()
                 .skip(self.tables_scroll)
                 .take(main_chunks[0].height as usize - 2) 
-                .map(|(i, table)| {
+                .flat_map(|(i, table)| {
                     let style = if i == self.selected_table {
-                        Style::default().bg(Color::Yellow).fg(Color::Black)
+                        theme.selected()
                     } else {
-                        Style::default().fg(Color::White)
+                        Style::default().fg(theme.text)
                     };
 
                     let mut items = vec![ListItem::new(table.to_string()).style(style)];
@@ -465,16 +602,10 @@ impl UIRenderer for DatabaseClientUI {
                         if expanded_idx == i {
                             if let Some(schema) = self.table_schemas.get(table) {
                                 for column in &schema.columns {
-                                    let column_info = format!(
-                                        "  ├─ {}: {} (Nullable: {}, Default: {:?})",
-                                        column.name,
-                                        column.data_type,
-                                        column.is_nullable,
-                                        column.default
-                                    );
+                                    let column_info = format!("  ├─ {}", column_summary(column));
                                     items.push(
                                         ListItem::new(column_info)
-                                            .style(Style::default().fg(Color::Gray)),
+                                            .style(Style::default().fg(theme.secondary)),
                                     );
                                 }
                             }
@@ -483,63 +614,149 @@ impl UIRenderer for DatabaseClientUI {
 
                     items
                 })
-                .flatten()
                 .collect();
+            if let Some(error) = self.table_list_error.as_ref().filter(|_| self.tables.is_empty()) {
+                let width = (main_chunks[0].width as usize).saturating_sub(2);
+                visible_tables = wrap_to_width(&format!("{} (F2 to retry)", error), width)
+                    .into_iter()
+                    .map(|line| ListItem::new(line).style(Style::default().fg(theme.error)))
+                    .collect();
+            }
 
             let tables_block = Block::default()
                 .borders(Borders::ALL)
-                .title(format!("Tables ({}/{})", self.selected_table + 1, self.tables.len()))
+                .title(format!(
+                    "Tables ({}/{}){}",
+                    self.selected_table + 1,
+                    self.tables.len(),
+                    filter_title(
+                        &self.table_filter,
+                        self.editing_filter && self.current_focus == FocusedWidget::TablesList
+                    )
+                ))
                 .border_style(if let FocusedWidget::TablesList = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                    theme.border_style(true)
                 } else {
-                    Style::default().fg(Color::White)
+                    theme.border_style(false)
                 });
 
             let tables_widget = List::new(visible_tables)
                 .block(tables_block)
-                .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
+                .highlight_style(theme.selected());
+
+            let tables_widget = if self.show_routines {
+                let visible = (main_chunks[0].height as usize).saturating_sub(2);
+                let offset = self.selected_routine.saturating_sub(visible.saturating_sub(1));
+                let items: Vec<ListItem> = self
+                    .routines
+                    .iter()
+                    .enumerate()
+                    .skip(offset)
+                    .take(visible)
+                    .map(|(i, routine)| {
+                        let style = if i == self.selected_routine {
+                            theme.selected()
+                        } else {
+                            Style::default().fg(theme.text)
+                        };
+                        let signature = match &routine.return_type {
+                            Some(return_type) => format!(" → {}", return_type),
+                            None => String::new(),
+                        };
+                        ListItem::new(Line::from(vec![
+                            Span::raw(routine.name.clone()),
+                            Span::styled(
+                                format!(" ({}){}", routine.routine_type.to_lowercase(), signature),
+                                Style::default().fg(theme.muted),
+                            ),
+                        ]))
+                        .style(style)
+                    })
+                    .collect();
+                let title = if self.routines.is_empty() {
+                    "Routines (none) - F7 tables".to_string()
+                } else {
+                    format!("Routines ({}/{}) - F7 tables", self.selected_routine + 1, self.routines.len())
+                };
+                List::new(items).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(title)
+                        .border_style(if let FocusedWidget::TablesList = self.current_focus {
+                            theme.border_style(true)
+                        } else {
+                            theme.border_style(false)
+                        }),
+                )
+            } else {
+                tables_widget
+            };
 
             let sql_query_block = Block::default()
                 .borders(Borders::ALL)
-                .title("SQL Query")
+                .title(if self.stashed_query.is_some() {
+                    "Notes - F5 loads the statement under the cursor, Ctrl+N back to the query"
+                } else {
+                    "SQL Query"
+                })
                 .border_style(if let FocusedWidget::SqlEditor = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                    theme.border_style(true)
                 } else {
-                    Style::default().fg(Color::White)
+                    theme.border_style(false)
                 });
 
-            let sql_query_widget = Paragraph::new(self.sql_editor_content.clone())
-                .block(sql_query_block)
-                .style(Style::default().fg(Color::White))
-                .wrap(Wrap { trim: false })
+            // Lines are not wrapped so that each one lines up with its number in the gutter
+            let gutter_width = self.editor_gutter_width();
+            let editor_columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Length(gutter_width), Constraint::Min(0)])
+                .split(sql_query_block.inner(right_chunks[0]));
+            let line_numbers: Vec<Line> = (1..=self.sql_editor_content.split('\n').count())
+                .map(|number| {
+                    Line::styled(
+                        format!("{:>width$} ", number, width = gutter_width as usize - 1),
+                        Style::default().fg(theme.muted),
+                    )
+                })
+                .collect();
+            let gutter_widget = Paragraph::new(line_numbers).scroll((self.sql_editor_scroll as u16, 0));
+            let sql_query_widget = Paragraph::new(highlight_error_token(
+                &self.sql_editor_content,
+                self.sql_error_position,
+                theme,
+            ))
+                .style(Style::default().fg(theme.text))
                 .scroll((self.sql_editor_scroll as u16, 0));
-
+            f.render_widget(sql_query_block, right_chunks[0]);
+            f.render_widget(gutter_widget, editor_columns[0]);
+            f.render_widget(sql_query_widget, editor_columns[1]);
+
+            let result_title = match self.result_kind {
+                Some(ResultKind::Returned) => format!("Query Result ({} rows returned)", self.sql_query_result.len()),
+                Some(ResultKind::Affected(Some(rows))) => format!("Query Result ({} rows affected)", rows),
+                Some(ResultKind::Affected(None)) => "Query Result (no rows returned)".to_string(),
+                None => "Query Result".to_string(),
+            };
             let sql_result_block = Block::default()
                 .borders(Borders::ALL)
-                .title("Query Result")
+                .title(result_title.clone())
                 .border_style(if let FocusedWidget::_QueryResult = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                    theme.border_style(true)
                 } else {
-                    Style::default().fg(Color::White)
+                    theme.border_style(false)
                 });
 
             if let Some(error) = &self.sql_query_error {
                 let error_widget = Paragraph::new(format!("Error: {}", error))
                     .block(sql_result_block)
-                    .style(Style::default().fg(Color::Red));
+                    .style(Style::default().fg(theme.error));
 
                 f.render_widget(tables_widget, main_chunks[0]);
-                f.render_widget(sql_query_widget, right_chunks[0]);
                 f.render_widget(error_widget, right_chunks[1]);
             } else if !self.sql_query_result.is_empty() {
                 // Get headers from IndexMap which preserves insertion order
-                let headers = if let Some(first_result) = self.sql_query_result.first() {
-                    let keys: Vec<String> = first_result.keys().cloned().collect();
-                    // Debug: check headers order in UI
-                    if keys.len() > 5 {
-                        eprintln!("UI Headers order (first 5): {:?}", &keys[0..5]);
-                    }
-                    keys
+                let headers: Vec<String> = if let Some(first_result) = self.sql_query_result.first() {
+                    first_result.keys().cloned().collect()
                 } else {
                     Vec::new()
                 };
@@ -564,37 +781,63 @@ impl UIRenderer for DatabaseClientUI {
                 // Calculate column widths for visible headers only with minimum widths
                 let mut column_widths = vec![6u16]; // Row number column (wider for better readability)
                 for header in &visible_headers {
-                    let header_width = header.len() as u16;
-                    let max_content_width = self
-                        .sql_query_result
-                        .iter()
-                        .take(std::cmp::min(50, self.sql_query_result.len())) // Sample fewer rows for performance
-                        .map(|row| {
-                            row.get(header)
-                                .map_or(4, |v| std::cmp::min(v.len(), 50)) as u16 // Limit sample width to 50 chars
-                        })
-                        .max()
-                        .unwrap_or(header_width) as u16;
-                    
-                    // Use reasonable width limits to prevent extreme stretching
-                    let optimal_width = std::cmp::max(header_width + 2, max_content_width + 2);
-                    let final_width = std::cmp::min(optimal_width, 40); // Max 40 chars per column
-                    column_widths.push(std::cmp::max(final_width, 8)); // Min 8 chars per column
+                    column_widths.push(self.result_column_width(header));
                 }
                 
-                let visible_rows = (right_chunks[1].height as usize).saturating_sub(3); // Account for borders and headers
+                // Numeric columns are right-aligned and, when enabled, get display formatting
+                let format_numbers = self.group_digits || self.float_precision.is_some();
+                // Columns the source table declares as TINYINT(1)/BOOL hold 0 and 1
+                let result_schema = self.result_table.as_ref().and_then(|table| self.table_schemas.get(table));
+                let boolean_columns: Vec<bool> = visible_headers
+                    .iter()
+                    .map(|header| {
+                        let declared = result_schema.is_some_and(|schema| {
+                            schema
+                                .columns
+                                .iter()
+                                .any(|column| &column.name == header && is_boolean_type(&column.data_type))
+                        });
+                        (declared || self.bool_display != BoolDisplay::Text)
+                            && is_boolean_column(
+                                self.sql_query_result
+                                    .iter()
+                                    .take(NUMERIC_SAMPLE_ROWS)
+                                    .filter_map(|row| row.get(header).map(String::as_str)),
+                                declared,
+                            )
+                    })
+                    .collect();
+
+                let numeric_columns: Vec<bool> = visible_headers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, header)| {
+                        !boolean_columns[i]
+                            && is_numeric_column(
+                                self.sql_query_result
+                                    .iter()
+                                    .take(NUMERIC_SAMPLE_ROWS)
+                                    .filter_map(|row| row.get(header).map(String::as_str)),
+                            )
+                    })
+                    .collect();
+
+                let visible_rows = (right_chunks[1].height as usize).saturating_sub(4); // Account for borders, header and its margin
                 let total_rows = self.sql_query_result.len();
                 
                 // Ensure scroll position is within bounds
                 let safe_scroll = std::cmp::min(self.sql_result_scroll, total_rows.saturating_sub(1));
                 
                 // Limit the number of rows to prevent performance issues
-                let max_displayable_rows = std::cmp::min(visible_rows, 1000); // Max 1000 rows at once
+                let max_displayable_rows = std::cmp::min(visible_rows, self.max_display_rows);
                 let end_index = std::cmp::min(
                     safe_scroll + max_displayable_rows,
                     total_rows
                 );
                 
+                // Rows added or changed since the snapshot, when one is kept
+                let diff = self.result_snapshot.as_ref().map(|snapshot| snapshot.diff(&self.sql_query_result));
+
                 let rows: Vec<Row> = if total_rows > 0 && end_index > safe_scroll {
                     self.sql_query_result
                         .iter()
@@ -603,53 +846,72 @@ impl UIRenderer for DatabaseClientUI {
                         .enumerate()
                         .map(|(idx, result)| {
                             let row_num = safe_scroll + idx + 1;
-                            let mut cells = vec![format!("{}", row_num)];
+                            let mut cells = vec![Cell::from(row_num.to_string())];
                             
+                            let is_selected_row = safe_scroll + idx == self.selected_result_row
+                                && matches!(self.current_focus, FocusedWidget::_QueryResult);
+                            let change = diff.as_ref().and_then(|diff| diff.rows.get(safe_scroll + idx));
+
                             // Apply horizontal scroll to data columns
-                            for header in &visible_headers {
+                            let is_expanded = is_selected_row
+                                && self.expanded_result_row == Some(self.selected_result_row);
+                            let mut row_height = 1;
+                            for (column_idx, header) in visible_headers.iter().enumerate() {
                                 let value = result
                                     .get(header)
-                                    .map_or("NULL".to_string(), |v| {
-                                        // More aggressive data cleaning but preserve full length
-                                        let cleaned = v
-                                            .chars()
-                                            .filter(|c| {
-                                                // Keep all printable characters, including Unicode (Cyrillic, emojis, etc.)
-                                                !c.is_control() || *c == '\t' || *c == '\n'
-                                            })
-                                            .collect::<String>()
-                                            .trim()
-                                            .to_string();
-                                        
-                                        // Smart truncation for display - keep reasonable cell sizes
-                                        if cleaned.len() > 100 {
-                                            let mut chars: Vec<char> = cleaned.chars().collect();
-                                            if chars.len() >= 97 {
-                                                chars.truncate(97);
-                                                let truncated: String = chars.into_iter().collect();
-                                                format!("{}...", truncated)
-                                            } else {
-                                                cleaned
-                                            }
-                                        } else if cleaned.is_empty() {
-                                            "NULL".to_string()
-                                        } else {
-                                            cleaned
-                                        }
-                                    });
-                                cells.push(value);
+                                    .map_or_else(|| "NULL".to_string(), |v| visible_control_chars(v).into_owned());
+                                let value = if numeric_columns[column_idx] && value != "NULL" && format_numbers {
+                                    format_number(&value, self.group_digits, self.float_precision)
+                                } else if boolean_columns[column_idx] && value != "NULL" {
+                                    self.bool_display.render(value == "true" || value == "1").to_string()
+                                } else {
+                                    value
+                                };
+                                let width = column_widths[column_idx + 1] as usize;
+
+                                let line = if is_expanded {
+                                    let lines = wrap_to_width(&value, width);
+                                    row_height = row_height.max(lines.len());
+                                    Text::from(lines.into_iter().map(Line::from).collect::<Vec<_>>())
+                                } else {
+                                    match truncate_to_width(&value, width) {
+                                        // Truncated cells end in a coloured marker
+                                        Some(shown) => Text::from(Line::from(vec![
+                                            Span::raw(shown),
+                                            Span::styled("…", Style::default().fg(theme.marker)),
+                                        ])),
+                                        None => Text::from(value),
+                                    }
+                                };
+                                let cell = if numeric_columns[column_idx] && !is_expanded {
+                                    Cell::from(line.alignment(Alignment::Right))
+                                } else {
+                                    Cell::from(line)
+                                };
+                                if is_selected_row && self.sql_result_horizontal_scroll + column_idx == self.selected_result_column {
+                                    cells.push(cell.style(Style::default().add_modifier(Modifier::REVERSED)));
+                                } else if matches!(change, Some(RowChange::Changed(columns)) if columns.contains(header)) {
+                                    cells.push(cell.style(Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)));
+                                } else {
+                                    cells.push(cell);
+                                }
                             }
                             
-                            let row = Row::new(cells);
+                            let row = Row::new(cells).height(row_height.min(visible_rows.max(1)) as u16);
                             
                             // Highlight selected row (with bounds checking)
                             if total_rows > 0 && 
                                self.selected_result_row < total_rows &&
                                safe_scroll + idx == self.selected_result_row && 
                                matches!(self.current_focus, FocusedWidget::_QueryResult) {
-                                row.style(Style::default().bg(Color::Yellow).fg(Color::Black))
+                                row.style(theme.selected())
                             } else {
-                                row.style(Style::default().fg(Color::White))
+                                match change {
+                                    Some(RowChange::Added) => row.style(theme.bold_or(Style::default().fg(theme.success))),
+                                    Some(RowChange::Changed(_)) if theme.has_colors() => row.style(Style::default().fg(theme.changed)),
+                                    Some(RowChange::Changed(_)) => row.style(Style::default().add_modifier(Modifier::ITALIC)),
+                                    _ => row.style(Style::default().fg(theme.text)),
+                                }
                             }
                         })
                         .collect()
@@ -660,8 +922,14 @@ impl UIRenderer for DatabaseClientUI {
                 // Create constraints based on calculated widths - no compression needed with horizontal scroll
                 let constraints: Vec<Constraint> = column_widths.into_iter().map(Constraint::Length).collect();
 
-                let mut header_cells = vec!["#".to_string()];
-                header_cells.extend(visible_headers.clone());
+                let mut header_cells = vec![Cell::from("#")];
+                header_cells.extend(visible_headers.iter().zip(&numeric_columns).map(|(header, numeric)| {
+                    if *numeric {
+                        Cell::from(Line::from(header.clone()).alignment(Alignment::Right))
+                    } else {
+                        Cell::from(header.clone())
+                    }
+                }));
 
                 // Create title with scroll indicators
                 let title = if total_rows > visible_rows || self.sql_result_horizontal_scroll > 0 || total_columns > max_visible_columns {
@@ -692,13 +960,40 @@ impl UIRenderer for DatabaseClientUI {
                         h_scroll_info
                     )
                 } else {
-                    format!("Query Result ({} rows)", total_rows)
+                    result_title
+                };
+                let title = match (&diff, &self.result_snapshot) {
+                    (Some(diff), Some(snapshot)) => format!(
+                        "{} - diff by {}: +{} ~{} -{}",
+                        title,
+                        snapshot.key_column,
+                        diff.added(),
+                        diff.changed(),
+                        diff.removed.len()
+                    ),
+                    _ => title,
+                };
+                let title = if self.result_truncated {
+                    let total = match (self.result_total_rows, &self.total_row_count) {
+                        (Some(total), _) => format_number(&total.to_string(), true, None),
+                        (None, Some(_)) => "… (counting)".to_string(),
+                        (None, None) => "more".to_string(),
+                    };
+                    format!("{} - showing {} of {}", title, format_number(&total_rows.to_string(), true, None), total)
+                } else {
+                    title
+                };
+                let title = match &self.sql_query_success_message {
+                    Some(message) if !message.is_empty() => {
+                        format!("{} - {}", title, message.replace('\n', " | "))
+                    }
+                    _ => title,
                 };
 
                 let sql_result_widget = Table::new(rows, constraints.clone())
                     .header(
                         Row::new(header_cells)
-                            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                            .style(Style::default().fg(theme.header).add_modifier(Modifier::BOLD))
                             .bottom_margin(1)
                     )
                     .block(
@@ -706,15 +1001,15 @@ impl UIRenderer for DatabaseClientUI {
                             .borders(Borders::ALL)
                             .border_type(ratatui::widgets::BorderType::Double)
                             .border_style(if let FocusedWidget::_QueryResult = self.current_focus {
-                                Style::default().fg(Color::Yellow)
+                                theme.border_style(true)
                             } else {
-                                Style::default().fg(Color::White)
+                                theme.border_style(false)
                             })
                             .title(title)
                     )
                     .column_spacing(1)
                     .widths(&constraints)
-                    .style(Style::default().fg(Color::White));
+                    .style(Style::default().fg(theme.text));
 
                 f.render_widget(tables_widget, main_chunks[0]);
                 
@@ -742,9 +1037,9 @@ impl UIRenderer for DatabaseClientUI {
                         // Draw scrollbar track
                         for y in 0..scrollbar_height {
                             let style = if y >= thumb_position && y < thumb_position + thumb_size {
-                                Style::default().bg(Color::Black).fg(Color::White) // Thumb
+                                Style::default().bg(theme.background).fg(theme.text) // Thumb
                             } else {
-                                Style::default().bg(Color::White).fg(Color::Black) // Track
+                                theme.reversed_or(Style::default().bg(theme.text).fg(theme.background)) // Track
                             };
                             
                             f.render_widget(
@@ -760,11 +1055,29 @@ impl UIRenderer for DatabaseClientUI {
                     }
                 }
                 
-                f.render_widget(sql_query_widget, right_chunks[0]);
-                f.render_widget(sql_result_widget, right_chunks[1]);
+                if self.vertical_results {
+                    let row_index = self.selected_result_row.min(total_rows - 1);
+                    let (lines, selected_line) =
+                        record_lines(&self.sql_query_result[row_index], self.selected_result_column, theme);
+                    // Keep the selected column in view when the record is taller than the pane
+                    let visible_lines = (right_chunks[1].height as usize).saturating_sub(2);
+                    let scroll = (selected_line + 1).saturating_sub(visible_lines);
+                    let record_widget = Paragraph::new(lines)
+                        .scroll((scroll as u16, 0))
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .border_type(ratatui::widgets::BorderType::Double)
+                                .border_style(theme.border_style(self.current_focus == FocusedWidget::_QueryResult))
+                                .title(format!("Record {}/{} - v for the grid", row_index + 1, total_rows)),
+                        );
+                    f.render_widget(record_widget, right_chunks[1]);
+                } else {
+                    f.render_widget(sql_result_widget, right_chunks[1]);
+                }
                 
                 // Add scrollbar for query results
-                if total_rows > visible_rows {
+                if total_rows > visible_rows && !self.vertical_results {
                     let scrollbar_area = Rect {
                         x: right_chunks[1].x + right_chunks[1].width - 1,
                         y: right_chunks[1].y + 1,
@@ -785,9 +1098,9 @@ impl UIRenderer for DatabaseClientUI {
                         // Draw scrollbar track
                         for y in 0..scrollbar_height {
                             let style = if y >= thumb_position && y < thumb_position + thumb_size {
-                                Style::default().bg(Color::Black).fg(Color::White) // Thumb
+                                Style::default().bg(theme.background).fg(theme.text) // Thumb
                             } else {
-                                Style::default().bg(Color::White).fg(Color::Black) // Track
+                                theme.reversed_or(Style::default().bg(theme.text).fg(theme.background)) // Track
                             };
                             
                             f.render_widget(
@@ -803,10 +1116,15 @@ impl UIRenderer for DatabaseClientUI {
                     }
                 }
             } else {
-                let result_message = self
-                    .sql_query_success_message
-                    .clone()
-                    .unwrap_or_else(|| "No results".to_string());
+                let result_message = if let Some(running) = &self.running_query {
+                    let elapsed = running.started.elapsed();
+                    let frame = SPINNER_FRAMES[(elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len()];
+                    format!("{} Running… {:.1}s", frame, elapsed.as_secs_f64())
+                } else {
+                    self.sql_query_success_message
+                        .clone()
+                        .unwrap_or_else(|| "No results".to_string())
+                };
                 let result_widget = Paragraph::new(result_message).block(sql_result_block);
 
                 f.render_widget(tables_widget, main_chunks[0]);
@@ -835,9 +1153,9 @@ impl UIRenderer for DatabaseClientUI {
                         // Draw scrollbar track
                         for y in 0..scrollbar_height {
                             let style = if y >= thumb_position && y < thumb_position + thumb_size {
-                                Style::default().bg(Color::Black).fg(Color::White) // Thumb
+                                Style::default().bg(theme.background).fg(theme.text) // Thumb
                             } else {
-                                Style::default().bg(Color::White).fg(Color::Black) // Track
+                                theme.reversed_or(Style::default().bg(theme.text).fg(theme.background)) // Track
                             };
                             
                             f.render_widget(
@@ -853,7 +1171,6 @@ impl UIRenderer for DatabaseClientUI {
                     }
                 }
                 
-                f.render_widget(sql_query_widget, right_chunks[0]);
                 f.render_widget(result_widget, right_chunks[1]);
             }
 
@@ -861,7 +1178,7 @@ impl UIRenderer for DatabaseClientUI {
                 let cursor_x = self.sql_editor_cursor_x as u16;
                 let cursor_y = self.sql_editor_cursor_y as u16;
 
-                let adjusted_cursor_x = right_chunks[0].x + cursor_x + 1;
+                let adjusted_cursor_x = right_chunks[0].x + gutter_width + cursor_x + 1;
                 let adjusted_cursor_y = right_chunks[0].y + cursor_y + 1 - (self.sql_editor_scroll as u16);
 
                 if adjusted_cursor_y >= right_chunks[0].y && 
@@ -871,71 +1188,758 @@ impl UIRenderer for DatabaseClientUI {
                 }
             }
 
+            if !self.autocomplete_suggestions.is_empty() {
+                let width = self
+                    .autocomplete_suggestions
+                    .iter()
+                    .map(|s| s.chars().count() as u16)
+                    .max()
+                    .unwrap_or(0)
+                    .max(16)
+                    + 2;
+                let height = std::cmp::min(self.autocomplete_suggestions.len(), 8) as u16 + 2;
+                let x = right_chunks[0].x + gutter_width + self.sql_editor_cursor_x as u16 + 1;
+                let y = (right_chunks[0].y + self.sql_editor_cursor_y as u16 + 2)
+                    .saturating_sub(self.sql_editor_scroll as u16);
+                let popup_area = Rect {
+                    x: std::cmp::min(x, size.width.saturating_sub(width)),
+                    y: std::cmp::min(y, size.height.saturating_sub(height)),
+                    width: std::cmp::min(width, size.width),
+                    height: std::cmp::min(height, size.height),
+                };
+
+                // Keep the selected suggestion inside the visible window
+                let visible = height.saturating_sub(2) as usize;
+                let offset = self.selected_suggestion.saturating_sub(visible.saturating_sub(1));
+                let items: Vec<ListItem> = self
+                    .autocomplete_suggestions
+                    .iter()
+                    .enumerate()
+                    .skip(offset)
+                    .take(visible)
+                    .map(|(i, suggestion)| {
+                        let style = if i == self.selected_suggestion {
+                            theme.selected()
+                        } else {
+                            Style::default().fg(theme.text)
+                        };
+                        ListItem::new(suggestion.clone()).style(style)
+                    })
+                    .collect();
+
+                let popup = List::new(items).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+                f.render_widget(Clear, popup_area);
+                f.render_widget(popup, popup_area);
+            }
+
+            if let ScreenState::CommandPalette = self.current_screen {
+                let commands = palette::filter(&self.command_palette_input);
+                let height = std::cmp::min(commands.len() as u16, 10) + 3;
+                let area = centered_rect(50, Rect {
+                    y: size.height / 4,
+                    height: std::cmp::min(height, size.height.saturating_sub(size.height / 4)),
+                    ..size
+                });
+
+                let palette_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+                    .split(area);
+
+                let input = Paragraph::new(format!("> {}", self.command_palette_input)).block(
+                    Block::default()
+                        .title("Commands")
+                        .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+
+                let visible = palette_chunks[1].height.saturating_sub(1) as usize;
+                let offset = self.selected_command.saturating_sub(visible.saturating_sub(1));
+                let items: Vec<ListItem> = commands
+                    .iter()
+                    .enumerate()
+                    .skip(offset)
+                    .take(visible)
+                    .map(|(i, command)| {
+                        let style = if i == self.selected_command {
+                            theme.selected()
+                        } else {
+                            Style::default().fg(theme.text)
+                        };
+                        ListItem::new(Line::from(vec![
+                            Span::raw(command.label()),
+                            Span::styled(
+                                format!("  {}", command.shortcut()),
+                                Style::default().fg(theme.muted),
+                            ),
+                        ]))
+                        .style(style)
+                    })
+                    .collect();
+                let list = List::new(items).block(
+                    Block::default()
+                        .borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+
+                f.set_cursor_position((
+                    palette_chunks[0].x + 3 + self.command_palette_input.chars().count() as u16,
+                    palette_chunks[0].y + 1,
+                ));
+                f.render_widget(Clear, area);
+                f.render_widget(input, palette_chunks[0]);
+                f.render_widget(list, palette_chunks[1]);
+            }
+
+            if let ScreenState::DeleteConfirmation = self.current_screen {
+                let area = centered_rect(50, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(3, size.height),
+                    ..size
+                });
+                let prompt = Paragraph::new(Line::from(vec![
+                    Span::raw(format!(
+                        "Delete row {} from {}? ",
+                        self.selected_result_row + 1,
+                        self.result_table.as_deref().unwrap_or_default()
+                    )),
+                    Span::styled("(y/n)", theme.bold_or(Style::default().fg(theme.key))),
+                ]))
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .title("Confirm delete")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.error)),
+                );
+                f.render_widget(Clear, area);
+                f.render_widget(prompt, area);
+            }
+
+            if let ScreenState::ExplainConfirmation = self.current_screen {
+                let area = centered_rect(50, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(3, size.height),
+                    ..size
+                });
+                let prompt = Paragraph::new(Line::from(vec![
+                    Span::raw("The statement will really run. Continue? "),
+                    Span::styled("(y/n)", theme.bold_or(Style::default().fg(theme.key))),
+                ]))
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .title("Confirm EXPLAIN ANALYZE")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.error)),
+                );
+                f.render_widget(Clear, area);
+                f.render_widget(prompt, area);
+            }
+
+            if let ScreenState::CellEdit = self.current_screen {
+                let area = centered_rect(60, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(3, size.height),
+                    ..size
+                });
+                let column = self
+                    .sql_query_result
+                    .first()
+                    .and_then(|row| row.keys().nth(self.selected_result_column))
+                    .cloned()
+                    .unwrap_or_default();
+                let editor = Paragraph::new(self.cell_edit_input.as_str()).block(
+                    Block::default()
+                        .title(format!(
+                            "Edit {}.{} - Enter to save, Esc to cancel, NULL for null",
+                            self.result_table.as_deref().unwrap_or_default(),
+                            column
+                        ))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+                f.set_cursor_position((
+                    area.x + 1 + self.cell_edit_input.chars().count() as u16,
+                    area.y + 1,
+                ));
+                f.render_widget(Clear, area);
+                f.render_widget(editor, area);
+            }
+
+            if let ScreenState::ParamPrompt = self.current_screen {
+                let area = centered_rect(50, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(3, size.height),
+                    ..size
+                });
+                let placeholder = if self.selected_db_type == 0 {
+                    format!("${}", self.query_params.len() + 1)
+                } else {
+                    format!("? #{}", self.query_params.len() + 1)
+                };
+                let prompt = Paragraph::new(self.param_input.as_str()).block(
+                    Block::default()
+                        .title(format!(
+                            "Value for {} ({}/{}) - Enter to confirm, Esc to cancel",
+                            placeholder,
+                            self.query_params.len() + 1,
+                            self.param_count
+                        ))
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+                f.set_cursor_position((
+                    area.x + 1 + self.param_input.chars().count() as u16,
+                    area.y + 1,
+                ));
+                f.render_widget(Clear, area);
+                f.render_widget(prompt, area);
+            }
+
+            if let ScreenState::ImportPrompt = self.current_screen {
+                let area = centered_rect(60, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(4, size.height),
+                    ..size
+                });
+                let prompt = Paragraph::new(vec![
+                    Line::from(format!("File: {}", self.import_path_input)),
+                    Line::from(format!("Into: {}", self.import_table_input)),
+                ])
+                .block(
+                    Block::default()
+                        .title("Import CSV - Tab switches field, Enter to load, Esc to cancel")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+                let (line, input) = if self.import_editing_table {
+                    (1, &self.import_table_input)
+                } else {
+                    (0, &self.import_path_input)
+                };
+                f.set_cursor_position((
+                    area.x + 7 + input.chars().count() as u16,
+                    area.y + 1 + line,
+                ));
+                f.render_widget(Clear, area);
+                f.render_widget(prompt, area);
+            }
+
+            if let ScreenState::SnippetName = self.current_screen {
+                let area = centered_rect(60, Rect {
+                    y: size.height / 3,
+                    height: std::cmp::min(3, size.height),
+                    ..size
+                });
+                let prompt = Paragraph::new(format!("Name: {}", self.snippet_input)).block(
+                    Block::default()
+                        .title("Save query - Enter to save, Esc to cancel")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+                f.set_cursor_position((area.x + 7 + self.snippet_input.chars().count() as u16, area.y + 1));
+                f.render_widget(Clear, area);
+                f.render_widget(prompt, area);
+            }
+
+            if let ScreenState::SnippetPicker = self.current_screen {
+                let names = self.snippet_names();
+                let height = std::cmp::min(names.len() as u16, 10) + 3;
+                let area = centered_rect(60, Rect {
+                    y: size.height / 4,
+                    height: std::cmp::min(height, size.height.saturating_sub(size.height / 4)),
+                    ..size
+                });
+
+                let picker_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+                    .split(area);
+
+                let input = Paragraph::new(format!("> {}", self.snippet_input)).block(
+                    Block::default()
+                        .title("Saved queries - Enter to load, Del to delete")
+                        .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+
+                let visible = picker_chunks[1].height.saturating_sub(1) as usize;
+                let offset = self.selected_snippet.saturating_sub(visible.saturating_sub(1));
+                let items: Vec<ListItem> = names
+                    .iter()
+                    .enumerate()
+                    .skip(offset)
+                    .take(visible)
+                    .map(|(i, name)| {
+                        let style = if i == self.selected_snippet {
+                            theme.selected()
+                        } else {
+                            Style::default().fg(theme.text)
+                        };
+                        // The first line of the query, to tell similar names apart
+                        let preview = self.snippets[name].lines().next().unwrap_or_default();
+                        ListItem::new(Line::from(vec![
+                            Span::raw(name.clone()),
+                            Span::styled(format!("  {}", preview), Style::default().fg(theme.muted)),
+                        ]))
+                        .style(style)
+                    })
+                    .collect();
+                let list = List::new(items).block(
+                    Block::default()
+                        .borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT)
+                        .border_style(Style::default().fg(theme.header)),
+                );
+
+                f.set_cursor_position((
+                    picker_chunks[0].x + 3 + self.snippet_input.chars().count() as u16,
+                    picker_chunks[0].y + 1,
+                ));
+                f.render_widget(Clear, area);
+                f.render_widget(input, picker_chunks[0]);
+                f.render_widget(list, picker_chunks[1]);
+            }
+
             let help_message = vec![Line::from(vec![
                 Span::styled(
                     "Tab",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(theme.key)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - navigate, "),
                 Span::styled(
                     "F5",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("/"),
                 Span::styled(
                     "Ctrl+E",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(theme.success)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - execute, "),
                 Span::styled(
                     "Ctrl+C",
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme.header)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - copy row, "),
                 Span::styled(
                     "Ctrl+A",
                     Style::default()
-                        .fg(Color::Cyan)
+                        .fg(theme.header)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - copy all, "),
+                Span::styled(
+                    "Ctrl+K",
+                    Style::default()
+                        .fg(theme.header)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - commands, "),
                 Span::styled(
                     "F12",
                     Style::default()
-                        .fg(Color::Magenta)
+                        .fg(theme.marker)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - debug, "),
                 Span::styled(
                     "F1",
                     Style::default()
-                        .fg(Color::Blue)
+                        .fg(theme.info)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - databases, "),
                 Span::styled(
                     "Esc",
                     Style::default()
-                        .fg(Color::Red)
+                        .fg(theme.error)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" - quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
-            f.render_widget(help_paragraph, chunks[1]);
+            let status_line = Line::from(vec![
+                Span::styled(
+                    format!(
+                        " {} ",
+                        self.server_version.as_deref().unwrap_or("Unknown server")
+                    ),
+                    theme.reversed_or(Style::default().fg(theme.background).bg(theme.header)),
+                ),
+                Span::raw(format!(
+                    " {}@{}:{}",
+                    self.connection_input.username,
+                    self.connection_input.hostname,
+                    self.connection_input.port
+                )),
+                Span::styled(
+                    self.current_database
+                        .as_deref()
+                        .map(|database| format!("/{}", database))
+                        .unwrap_or_default(),
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+            ]);
+            // A running export takes the right half of the status line for its progress bar
+            let status_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(if self.table_export.is_some() {
+                    [Constraint::Percentage(50), Constraint::Percentage(50)]
+                } else {
+                    [Constraint::Percentage(100), Constraint::Percentage(0)]
+                })
+                .split(chunks[1]);
+            f.render_widget(
+                Paragraph::new(status_line).style(Style::default().fg(theme.text)),
+                status_chunks[0],
+            );
+            if let Some(export) = &self.table_export {
+                f.render_widget(export_gauge(export, theme), status_chunks[1]);
+            }
+
+            f.render_widget(help_paragraph, chunks[2]);
+        })?;
+
+        if let Some((tables_area, sql_editor_area, sql_result_area)) = pane_areas {
+            self.tables_area = tables_area;
+            self.sql_editor_area = sql_editor_area;
+            self.sql_result_area = sql_result_area;
+        }
+
+        Ok(())
+    }
+
+    async fn render_cell_detail(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        let (title, value) = self.cell_detail.clone().unwrap_or_default();
+        let mut detail_area = Rect::default();
+
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(f.area());
+
+            let mut detail = Paragraph::new(value)
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_focused)),
+                )
+                .scroll((self.cell_detail_scroll, 0));
+            if self.cell_detail_wrap {
+                detail = detail.wrap(Wrap { trim: false });
+            }
+
+            detail_area = chunks[0];
+            f.render_widget(detail, chunks[0]);
+
+            let help_message = Line::from(vec![
+                Span::styled(
+                    "↑/↓ PgUp/PgDn",
+                    Style::default()
+                        .fg(theme.key)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - scroll, "),
+                Span::styled(
+                    "w",
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(if self.cell_detail_wrap {
+                    " - disable wrap, "
+                } else {
+                    " - enable wrap, "
+                }),
+                Span::styled(
+                    "Esc",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - back"),
+            ]);
+
+            f.render_widget(
+                Paragraph::new(help_message).alignment(Alignment::Center),
+                chunks[1],
+            );
+        })?;
+
+        self.cell_detail_area = detail_area;
+
+        Ok(())
+    }
+
+    async fn render_sessions_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        if self.needs_sessions_refresh {
+            self.needs_sessions_refresh = false;
+            self.refresh_sessions().await;
+        }
+
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(f.area());
+
+            // Keep the selected session in view
+            let visible = (chunks[0].height as usize).saturating_sub(4).max(1);
+            let first = self.selected_session.saturating_sub(visible - 1);
+            let rows: Vec<Row> = self
+                .sessions
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(visible)
+                .map(|(i, session)| {
+                    let style = if i == self.selected_session {
+                        theme.selected()
+                    } else {
+                        Style::default()
+                    };
+                    Row::new(vec![
+                        Cell::from(session.pid.to_string()),
+                        Cell::from(session.user.clone().unwrap_or_default()),
+                        Cell::from(session.state.clone().unwrap_or_default()),
+                        Cell::from(session.seconds.map(|s| format!("{}s", s)).unwrap_or_default()),
+                        Cell::from(session.query.as_deref().unwrap_or_default().replace('\n', " ")),
+                    ])
+                    .style(style)
+                })
+                .collect();
+
+            let title = match &self.sessions_message {
+                Some(message) => format!("Active sessions ({}) - {}", self.sessions.len(), message),
+                None => format!("Active sessions ({})", self.sessions.len()),
+            };
+            let widths = [
+                Constraint::Length(8),
+                Constraint::Length(12),
+                Constraint::Length(20),
+                Constraint::Length(8),
+                Constraint::Min(20),
+            ];
+            let table = Table::new(rows, widths)
+                .header(
+                    Row::new(vec!["PID", "User", "State", "Time", "Query"])
+                        .style(Style::default().fg(theme.header).add_modifier(Modifier::BOLD))
+                        .bottom_margin(1),
+                )
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_focused)),
+                )
+                .column_spacing(1);
+            f.render_widget(table, chunks[0]);
+
+            let help_message = match self.pending_session_kill {
+                Some(pid) => Line::from(vec![
+                    Span::raw(format!("Terminate session {}? ", pid)),
+                    Span::styled("(y/n)", theme.bold_or(Style::default().fg(theme.key))),
+                ]),
+                None => Line::from(vec![
+                    Span::styled(
+                        "↑/↓",
+                        Style::default().fg(theme.key).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" - select, "),
+                    Span::styled(
+                        "k",
+                        Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" - terminate, "),
+                    Span::styled(
+                        "r",
+                        Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" - refresh, "),
+                    Span::styled(
+                        "Esc",
+                        Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" - back"),
+                ]),
+            };
+            f.render_widget(
+                Paragraph::new(help_message).alignment(Alignment::Center),
+                chunks[1],
+            );
+        })?;
+
+        Ok(())
+    }
+
+    async fn render_table_stats_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        if self.needs_table_stats_refresh {
+            self.needs_table_stats_refresh = false;
+            self.refresh_table_stats().await;
+        }
+
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(f.area());
+
+            let count = |n: u64| format_number(&n.to_string(), true, None);
+            let rows: Vec<Row> = self
+                .table_stats
+                .iter()
+                .flat_map(|stats| &stats.columns)
+                .map(|column| {
+                    Row::new(vec![
+                        Cell::from(column.name.clone()),
+                        Cell::from(column.data_type.clone()),
+                        Cell::from(count(column.null_count)),
+                        Cell::from(column.distinct_count.map(count).unwrap_or_else(|| "-".to_string())),
+                        Cell::from(column.min.clone().unwrap_or_default()),
+                        Cell::from(column.max.clone().unwrap_or_default()),
+                    ])
+                })
+                .collect();
+
+            let table_name = self.table_stats_table.as_deref().unwrap_or_default();
+            let title = match (&self.table_stats, &self.table_stats_message) {
+                (_, Some(message)) => format!("Column stats: {} - {}", table_name, message),
+                (Some(stats), None) => format!("Column stats: {} ({} rows)", table_name, count(stats.row_count)),
+                (None, None) => format!("Column stats: {}", table_name),
+            };
+            let widths = [
+                Constraint::Min(16),
+                Constraint::Length(16),
+                Constraint::Length(12),
+                Constraint::Length(12),
+                Constraint::Length(16),
+                Constraint::Length(16),
+            ];
+            let table = Table::new(rows, widths)
+                .header(
+                    Row::new(vec!["Column", "Type", "Nulls", "Distinct", "Min", "Max"])
+                        .style(Style::default().fg(theme.header).add_modifier(Modifier::BOLD))
+                        .bottom_margin(1),
+                )
+                .block(
+                    Block::default()
+                        .title(title)
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_focused)),
+                )
+                .column_spacing(1);
+            f.render_widget(table, chunks[0]);
+
+            let help_message = Line::from(vec![
+                Span::styled(
+                    "r",
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - analyze again, "),
+                Span::styled(
+                    "Esc",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - back"),
+            ]);
+            f.render_widget(
+                Paragraph::new(help_message).alignment(Alignment::Center),
+                chunks[1],
+            );
+        })?;
+
+        Ok(())
+    }
+
+    async fn render_help_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let theme = self.render_theme();
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                .split(f.area());
+
+            let key_width = help::KEYBINDINGS
+                .iter()
+                .flat_map(|(_, bindings)| bindings.iter())
+                .map(|(keys, _)| keys.chars().count())
+                .max()
+                .unwrap_or_default();
+            let mut lines = Vec::with_capacity(help::line_count());
+            for (context, bindings) in help::KEYBINDINGS {
+                lines.push(Line::from(Span::styled(
+                    context,
+                    Style::default().fg(theme.header).add_modifier(Modifier::BOLD),
+                )));
+                for (keys, description) in bindings {
+                    lines.push(Line::from(vec![
+                        Span::styled(
+                            format!("  {:<width$}  ", keys, width = key_width),
+                            theme.bold_or(Style::default().fg(theme.key)),
+                        ),
+                        Span::raw(*description),
+                    ]));
+                }
+                lines.push(Line::default());
+            }
+
+            let reference = Paragraph::new(lines)
+                .scroll((self.help_scroll as u16, 0))
+                .block(
+                    Block::default()
+                        .title("Keybindings")
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.border_focused)),
+                );
+            f.render_widget(reference, chunks[0]);
+
+            let help_message = Line::from(vec![
+                Span::styled(
+                    "↑/↓ PgUp/PgDn",
+                    Style::default().fg(theme.key).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - scroll, "),
+                Span::styled(
+                    "Esc",
+                    Style::default().fg(theme.error).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" - back"),
+            ]);
+            f.render_widget(
+                Paragraph::new(help_message).alignment(Alignment::Center),
+                chunks[1],
+            );
         })?;
 
         Ok(())
@@ -946,6 +1950,7 @@ impl UIRenderer for DatabaseClientUI {
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
         table_schema: &TableSchema,
     ) -> io::Result<()> {
+        let theme = self.render_theme();
         terminal.draw(|f| {
             let size = f.area();
 
@@ -957,11 +1962,7 @@ impl UIRenderer for DatabaseClientUI {
                 .columns
                 .iter()
                 .map(|col| {
-                    let col_info = format!(
-                        "{}: {} (Nullable: {}, Default: {:?})",
-                        col.name, col.data_type, col.is_nullable, col.default
-                    );
-                    ListItem::new(col_info).style(Style::default().fg(Color::White))
+                    ListItem::new(column_summary(col)).style(Style::default().fg(theme.text))
                 })
                 .collect();
 
@@ -974,6 +1975,211 @@ impl UIRenderer for DatabaseClientUI {
     }
 }
 
+fn column_summary(column: &ColumnSchema) -> String {
+    format!(
+        "{}: {} (Nullable: {}, Default: {})",
+        column.name,
+        column.data_type,
+        if column.is_nullable { "YES" } else { "NO" },
+        column.default.as_deref().unwrap_or("NULL")
+    )
+}
+
+/// Splits the editor content into lines, marking the token starting at `error_position`
+/// (line, column) so a syntax error is visible in place.
+fn highlight_error_token(
+    content: &str,
+    error_position: Option<(usize, usize)>,
+    theme: Theme,
+) -> Text<'_> {
+    let lines = content.split('\n').enumerate().map(|(y, line)| match error_position {
+        Some((error_y, error_x)) if error_y == y => {
+            let start = line.char_indices().nth(error_x).map_or(line.len(), |(i, _)| i);
+            let token_len = line[start..]
+                .char_indices()
+                .find(|(_, c)| !(c.is_alphanumeric() || *c == '_'))
+                .map_or(line.len() - start, |(i, _)| i);
+            // Punctuation errors still get a one character marker
+            let end = if token_len == 0 {
+                line[start..].chars().next().map_or(start, |c| start + c.len_utf8())
+            } else {
+                start + token_len
+            };
+            Line::from(vec![
+                Span::raw(&line[..start]),
+                Span::styled(
+                    &line[start..end],
+                    theme
+                        .reversed_or(Style::default().fg(theme.text).bg(theme.error))
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(&line[end..]),
+            ])
+        }
+        _ => Line::raw(line),
+    });
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+/// A column is numeric when it has at least one value and every non-NULL value parses
+/// as a number.
+fn is_numeric_column<'a>(values: impl Iterator<Item = &'a str>) -> bool {
+    let mut has_value = false;
+    for value in values.filter(|v| *v != "NULL") {
+        if value.parse::<f64>().is_err() {
+            return false;
+        }
+        has_value = true;
+    }
+    has_value
+}
+
+/// Whether the sampled values are all `true`/`false` (ignoring NULLs) and at least one
+/// is set. With `allow_digits`, `0` and `1` count too.
+fn is_boolean_column<'a>(values: impl Iterator<Item = &'a str>, allow_digits: bool) -> bool {
+    let mut has_value = false;
+    for value in values.filter(|v| *v != "NULL") {
+        let is_digit = allow_digits && (value == "0" || value == "1");
+        if value != "true" && value != "false" && !is_digit {
+            return false;
+        }
+        has_value = true;
+    }
+    has_value
+}
+
+/// One result row as `column: value` lines with the values lined up, like psql's expanded
+/// display. Also returns the line where the selected column starts.
+fn record_lines(row: &IndexMap<String, String>, selected_column: usize, theme: Theme) -> (Vec<Line<'static>>, usize) {
+    let label_width = row.keys().map(|name| name.chars().count()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    for (i, (name, value)) in row.iter().enumerate() {
+        if i == selected_column {
+            selected_line = lines.len();
+        }
+        let style = if i == selected_column {
+            theme.selected()
+        } else {
+            Style::default().fg(theme.text)
+        };
+        // Lines after the first of a multi-line value are indented under it
+        for (n, part) in value.split('\n').enumerate() {
+            let label = if n == 0 {
+                format!("{:<width$} : ", name, width = label_width)
+            } else {
+                " ".repeat(label_width + 3)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.header)),
+                Span::raw(visible_control_chars(part).into_owned()),
+            ]).style(style));
+        }
+    }
+    (lines, selected_line)
+}
+
+/// `value` with control characters other than line breaks drawn as their Unicode
+/// control pictures (␛, ␉, ...), so they can't move the cursor or restyle the terminal.
+fn visible_control_chars(value: &str) -> Cow<'_, str> {
+    if !value.chars().any(|c| c.is_control() && c != '\n') {
+        return Cow::Borrowed(value);
+    }
+    value
+        .chars()
+        .map(|c| match c {
+            '\n' => c,
+            '\u{7f}' => '\u{2421}',
+            c if (c as u32) < 0x20 => char::from_u32(0x2400 + c as u32).unwrap_or('\u{fffd}'),
+            c if c.is_control() => '\u{fffd}',
+            c => c,
+        })
+        .collect()
+}
+
+/// Returns the part of `value` that fits in `width` columns, leaving room for a
+/// truncation marker, or `None` when the whole single-line value fits.
+fn truncate_to_width(value: &str, width: usize) -> Option<String> {
+    let first_line = value.lines().next().unwrap_or_default();
+    if first_line.len() == value.len() && value.chars().count() <= width {
+        return None;
+    }
+    Some(first_line.chars().take(width.saturating_sub(1)).collect())
+}
+
+/// Splits `value` into lines of at most `width` characters, keeping its own line breaks.
+fn wrap_to_width(value: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    value
+        .lines()
+        .flat_map(|line| {
+            let chars: Vec<char> = line.chars().collect();
+            if chars.is_empty() {
+                return vec![String::new()];
+            }
+            chars.chunks(width).map(|chunk| chunk.iter().collect()).collect()
+        })
+        .collect()
+}
+
+/// Suffix for a list title showing its filter, with a cursor while it is being typed.
+fn filter_title(filter: &str, editing: bool) -> String {
+    match (filter.is_empty(), editing) {
+        (true, false) => String::new(),
+        (_, true) => format!(" /{}▏", filter),
+        (false, false) => format!(" /{}", filter),
+    }
+}
+
+/// Progress bar for a running table export, filled against the server's row estimate.
+fn export_gauge(export: &TableExport, theme: Theme) -> Gauge<'static> {
+    Gauge::default()
+        .gauge_style(Style::default().fg(theme.success).bg(theme.background))
+        .ratio(export.progress_ratio().unwrap_or(0.0))
+        .label(export_progress_label(export))
+        .use_unicode(true)
+}
+
+fn export_progress_label(export: &TableExport) -> String {
+    match export.progress_ratio() {
+        Some(ratio) => format!(
+            "{} {}/~{} rows ({:.0}%) - c cancels",
+            export.path.display(),
+            export.rows_written(),
+            export.estimated_rows().unwrap_or_default(),
+            ratio * 100.0
+        ),
+        None => format!(
+            "{} {} rows - c cancels",
+            export.path.display(),
+            export.rows_written()
+        ),
+    }
+}
+
+/// Formats a numeric cell for display, optionally rounding floats and grouping digits.
+fn format_number(value: &str, group_digits: bool, float_precision: Option<usize>) -> String {
+    let is_integer = value.trim_start_matches('-').chars().all(|c| c.is_ascii_digit());
+    let value = match (float_precision, value.parse::<f64>()) {
+        (Some(precision), Ok(number)) if !is_integer => format!("{:.*}", precision, number),
+        _ => value.to_string(),
+    };
+    if !group_digits || value.contains(['e', 'E']) {
+        return value;
+    }
+
+    let (sign, unsigned) = value.split_at(if value.starts_with('-') { 1 } else { 0 });
+    let (integer, fraction) = unsigned.split_at(unsigned.find('.').unwrap_or(unsigned.len()));
+    let mut grouped = String::with_capacity(value.len() + integer.len() / 3);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
 fn centered_rect(percent_x: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -989,3 +2195,88 @@ fn centered_rect(percent_x: u16, r: Rect) -> Rect {
 
     popup_layout[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_control_chars() {
+        assert!(matches!(visible_control_chars("Привет 👋\nnext"), Cow::Borrowed(_)));
+        assert_eq!(visible_control_chars("a\tb\x1b[31m\r\n"), "a␉b␛[31m␍\n");
+        assert_eq!(visible_control_chars("del\x7f\u{85}"), "del␡\u{fffd}");
+    }
+
+    #[test]
+    fn test_record_lines_line_up_values() {
+        let row: IndexMap<String, String> = [("id", "7"), ("note", "first\nsecond"), ("status", "paid")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let (lines, selected_line) = record_lines(&row, 2, Theme::DARK);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text, vec!["id     : 7", "note   : first", "         second", "status : paid"]);
+        assert_eq!(selected_line, 3);
+    }
+
+    #[test]
+    fn test_export_progress_label_uses_estimate_when_known() {
+        let export = TableExport::new("events".to_string(), "events.csv".into());
+        export.progress().fetch_add(250, std::sync::atomic::Ordering::Relaxed);
+        assert_eq!(export_progress_label(&export), "events.csv 250 rows - c cancels");
+
+        export.set_estimated_rows(1000);
+        assert_eq!(export_progress_label(&export), "events.csv 250/~1000 rows (25%) - c cancels");
+
+        // The estimate was low; the bar stays full instead of overflowing
+        export.set_estimated_rows(100);
+        assert_eq!(export.progress_ratio(), Some(1.0));
+    }
+
+    #[test]
+    fn test_truncate_to_width_marks_cut_values() {
+        assert_eq!(truncate_to_width("short", 8), None);
+        assert_eq!(truncate_to_width("a longer value", 8), Some("a longe".to_string()));
+        assert_eq!(truncate_to_width("two\nlines", 8), Some("two".to_string()));
+    }
+
+    #[test]
+    fn test_wrap_to_width() {
+        assert_eq!(wrap_to_width("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(wrap_to_width("ab\ncd", 8), vec!["ab", "cd"]);
+    }
+
+    #[test]
+    fn test_is_numeric_column() {
+        assert!(is_numeric_column(["1", "NULL", "-2.5"].into_iter()));
+        assert!(!is_numeric_column(["1", "abc"].into_iter()));
+        assert!(!is_numeric_column(["NULL", "NULL"].into_iter()));
+    }
+
+    #[test]
+    fn test_is_boolean_column() {
+        assert!(is_boolean_column(["true", "NULL", "false"].into_iter(), false));
+        assert!(!is_boolean_column(["true", "yes"].into_iter(), false));
+        assert!(!is_boolean_column(["NULL"].into_iter(), false));
+        assert!(!is_boolean_column(["0", "1"].into_iter(), false));
+        assert!(is_boolean_column(["0", "1", "NULL"].into_iter(), true));
+        assert!(!is_boolean_column(["0", "2"].into_iter(), true));
+        assert_eq!(BoolDisplay::Symbols.render(false), "✗");
+        assert_eq!(BoolDisplay::Letters.next(), BoolDisplay::Text);
+    }
+
+    #[test]
+    fn test_format_number_groups_digits() {
+        assert_eq!(format_number("1234567", true, None), "1,234,567");
+        assert_eq!(format_number("-1234.5", true, None), "-1,234.5");
+        assert_eq!(format_number("999", true, None), "999");
+        assert_eq!(format_number("1234567", false, None), "1234567");
+    }
+
+    #[test]
+    fn test_format_number_rounds_floats_only() {
+        assert_eq!(format_number("12345.6789000001", true, Some(2)), "12,345.68");
+        assert_eq!(format_number("42", false, Some(2)), "42");
+        assert_eq!(format_number("1e21", true, None), "1e21");
+    }
+}
@@ -0,0 +1,193 @@
+use crate::db::{sql_spans, SqlSpan};
+
+// Words written in upper case when the editor's SQL is formatted
+pub const KEYWORDS: [&str; 70] = [
+    "SELECT", "FROM", "WHERE", "AND", "OR", "NOT", "IN", "IS", "NULL", "LIKE", "ILIKE", "BETWEEN",
+    "AS", "ON", "USING", "JOIN", "LEFT", "RIGHT", "INNER", "OUTER", "FULL", "CROSS", "NATURAL",
+    "GROUP", "BY", "ORDER", "HAVING", "LIMIT", "OFFSET", "UNION", "INTERSECT", "EXCEPT", "ALL",
+    "DISTINCT", "INSERT", "INTO", "VALUES", "UPDATE", "SET", "DELETE", "RETURNING", "WITH",
+    "RECURSIVE", "CASE", "WHEN", "THEN", "ELSE", "END", "ASC", "DESC", "EXISTS", "ANY", "CREATE",
+    "ALTER", "DROP", "TABLE", "VIEW", "TRUNCATE", "PRIMARY", "FOREIGN", "REFERENCES",
+    "CONSTRAINT", "DEFAULT", "UNIQUE", "TRUE", "FALSE", "EXPLAIN", "ANALYZE", "CALL", "CONFLICT",
+];
+
+// Keywords that start a new line when they appear outside parentheses
+const CLAUSES: [&str; 23] = [
+    "SELECT", "FROM", "WHERE", "GROUP", "ORDER", "HAVING", "LIMIT", "OFFSET", "UNION", "INTERSECT",
+    "EXCEPT", "VALUES", "SET", "RETURNING", "WITH", "INSERT", "UPDATE", "DELETE", "JOIN", "LEFT",
+    "RIGHT", "INNER", "FULL",
+];
+
+// Words after which a clause keyword continues the line, as in `LEFT JOIN`, `DELETE FROM`
+// or `ON CONFLICT DO UPDATE`
+const CONTINUES_AFTER: [&str; 12] = [
+    "LEFT", "RIGHT", "INNER", "FULL", "OUTER", "CROSS", "NATURAL", "DELETE", "DISTINCT", "FOR",
+    "DO", "ON",
+];
+
+enum Token<'a> {
+    Word(&'a str),
+    /// String literal, quoted identifier, dollar-quoted body or comment, copied as written
+    Verbatim(&'a str),
+    Space,
+    Other(char),
+}
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+fn tokens(sql: &str, mysql: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for (kind, range) in sql_spans(sql, mysql) {
+        let text = &sql[range];
+        if kind != SqlSpan::Code {
+            tokens.push(Token::Verbatim(text));
+            continue;
+        }
+        let mut chars = text.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                tokens.push(Token::Space);
+            } else if is_word_char(c) {
+                while chars.next_if(|(_, c)| is_word_char(*c)).is_some() {}
+                let end = chars.peek().map_or(text.len(), |(i, _)| *i);
+                tokens.push(Token::Word(&text[start..end]));
+            } else {
+                tokens.push(Token::Other(c));
+            }
+        }
+    }
+    tokens
+}
+
+/// Where the keyword `sql` ends with starts. Words inside string literals, quoted names
+/// and comments don't count, nor does one after a `.`, as in `t.order`. `mysql` is as
+/// for [`sql_spans`].
+pub fn trailing_keyword(sql: &str, mysql: bool) -> Option<usize> {
+    let tokens = tokens(sql, mysql);
+    match tokens.as_slice() {
+        [.., Token::Other('.'), Token::Word(_)] => None,
+        [.., Token::Word(word)] if is_keyword(word) => Some(sql.len() - word.len()),
+        _ => None,
+    }
+}
+
+/// Starts a new line in `out` with `indent` spaces, dropping the space before it.
+fn new_line(out: &mut String, indent: usize) {
+    out.truncate(out.trim_end_matches(' ').len());
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&" ".repeat(indent));
+}
+
+/// Lays `sql` out one clause per line with keywords in upper case. Conditions joined by
+/// AND/OR get a line each; anything inside parentheses stays on its line. String
+/// literals, quoted names and comments are kept as written. `mysql` is as for
+/// [`sql_spans`].
+pub fn format_sql(sql: &str, mysql: bool) -> String {
+    let tokens = tokens(sql, mysql);
+    let mut out = String::new();
+    let mut depth = 0usize;
+    let mut previous_word = String::new();
+    let mut in_between = false;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Space => {
+                if !out.is_empty() && !out.ends_with([' ', '\n']) {
+                    out.push(' ');
+                }
+            }
+            Token::Verbatim(text) => {
+                out.push_str(text);
+                if text.starts_with("--") {
+                    out.push('\n');
+                }
+            }
+            Token::Other(c) => {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                out.push(*c);
+                if *c == ';' {
+                    out.push_str("\n\n");
+                }
+            }
+            Token::Word(word) => {
+                let upper = word.to_ascii_uppercase();
+                let next_is_word = matches!(
+                    tokens[i + 1..].iter().find(|token| !matches!(token, Token::Space)),
+                    Some(Token::Word(_))
+                );
+                if depth == 0 {
+                    // LEFT and RIGHT are also functions, which a parenthesis follows
+                    let is_clause = CLAUSES.contains(&upper.as_str())
+                        && !CONTINUES_AFTER.contains(&previous_word.as_str())
+                        && (!matches!(upper.as_str(), "LEFT" | "RIGHT" | "INNER" | "FULL") || next_is_word);
+                    if is_clause {
+                        new_line(&mut out, 0);
+                    } else if matches!(upper.as_str(), "AND" | "OR") && !in_between {
+                        new_line(&mut out, 2);
+                    }
+                }
+                match upper.as_str() {
+                    "BETWEEN" => in_between = true,
+                    "AND" => in_between = false,
+                    _ => {}
+                }
+                if is_keyword(word) {
+                    out.push_str(&upper);
+                } else {
+                    out.push_str(word);
+                }
+                previous_word = upper;
+            }
+        }
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_sql_breaks_clauses_and_uppercases_keywords() {
+        let sql = "select id, left(name, 3)  from users u left join orders o on o.user_id = u.id \
+                   where u.age between 18 and 30 and o.note = 'where and' -- keep\n order by id;";
+        assert_eq!(
+            format_sql(sql, false),
+            "SELECT id, LEFT(name, 3)\nFROM users u\nLEFT JOIN orders o ON o.user_id = u.id\n\
+             WHERE u.age BETWEEN 18 AND 30\n  AND o.note = 'where and' -- keep\nORDER BY id;"
+        );
+    }
+
+    #[test]
+    fn test_trailing_keyword_skips_literals_and_qualified_names() {
+        assert_eq!(trailing_keyword("select", false), Some(0));
+        assert_eq!(trailing_keyword("SELECT id from", false), Some(10));
+        assert_eq!(trailing_keyword("SELECT o.order", false), None);
+        assert_eq!(trailing_keyword("SELECT 'a from", false), None);
+        assert_eq!(trailing_keyword("SELECT 1 -- from", false), None);
+        assert_eq!(trailing_keyword("SELECT selected", false), None);
+        // Only MySQL reads the backslash as escaping the closing quote
+        assert_eq!(trailing_keyword("SELECT 'C:\\' from", false), Some(13));
+        assert_eq!(trailing_keyword("SELECT 'C:\\' from", true), None);
+    }
+
+    #[test]
+    fn test_format_sql_keeps_subqueries_on_their_line() {
+        assert_eq!(
+            format_sql("DELETE FROM t WHERE id IN (select id from old)", false),
+            "DELETE FROM t\nWHERE id IN (SELECT id FROM old)"
+        );
+    }
+}
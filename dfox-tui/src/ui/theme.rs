@@ -0,0 +1,155 @@
+use ratatui::style::{Color, Modifier, Style};
+
+/// Colors the UI draws with, by role.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub text: Color,
+    /// The terminal's background; text drawn on a highlight uses it.
+    pub background: Color,
+    pub border: Color,
+    pub border_focused: Color,
+    /// Background of the selected list entry or result row.
+    pub highlight: Color,
+    /// Table headers, popup borders and the connection in the status line.
+    pub header: Color,
+    /// Keys in the help lines and y/n prompts.
+    pub key: Color,
+    pub error: Color,
+    pub success: Color,
+    pub info: Color,
+    /// Placeholders and hints in empty lists.
+    pub muted: Color,
+    /// Column lines of an expanded table.
+    pub secondary: Color,
+    /// Truncated cell marker and the debug key.
+    pub marker: Color,
+    /// Result rows changed since the snapshot.
+    pub changed: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        text: Color::White,
+        background: Color::Black,
+        border: Color::White,
+        border_focused: Color::Yellow,
+        highlight: Color::Yellow,
+        header: Color::Cyan,
+        key: Color::Yellow,
+        error: Color::Red,
+        success: Color::Green,
+        info: Color::Blue,
+        muted: Color::DarkGray,
+        secondary: Color::Gray,
+        marker: Color::Magenta,
+        changed: Color::LightYellow,
+    };
+
+    /// For terminals with a light background, where yellow and cyan text is hard to read.
+    pub const LIGHT: Theme = Theme {
+        text: Color::Black,
+        background: Color::White,
+        border: Color::DarkGray,
+        border_focused: Color::Blue,
+        highlight: Color::Blue,
+        header: Color::Magenta,
+        key: Color::Blue,
+        error: Color::Red,
+        success: Color::Green,
+        info: Color::Blue,
+        muted: Color::Gray,
+        secondary: Color::DarkGray,
+        marker: Color::Magenta,
+        changed: Color::Rgb(176, 112, 0),
+    };
+
+    /// Terminal default colors only, for NO_COLOR; emphasis falls back to bold and
+    /// reverse video.
+    pub const MONOCHROME: Theme = Theme {
+        text: Color::Reset,
+        background: Color::Reset,
+        border: Color::Reset,
+        border_focused: Color::Reset,
+        highlight: Color::Reset,
+        header: Color::Reset,
+        key: Color::Reset,
+        error: Color::Reset,
+        success: Color::Reset,
+        info: Color::Reset,
+        muted: Color::Reset,
+        secondary: Color::Reset,
+        marker: Color::Reset,
+        changed: Color::Reset,
+    };
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(Theme::DARK),
+            "light" => Some(Theme::LIGHT),
+            _ => None,
+        }
+    }
+
+    pub fn has_colors(&self) -> bool {
+        *self != Theme::MONOCHROME
+    }
+
+    /// `style`, or reverse video when there are no colors to set it apart.
+    pub fn reversed_or(&self, style: Style) -> Style {
+        if self.has_colors() {
+            style
+        } else {
+            Style::default().add_modifier(Modifier::REVERSED)
+        }
+    }
+
+    /// `style`, or bold when there are no colors to set it apart.
+    pub fn bold_or(&self, style: Style) -> Style {
+        if self.has_colors() {
+            style
+        } else {
+            Style::default().add_modifier(Modifier::BOLD)
+        }
+    }
+
+    /// Style of the selected entry in a list, table or menu.
+    pub fn selected(&self) -> Style {
+        self.reversed_or(Style::default().bg(self.highlight).fg(self.background))
+    }
+
+    /// Border style for a pane, depending on whether it has focus.
+    pub fn border_style(&self, focused: bool) -> Style {
+        if focused {
+            self.bold_or(Style::default().fg(self.border_focused))
+        } else {
+            Style::default().fg(self.border)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_from_name() {
+        assert_eq!(Theme::from_name("Light"), Some(Theme::LIGHT));
+        assert_eq!(Theme::from_name("dark"), Some(Theme::DARK));
+        assert_eq!(Theme::from_name("solarized"), None);
+    }
+
+    #[test]
+    fn test_monochrome_uses_modifiers_for_emphasis() {
+        let theme = Theme::MONOCHROME;
+        assert_eq!(theme.selected(), Style::default().add_modifier(Modifier::REVERSED));
+        assert_eq!(theme.border_style(true), Style::default().add_modifier(Modifier::BOLD));
+        assert_eq!(theme.border_style(false), Style::default().fg(Color::Reset));
+        assert_eq!(Theme::DARK.selected(), Style::default().bg(Color::Yellow).fg(Color::Black));
+    }
+}